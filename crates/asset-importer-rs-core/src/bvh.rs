@@ -0,0 +1,695 @@
+use asset_importer_rs_scene::{AiAABB, AiReal, AiScene, AiVector3D};
+
+/// Maximum number of triangles stored in a single leaf of the BVH.
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// A triangle of the scene in world space.
+///
+/// Polygons are split into a fan of triangles, which all share the index of the face
+/// they were created from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhTriangle {
+    /// Index of the node instancing the mesh.
+    pub node_index: usize,
+    /// Index of the mesh in the scene.
+    pub mesh_index: usize,
+    /// Index of the face within the mesh.
+    pub face_index: usize,
+    /// Indices of the triangle corners into the mesh's vertices.
+    pub indices: [usize; 3],
+    /// Positions of the triangle corners in world space.
+    pub positions: [AiVector3D; 3],
+}
+
+impl BvhTriangle {
+    fn aabb(&self) -> AiAABB {
+        AiAABB::from_points(&self.positions)
+    }
+
+    fn centroid(&self) -> AiVector3D {
+        (self.positions[0] + self.positions[1] + self.positions[2]) * (1.0 / 3.0)
+    }
+
+    /// Returns the point on the triangle for the given barycentric coordinates.
+    pub fn interpolate(&self, barycentric: &AiVector3D) -> AiVector3D {
+        self.positions[0] * barycentric.x
+            + self.positions[1] * barycentric.y
+            + self.positions[2] * barycentric.z
+    }
+}
+
+/// The closest intersection of a ray with the scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhRayHit {
+    /// Index of the hit triangle, see [`SceneBvh::triangles`].
+    pub triangle_index: usize,
+    pub node_index: usize,
+    pub mesh_index: usize,
+    pub face_index: usize,
+    /// Weights of the three triangle corners at the hit point.
+    pub barycentric: AiVector3D,
+    /// Distance along the ray, in multiples of the ray direction's length.
+    pub distance: AiReal,
+    /// Hit point in world space.
+    pub position: AiVector3D,
+}
+
+/// The point of the scene closest to a query point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhClosestPoint {
+    /// Index of the closest triangle, see [`SceneBvh::triangles`].
+    pub triangle_index: usize,
+    pub node_index: usize,
+    pub mesh_index: usize,
+    pub face_index: usize,
+    /// Weights of the three triangle corners at the closest point.
+    pub barycentric: AiVector3D,
+    /// Distance between the query point and the closest point.
+    pub distance: AiReal,
+    /// Closest point in world space.
+    pub position: AiVector3D,
+}
+
+#[derive(Debug, Clone)]
+enum BvhNodeKind {
+    Leaf { start: usize, count: usize },
+    Inner { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    aabb: AiAABB,
+    kind: BvhNodeKind,
+}
+
+/// A bounding volume hierarchy over all triangles of a scene in world space.
+///
+/// Every mesh is placed once for each node referencing it, using the node's global transform.
+/// Points and lines are ignored, polygons are split into triangle fans.
+///
+/// # Example
+///
+/// ```rust
+/// use asset_importer_rs_core::SceneBvh;
+/// use asset_importer_rs_scene::{AiMesh, AiNode, AiNodeTree, AiReal, AiScene, AiVector3D};
+///
+/// let scene = AiScene {
+///     nodes: AiNodeTree {
+///         root: Some(0),
+///         arena: vec![AiNode { mesh_indexes: vec![0], ..Default::default() }],
+///     },
+///     meshes: vec![AiMesh {
+///         vertices: vec![
+///             AiVector3D::new(-1.0, -1.0, 0.0),
+///             AiVector3D::new(1.0, -1.0, 0.0),
+///             AiVector3D::new(0.0, 1.0, 0.0),
+///         ],
+///         faces: vec![vec![0, 1, 2]],
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let bvh = SceneBvh::new(&scene);
+/// let hit = bvh
+///     .ray_cast(AiVector3D::new(0.0, 0.0, 5.0), AiVector3D::new(0.0, 0.0, -1.0), AiReal::MAX)
+///     .unwrap();
+/// assert_eq!(hit.distance, 5.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SceneBvh {
+    triangles: Vec<BvhTriangle>,
+    nodes: Vec<BvhNode>,
+}
+
+impl SceneBvh {
+    /// Builds the hierarchy over all triangles of the scene.
+    pub fn new(scene: &AiScene) -> Self {
+        let mut triangles = Vec::new();
+        for (node_index, node) in scene.nodes.arena.iter().enumerate() {
+            if node.mesh_indexes.is_empty() {
+                continue;
+            }
+            let Some(transform) = scene.nodes.global_transform(node_index) else {
+                continue;
+            };
+            for &mesh_index in &node.mesh_indexes {
+                let Some(mesh) = scene.meshes.get(mesh_index) else {
+                    continue;
+                };
+                let positions: Vec<AiVector3D> = mesh
+                    .vertices
+                    .iter()
+                    .map(|vertex| transform.transform_point(vertex))
+                    .collect();
+                for (face_index, face) in mesh.faces.iter().enumerate() {
+                    if face.len() < 3 || face.iter().any(|&index| index >= positions.len()) {
+                        continue;
+                    }
+                    for corner in 1..face.len() - 1 {
+                        let indices = [face[0], face[corner], face[corner + 1]];
+                        triangles.push(BvhTriangle {
+                            node_index,
+                            mesh_index,
+                            face_index,
+                            indices,
+                            positions: indices.map(|index| positions[index]),
+                        });
+                    }
+                }
+            }
+        }
+        Self::from_triangles(triangles)
+    }
+
+    /// Builds the hierarchy over the given triangles.
+    pub fn from_triangles(triangles: Vec<BvhTriangle>) -> Self {
+        let mut bvh = SceneBvh {
+            triangles,
+            nodes: Vec::new(),
+        };
+        if bvh.triangles.is_empty() {
+            return bvh;
+        }
+        let bounds: Vec<(AiAABB, AiVector3D)> = bvh
+            .triangles
+            .iter()
+            .map(|triangle| (triangle.aabb(), triangle.centroid()))
+            .collect();
+        let mut order: Vec<usize> = (0..bvh.triangles.len()).collect();
+        bvh.build_node(&bounds, &mut order, 0);
+
+        // Store the triangles in leaf order so leaves reference contiguous ranges
+        let mut triangles: Vec<Option<BvhTriangle>> = bvh.triangles.drain(..).map(Some).collect();
+        bvh.triangles = order
+            .iter()
+            .filter_map(|&index| triangles[index].take())
+            .collect();
+        bvh
+    }
+
+    fn build_node(
+        &mut self,
+        bounds: &[(AiAABB, AiVector3D)],
+        order: &mut [usize],
+        start: usize,
+    ) -> usize {
+        let aabb = order
+            .iter()
+            .fold(AiAABB::empty(), |aabb, &index| aabb.union(&bounds[index].0));
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            aabb,
+            kind: BvhNodeKind::Leaf {
+                start,
+                count: order.len(),
+            },
+        });
+        if order.len() <= MAX_LEAF_TRIANGLES {
+            return node_index;
+        }
+
+        // Split at the median centroid along the longest axis of the centroid bounds
+        let centroid_aabb = AiAABB::from_points(order.iter().map(|&index| &bounds[index].1));
+        let size = centroid_aabb.size();
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        if size[axis as usize] <= 0.0 {
+            return node_index;
+        }
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |&lhs, &rhs| {
+            bounds[lhs].1[axis as usize].total_cmp(&bounds[rhs].1[axis as usize])
+        });
+
+        let (left_order, right_order) = order.split_at_mut(middle);
+        let left = self.build_node(bounds, left_order, start);
+        let right = self.build_node(bounds, right_order, start + middle);
+        self.nodes[node_index].kind = BvhNodeKind::Inner { left, right };
+        node_index
+    }
+
+    /// All triangles of the hierarchy.
+    pub fn triangles(&self) -> &[BvhTriangle] {
+        &self.triangles
+    }
+
+    /// Bounding box of the whole scene, `None` if the scene has no triangles.
+    pub fn aabb(&self) -> Option<&AiAABB> {
+        self.nodes.first().map(|node| &node.aabb)
+    }
+
+    /// Finds the closest intersection of a ray with the scene.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - Start of the ray in world space.
+    /// * `direction` - Direction of the ray, does not need to be normalized.
+    /// * `max_distance` - Hits further away than this, in multiples of `direction`, are ignored.
+    ///
+    /// Both sides of a triangle are hit.
+    pub fn ray_cast(
+        &self,
+        origin: AiVector3D,
+        direction: AiVector3D,
+        max_distance: AiReal,
+    ) -> Option<BvhRayHit> {
+        let inverse_direction =
+            AiVector3D::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+        let mut closest: Option<(usize, AiReal, AiVector3D)> = None;
+        let mut max_distance = max_distance;
+        let mut stack: Vec<usize> = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !ray_intersects_aabb(&origin, &inverse_direction, &node.aabb, max_distance) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    for triangle_index in start..start + count {
+                        if let Some((distance, barycentric)) = ray_intersects_triangle(
+                            &origin,
+                            &direction,
+                            &self.triangles[triangle_index].positions,
+                        ) && distance <= max_distance
+                        {
+                            max_distance = distance;
+                            closest = Some((triangle_index, distance, barycentric));
+                        }
+                    }
+                }
+                BvhNodeKind::Inner { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+
+        closest.map(|(triangle_index, distance, barycentric)| {
+            let triangle = &self.triangles[triangle_index];
+            BvhRayHit {
+                triangle_index,
+                node_index: triangle.node_index,
+                mesh_index: triangle.mesh_index,
+                face_index: triangle.face_index,
+                barycentric,
+                distance,
+                position: triangle.interpolate(&barycentric),
+            }
+        })
+    }
+
+    /// Finds the point on the scene's surface closest to `point`.
+    ///
+    /// Points further away than `max_distance` are ignored.
+    pub fn closest_point(
+        &self,
+        point: AiVector3D,
+        max_distance: AiReal,
+    ) -> Option<BvhClosestPoint> {
+        let mut closest: Option<(usize, AiVector3D, AiVector3D)> = None;
+        let mut max_square_distance = max_distance * max_distance;
+        let mut stack: Vec<usize> = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.aabb.square_distance(&point) > max_square_distance {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    for triangle_index in start..start + count {
+                        let triangle = &self.triangles[triangle_index];
+                        let barycentric = closest_point_on_triangle(&point, &triangle.positions);
+                        let position = triangle.interpolate(&barycentric);
+                        let square_distance = (position - point).square_length();
+                        if square_distance <= max_square_distance {
+                            max_square_distance = square_distance;
+                            closest = Some((triangle_index, barycentric, position));
+                        }
+                    }
+                }
+                BvhNodeKind::Inner { left, right } => {
+                    // Visit the closer child first so the search radius shrinks faster
+                    let left_distance = self.nodes[left].aabb.square_distance(&point);
+                    let right_distance = self.nodes[right].aabb.square_distance(&point);
+                    if left_distance < right_distance {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+            }
+        }
+
+        closest.map(|(triangle_index, barycentric, position)| {
+            let triangle = &self.triangles[triangle_index];
+            BvhClosestPoint {
+                triangle_index,
+                node_index: triangle.node_index,
+                mesh_index: triangle.mesh_index,
+                face_index: triangle.face_index,
+                barycentric,
+                distance: (position - point).len(),
+                position,
+            }
+        })
+    }
+
+    /// Finds all triangles whose bounding box overlaps `aabb`, returning their indices.
+    pub fn query_aabb(&self, aabb: &AiAABB) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.aabb.overlaps(aabb) {
+                continue;
+            }
+            match node.kind {
+                BvhNodeKind::Leaf { start, count } => {
+                    result.extend(
+                        (start..start + count)
+                            .filter(|&index| self.triangles[index].aabb().overlaps(aabb)),
+                    );
+                }
+                BvhNodeKind::Inner { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+}
+
+/// Slab test of a ray against a box.
+fn ray_intersects_aabb(
+    origin: &AiVector3D,
+    inverse_direction: &AiVector3D,
+    aabb: &AiAABB,
+    max_distance: AiReal,
+) -> bool {
+    let mut t_min: AiReal = 0.0;
+    let mut t_max = max_distance;
+    for axis in 0..3_usize {
+        let t1 = (aabb.min[axis] - origin[axis]) * inverse_direction[axis];
+        let t2 = (aabb.max[axis] - origin[axis]) * inverse_direction[axis];
+        // NaN appears for rays parallel to a slab starting on its boundary, treat it as inside
+        if t1.is_nan() || t2.is_nan() {
+            continue;
+        }
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+    t_min <= t_max
+}
+
+/// Möller–Trumbore ray/triangle intersection, returning the distance and barycentric weights.
+fn ray_intersects_triangle(
+    origin: &AiVector3D,
+    direction: &AiVector3D,
+    positions: &[AiVector3D; 3],
+) -> Option<(AiReal, AiVector3D)> {
+    let edge1 = positions[1] - positions[0];
+    let edge2 = positions[2] - positions[0];
+    let p = direction ^ edge2;
+    let determinant = edge1 * p;
+    // The determinant scales with the edge lengths, so small triangles are not mistaken for
+    // triangles parallel to the ray
+    if determinant.abs() <= AiReal::EPSILON * direction.len() * edge1.len() * edge2.len() {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let t = origin - &positions[0];
+    let u = (t * p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = t ^ edge1;
+    let v = (direction * q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = (edge2 * q) * inverse_determinant;
+    if distance < 0.0 {
+        return None;
+    }
+    Some((distance, AiVector3D::new(1.0 - u - v, u, v)))
+}
+
+/// Closest point on a triangle, returned as barycentric weights.
+///
+/// See Ericson, Real-Time Collision Detection, 5.1.5.
+fn closest_point_on_triangle(point: &AiVector3D, positions: &[AiVector3D; 3]) -> AiVector3D {
+    let [a, b, c] = positions;
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab * ap;
+    let d2 = ac * ap;
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return AiVector3D::new(1.0, 0.0, 0.0);
+    }
+
+    let bp = point - b;
+    let d3 = ab * bp;
+    let d4 = ac * bp;
+    if d3 >= 0.0 && d4 <= d3 {
+        return AiVector3D::new(0.0, 1.0, 0.0);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return AiVector3D::new(1.0 - v, v, 0.0);
+    }
+
+    let cp = point - c;
+    let d5 = ab * cp;
+    let d6 = ac * cp;
+    if d6 >= 0.0 && d5 <= d6 {
+        return AiVector3D::new(0.0, 0.0, 1.0);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return AiVector3D::new(1.0 - w, 0.0, w);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return AiVector3D::new(0.0, 1.0 - w, w);
+    }
+
+    let denominator = va + vb + vc;
+    if denominator == 0.0 {
+        // Degenerate triangle, fall back to the first corner
+        return AiVector3D::new(1.0, 0.0, 0.0);
+    }
+    let v = vb / denominator;
+    let w = vc / denominator;
+    AiVector3D::new(1.0 - v - w, v, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiMatrix4x4, AiMesh, AiNode, AiNodeTree};
+
+    /// A grid of quads in the XY plane, instanced twice: once at the origin and once moved along Z.
+    fn create_scene() -> AiScene {
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for y in 0..5 {
+            for x in 0..5 {
+                vertices.push(AiVector3D::new(x as AiReal, y as AiReal, 0.0));
+            }
+        }
+        for y in 0..4 {
+            for x in 0..4 {
+                let index = y * 5 + x;
+                faces.push(vec![index, index + 1, index + 6, index + 5]);
+            }
+        }
+        let mut nodes = AiNodeTree::default();
+        let root = nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0],
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, -10.0)),
+                    ..Default::default()
+                },
+                Some(root),
+            )
+            .unwrap();
+        AiScene {
+            nodes,
+            meshes: vec![AiMesh {
+                vertices,
+                faces,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bvh_ray_cast() {
+        let bvh = SceneBvh::new(&create_scene());
+        assert_eq!(bvh.triangles().len(), 64);
+
+        let hit = bvh
+            .ray_cast(
+                AiVector3D::new(1.25, 2.5, 5.0),
+                AiVector3D::new(0.0, 0.0, -1.0),
+                AiReal::MAX,
+            )
+            .unwrap();
+        assert_eq!(hit.node_index, 0);
+        assert_eq!(hit.face_index, 9);
+        assert_eq!(hit.distance, 5.0);
+        assert_eq!(hit.position, AiVector3D::new(1.25, 2.5, 0.0));
+        let sum = hit.barycentric.x + hit.barycentric.y + hit.barycentric.z;
+        assert!((sum - 1.0).abs() < 1e-5);
+
+        // Coming from below hits the moved instance first
+        let hit = bvh
+            .ray_cast(
+                AiVector3D::new(1.25, 2.5, -15.0),
+                AiVector3D::new(0.0, 0.0, 1.0),
+                AiReal::MAX,
+            )
+            .unwrap();
+        assert_eq!(hit.node_index, 1);
+        assert_eq!(hit.distance, 5.0);
+
+        assert!(
+            bvh.ray_cast(
+                AiVector3D::new(1.25, 2.5, 5.0),
+                AiVector3D::new(0.0, 0.0, -1.0),
+                4.0
+            )
+            .is_none()
+        );
+        assert!(
+            bvh.ray_cast(
+                AiVector3D::new(10.0, 2.5, 5.0),
+                AiVector3D::new(0.0, 0.0, -1.0),
+                AiReal::MAX
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_bvh_ray_cast_small_triangle() {
+        let scene = AiScene {
+            nodes: AiNodeTree::with_root(),
+            meshes: vec![AiMesh {
+                vertices: vec![
+                    AiVector3D::new(0.0, 0.0, 0.0),
+                    AiVector3D::new(1e-3, 0.0, 0.0),
+                    AiVector3D::new(0.0, 1e-4, 0.0),
+                ],
+                faces: vec![vec![0, 1, 2]],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut scene = scene;
+        scene.nodes.arena[0].mesh_indexes = vec![0];
+        let bvh = SceneBvh::new(&scene);
+
+        let hit = bvh
+            .ray_cast(
+                AiVector3D::new(2e-4, 2e-5, 1.0),
+                AiVector3D::new(0.0, 0.0, -1.0),
+                AiReal::MAX,
+            )
+            .unwrap();
+        assert_eq!(hit.face_index, 0);
+        assert!((hit.distance - 1.0).abs() < 1e-5);
+        assert!(
+            bvh.ray_cast(
+                AiVector3D::new(2e-3, 2e-5, 1.0),
+                AiVector3D::new(0.0, 0.0, -1.0),
+                AiReal::MAX
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_bvh_closest_point() {
+        let bvh = SceneBvh::new(&create_scene());
+
+        let closest = bvh
+            .closest_point(AiVector3D::new(2.5, 1.5, 1.0), AiReal::MAX)
+            .unwrap();
+        assert_eq!(closest.node_index, 0);
+        assert_eq!(closest.position, AiVector3D::new(2.5, 1.5, 0.0));
+        assert_eq!(closest.distance, 1.0);
+
+        let closest = bvh
+            .closest_point(AiVector3D::new(6.0, 2.0, -10.0), AiReal::MAX)
+            .unwrap();
+        assert_eq!(closest.node_index, 1);
+        assert_eq!(closest.position, AiVector3D::new(4.0, 2.0, -10.0));
+        assert_eq!(closest.distance, 2.0);
+
+        assert!(
+            bvh.closest_point(AiVector3D::new(2.5, 1.5, 1.0), 0.5)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_bvh_query_aabb() {
+        let bvh = SceneBvh::new(&create_scene());
+
+        let result = bvh.query_aabb(&AiAABB::new(
+            AiVector3D::new(0.1, 0.1, -1.0),
+            AiVector3D::new(0.9, 0.9, 1.0),
+        ));
+        assert_eq!(result.len(), 2);
+        assert!(
+            result
+                .iter()
+                .all(|&index| bvh.triangles()[index].face_index == 0
+                    && bvh.triangles()[index].node_index == 0)
+        );
+
+        let result = bvh.query_aabb(&AiAABB::new(
+            AiVector3D::new(-1.0, -1.0, -11.0),
+            AiVector3D::new(5.0, 5.0, -9.0),
+        ));
+        assert_eq!(result.len(), 32);
+    }
+}
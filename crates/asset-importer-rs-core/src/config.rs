@@ -1,4 +1,5 @@
-use asset_importer_rs_scene::AiReal;
+use asset_importer_rs_scene::{AiPrimitiveType, AiReal};
+use enumflags2::BitFlags;
 
 /// Configuration key for checking identity matrix epsilon.
 ///
@@ -36,3 +37,105 @@ pub const AI_CONFIG_EXPORT_GLTF_UNLIMITED_SKINNING_BONES_PER_VERTEX: &str =
 /// Controls the export of normal targets in GLTF2 files, which are used
 /// for morph target animations and normal map blending.
 pub const GLTF2_TARGET_NORMAL_EXP: &str = "GLTF2_TARGET_NORMAL_EXP";
+
+/// Default of `CalcTangentSpaces::max_smoothing_angle`, in degrees.
+///
+/// Corners of a vertex whose tangents differ by more than this angle keep separate tangents.
+/// 180 degrees smooths all tangents of a vertex as MikkTSpace does.
+pub const AI_CONFIG_PP_CT_MAX_SMOOTHING_ANGLE_DEFAULT: AiReal = 180.0;
+
+/// Default of `CalcTangentSpaces::uv_channel`.
+///
+/// The texture coordinate channel used to compute tangents.
+pub const AI_CONFIG_PP_CT_TEXTURE_CHANNEL_INDEX_DEFAULT: usize = 0;
+
+/// Default of `FindDegenerates::remove`.
+///
+/// Faces with repeated vertices are converted to lines and points instead of being removed.
+pub const AI_CONFIG_PP_FD_REMOVE_DEFAULT: bool = false;
+
+/// Default of `FindDegenerates::check_area`.
+///
+/// Triangles without area are treated as degenerate.
+pub const AI_CONFIG_PP_FD_CHECKAREA_DEFAULT: bool = true;
+
+/// Default of `FindInvalidData::anim_accuracy`.
+///
+/// Channels whose keys all lie within this distance of the first key are reduced to that key.
+pub const AI_CONFIG_PP_FID_ANIM_ACCURACY_DEFAULT: AiReal = 0.0;
+
+/// Default of `FindInvalidData::ignore_texture_coords`.
+///
+/// Texture coordinates are checked along with the other vertex attributes.
+pub const AI_CONFIG_PP_FID_IGNORE_TEXTURECOORDS_DEFAULT: bool = false;
+
+/// Default of `SplitLargeMeshes::max_vertices` and `OptimizeMeshes::max_vertices`.
+///
+/// Every index of a mesh within this limit fits into 16 bits.
+pub const AI_CONFIG_PP_SLM_VERTEX_LIMIT_DEFAULT: usize = 65_535;
+
+/// Default of `SplitLargeMeshes::max_faces` and `OptimizeMeshes::max_faces`.
+pub const AI_CONFIG_PP_SLM_TRIANGLE_LIMIT_DEFAULT: usize = 1_000_000;
+
+/// Default of `ImproveCacheLocality::cache_size`.
+///
+/// Most GPUs have a post-transform vertex cache of 16 to 32 entries, a smaller value is safe on
+/// every GPU while a value larger than the actual cache causes thrashing.
+pub const AI_CONFIG_PP_ICL_PTCACHE_SIZE_DEFAULT: usize = 12;
+
+/// Default of `LimitBoneWeights::max_weights`.
+///
+/// Four influences per vertex fit into a single vertex attribute.
+pub const AI_CONFIG_PP_LBW_MAX_WEIGHTS_DEFAULT: usize = 4;
+
+/// Default of `SplitByBoneCount::max_bones`.
+///
+/// Meshes with more bones are split, so they fit into a fixed size bone palette.
+pub const AI_CONFIG_PP_SBBC_MAX_BONES_DEFAULT: usize = 60;
+
+/// Default of `PreTransformVertices::keep_hierarchy`.
+///
+/// Meshes sharing their material and vertex format are merged and the node tree is flattened.
+pub const AI_CONFIG_PP_PTV_KEEP_HIERARCHY_DEFAULT: bool = false;
+
+/// Default of `PreTransformVertices::normalize`.
+///
+/// Pre-transformed vertices keep their scale instead of being fit into the `[-1, 1]` cube.
+pub const AI_CONFIG_PP_PTV_NORMALIZE_DEFAULT: bool = false;
+
+/// Default of `SortByPType::remove`.
+///
+/// No [`AiPrimitiveType`] is removed, faces of every type are kept.
+pub const AI_CONFIG_PP_SBP_REMOVE_DEFAULT: BitFlags<AiPrimitiveType> = BitFlags::EMPTY;
+
+/// Default of `FindInstances::epsilon`.
+///
+/// Meshes must match exactly to be considered instances of each other.
+pub const AI_CONFIG_PP_FI_EPSILON_DEFAULT: AiReal = 0.0;
+
+/// Default of `Debone::threshold`.
+///
+/// Only vertices fully owned by a single bone are treated as driven by that bone alone.
+pub const AI_CONFIG_PP_DB_THRESHOLD_DEFAULT: AiReal = 1.0;
+
+/// Default of `Debone::influence_threshold`.
+///
+/// Only bones without any weight are removed from deboned meshes.
+pub const AI_CONFIG_PP_DB_INFLUENCE_THRESHOLD_DEFAULT: AiReal = 0.0;
+
+/// Default of `Debone::all_or_none`.
+///
+/// Meshes are deboned even if only some of their bones can be removed.
+pub const AI_CONFIG_PP_DB_ALL_OR_NONE_DEFAULT: bool = false;
+
+/// Default of `GlobalScale::scale`.
+///
+/// The scene keeps its size unless units are converted.
+pub const AI_CONFIG_PP_GS_SCALE_FACTOR_DEFAULT: AiReal = 1.0;
+
+/// Default of `GlobalScale::convert_units`.
+///
+/// The unit described by [`AI_METADATA_UNIT_METER`](crate::AI_METADATA_UNIT_METER) or
+/// [`AI_METADATA_UNIT_SCALE_FACTOR`](crate::AI_METADATA_UNIT_SCALE_FACTOR) scene metadata is
+/// converted to meters before the scale factor is applied.
+pub const AI_CONFIG_PP_GS_CONVERT_UNITS_DEFAULT: bool = true;
@@ -0,0 +1,185 @@
+use asset_importer_rs_scene::{AiReal, AiVector3D};
+
+use crate::{SpatialEntry, SpatialLookup};
+
+/// A k-d tree over a set of positions.
+///
+/// The tree is stored implicitly: every range of `entries` has its median as the splitting
+/// entry, with the lower half to its left and the upper half to its right. The splitting axis
+/// cycles through x, y and z with the depth of the range.
+///
+/// Unlike [`Spatial`](crate::Spatial), lookups do not degrade when many positions share the same
+/// distance to the sorting plane, which makes it a better fit for welding large meshes.
+///
+/// # Example
+///
+/// ```rust
+/// use asset_importer_rs_core::{KdTree, SpatialLookup};
+/// use asset_importer_rs_scene::AiVector3D;
+///
+/// let positions = vec![
+///     AiVector3D::new(0.0, 0.0, 0.0),
+///     AiVector3D::new(1.0, 0.0, 0.0),
+///     AiVector3D::new(0.0, 1.0, 0.0),
+/// ];
+///
+/// let tree = KdTree::new(&positions);
+/// let nearby = tree.find_position(AiVector3D::new(0.9, 0.0, 0.0), 0.2);
+/// assert_eq!(nearby, vec![1]);
+/// assert_eq!(tree.nearest(AiVector3D::new(0.1, 0.8, 0.0)), Some(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    /// The entries of the tree in implicit tree order. `distance` is unused and set to zero.
+    pub entries: Vec<SpatialEntry>,
+}
+
+impl KdTree {
+    pub fn new(positions: &[AiVector3D]) -> Self {
+        let mut entries: Vec<SpatialEntry> = positions
+            .iter()
+            .enumerate()
+            .map(|(index, position)| SpatialEntry {
+                original_index: index,
+                distance: 0.0,
+                position: *position,
+            })
+            .collect();
+        build(&mut entries, 0);
+        Self { entries }
+    }
+
+    /// Finds the position closest to `position`, returning its original index.
+    pub fn nearest(&self, position: AiVector3D) -> Option<usize> {
+        let mut best: Option<(usize, AiReal)> = None;
+        nearest(&self.entries, 0, &position, &mut best);
+        best.map(|(index, _)| index)
+    }
+}
+
+fn build(entries: &mut [SpatialEntry], depth: usize) {
+    if entries.len() <= 1 {
+        return;
+    }
+    let axis = depth % 3;
+    let middle = entries.len() / 2;
+    entries.select_nth_unstable_by(middle, |lhs, rhs| {
+        lhs.position[axis].total_cmp(&rhs.position[axis])
+    });
+    let (lower, upper) = entries.split_at_mut(middle);
+    build(lower, depth + 1);
+    build(&mut upper[1..], depth + 1);
+}
+
+fn find_in_radius(
+    entries: &[SpatialEntry],
+    depth: usize,
+    position: &AiVector3D,
+    radius: AiReal,
+    results: &mut Vec<usize>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let axis = depth % 3;
+    let middle = entries.len() / 2;
+    let entry = &entries[middle];
+    if (entry.position - *position).square_length() < radius * radius {
+        results.push(entry.original_index);
+    }
+    let offset = position[axis] - entry.position[axis];
+    if offset - radius <= 0.0 {
+        find_in_radius(&entries[..middle], depth + 1, position, radius, results);
+    }
+    if offset + radius >= 0.0 {
+        find_in_radius(&entries[middle + 1..], depth + 1, position, radius, results);
+    }
+}
+
+fn nearest(
+    entries: &[SpatialEntry],
+    depth: usize,
+    position: &AiVector3D,
+    best: &mut Option<(usize, AiReal)>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let axis = depth % 3;
+    let middle = entries.len() / 2;
+    let entry = &entries[middle];
+    let square_distance = (entry.position - *position).square_length();
+    if best.is_none_or(|(_, best_distance)| square_distance < best_distance) {
+        *best = Some((entry.original_index, square_distance));
+    }
+    let offset = position[axis] - entry.position[axis];
+    let (near, far) = if offset < 0.0 {
+        (&entries[..middle], &entries[middle + 1..])
+    } else {
+        (&entries[middle + 1..], &entries[..middle])
+    };
+    nearest(near, depth + 1, position, best);
+    if best.is_none_or(|(_, best_distance)| offset * offset < best_distance) {
+        nearest(far, depth + 1, position, best);
+    }
+}
+
+impl SpatialLookup for KdTree {
+    fn find_position(&self, position: AiVector3D, radius: AiReal) -> Vec<usize> {
+        let mut results = Vec::new();
+        find_in_radius(&self.entries, 0, &position, radius, &mut results);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Spatial;
+
+    fn create_positions() -> Vec<AiVector3D> {
+        let mut positions = Vec::new();
+        for x in 0..10 {
+            for y in 0..10 {
+                for z in 0..10 {
+                    positions.push(AiVector3D::new(
+                        x as AiReal * 0.5,
+                        y as AiReal * 0.5,
+                        z as AiReal * 0.5,
+                    ));
+                }
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn test_kd_tree_matches_spatial() {
+        let positions = create_positions();
+        let tree = KdTree::new(&positions);
+        let spatial = Spatial::new(&positions);
+        for query in [
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(1.2, 2.3, 0.7),
+            AiVector3D::new(4.5, 4.5, 4.5),
+            AiVector3D::new(10.0, 10.0, 10.0),
+        ] {
+            for radius in [0.1, 0.6, 1.5] {
+                let mut expected = spatial.find_position(query, radius);
+                let mut result = tree.find_position(query, radius);
+                expected.sort_unstable();
+                result.sort_unstable();
+                assert_eq!(expected, result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kd_tree_nearest() {
+        let positions = create_positions();
+        let tree = KdTree::new(&positions);
+        let index = tree.nearest(AiVector3D::new(1.1, 2.4, 0.2)).unwrap();
+        assert_eq!(positions[index], AiVector3D::new(1.0, 2.5, 0.0));
+        assert_eq!(KdTree::new(&[]).nearest(AiVector3D::zero()), None);
+    }
+}
@@ -1,15 +1,21 @@
+mod bvh;
 mod config;
 mod export;
 mod import;
 mod importer_desc;
+mod kd_tree;
 mod metadata;
 mod post_process;
+mod scene_combiner;
 mod spatial;
 
+pub use bvh::*;
 pub use config::*;
 pub use export::*;
 pub use import::*;
 pub use importer_desc::*;
+pub use kd_tree::*;
 pub use metadata::*;
 pub use post_process::*;
+pub use scene_combiner::*;
 pub use spatial::*;
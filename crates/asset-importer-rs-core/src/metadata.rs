@@ -21,3 +21,21 @@ pub const AI_METADATA_SOURCE_GENERATOR: &str = "SourceAsset_Generator";
 /// This constant is used to store copyright and licensing information
 /// about the original asset, which should be preserved during import/export.
 pub const AI_METADATA_SOURCE_COPYRIGHT: &str = "SourceAsset_Copyright";
+
+/// Metadata key for the size of a source unit in centimeters.
+///
+/// This is the FBX `UnitScaleFactor`, e.g. 1 for files authored in centimeters and 100 for
+/// files authored in meters.
+pub const AI_METADATA_UNIT_SCALE_FACTOR: &str = "UnitScaleFactor";
+
+/// Metadata key for the size of a source unit in meters.
+///
+/// This is the `meter` attribute of the Collada `<unit>` element, e.g. 0.01 for files authored
+/// in centimeters.
+pub const AI_METADATA_UNIT_METER: &str = "UnitMeter";
+
+/// Metadata key for the path of the imported file.
+///
+/// This constant is used to store the path the scene was read from, so that
+/// files it references, such as textures, can be resolved after import.
+pub const AI_METADATA_SOURCE_FILE_PATH: &str = "SourceAsset_FilePath";
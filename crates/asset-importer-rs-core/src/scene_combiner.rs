@@ -0,0 +1,589 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+};
+
+use asset_importer_rs_scene::{
+    AiMaterial, AiNodeTree, AiPropertyTypeInfo, AiScene, AiSceneFlag, AiTexture, AiTextureType,
+    matkey,
+};
+
+/// Name of the material meshes with an invalid material index are assigned to.
+const DEFAULT_MATERIAL_NAME: &str = "DefaultMaterial";
+
+/// Combines several [`AiScene`]s into a single scene.
+///
+/// Every input scene is attached as a child of a newly created root node, and all
+/// cross-references are offset so they keep pointing at the same data:
+/// - node `mesh_indexes` into the combined mesh list
+/// - mesh `material_index` into the combined material list, invalid indices are replaced by a
+///   default material appended to the list
+/// - bone and skeleton bone node indices into the combined node arena
+/// - skeleton bone mesh indices into the combined mesh list
+/// - embedded texture references (`*N`) in materials into the combined texture list
+///
+/// Optionally, identical materials and textures are merged, and names of nodes, meshes and
+/// animations that collide with names of a previously combined scene are made unique.
+/// Renamed nodes are propagated to animation channels, bones, cameras and lights, renamed
+/// meshes to mesh and morph animation channels.
+///
+/// # Example
+///
+/// ```rust
+/// use asset_importer_rs_core::SceneCombiner;
+/// use asset_importer_rs_scene::AiScene;
+///
+/// let combiner = SceneCombiner::default();
+/// let scene = combiner.combine(vec![AiScene::default(), AiScene::default()]);
+/// assert_eq!(scene.nodes.root, Some(0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SceneCombiner {
+    /// Name given to the newly created root node.
+    pub root_name: String,
+    /// Merge materials with identical properties into a single material.
+    pub deduplicate_materials: bool,
+    /// Merge embedded textures with identical content into a single texture.
+    pub deduplicate_textures: bool,
+    /// Rename nodes, meshes and animations whose names collide with an earlier scene.
+    pub resolve_name_collisions: bool,
+}
+
+impl Default for SceneCombiner {
+    fn default() -> Self {
+        Self {
+            root_name: "ROOT".to_string(),
+            deduplicate_materials: true,
+            deduplicate_textures: true,
+            resolve_name_collisions: true,
+        }
+    }
+}
+
+impl SceneCombiner {
+    /// Combines the given scenes into one, consuming them.
+    pub fn combine(&self, scenes: Vec<AiScene>) -> AiScene {
+        let mut output = AiScene {
+            name: self.root_name.clone(),
+            nodes: AiNodeTree::with_root(),
+            ..Default::default()
+        };
+        if let Some(root) = output.nodes.arena.first_mut() {
+            root.name = self.root_name.clone();
+        }
+
+        let mut node_names: HashSet<String> = HashSet::from([self.root_name.clone()]);
+        let mut mesh_names: HashSet<String> = HashSet::new();
+        let mut animation_names: HashSet<String> = HashSet::new();
+        let mut texture_lookup: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut material_lookup: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut default_material: Option<usize> = None;
+
+        for mut scene in scenes {
+            // Textures
+            let mut texture_map: Vec<usize> = Vec::with_capacity(scene.textures.len());
+            for texture in scene.textures {
+                let hash = texture_hash(&texture);
+                let existing = if self.deduplicate_textures {
+                    texture_lookup.get(&hash).and_then(|candidates| {
+                        candidates
+                            .iter()
+                            .copied()
+                            .find(|&index| texture_eq(&output.textures[index], &texture))
+                    })
+                } else {
+                    None
+                };
+                let index = existing.unwrap_or_else(|| {
+                    output.textures.push(texture);
+                    let index = output.textures.len() - 1;
+                    texture_lookup.entry(hash).or_default().push(index);
+                    index
+                });
+                texture_map.push(index);
+            }
+
+            // Materials
+            let mut material_map: Vec<usize> = Vec::with_capacity(scene.materials.len());
+            for mut material in scene.materials {
+                remap_texture_references(&mut material, &texture_map);
+                let hash = material.properties_hash(&[]);
+                let existing = if self.deduplicate_materials {
+                    material_lookup.get(&hash).and_then(|candidates| {
+                        candidates
+                            .iter()
+                            .copied()
+                            .find(|&index| output.materials[index].properties_eq(&material, &[]))
+                    })
+                } else {
+                    None
+                };
+                let index = existing.unwrap_or_else(|| {
+                    output.materials.push(material);
+                    let index = output.materials.len() - 1;
+                    material_lookup.entry(hash).or_default().push(index);
+                    index
+                });
+                material_map.push(index);
+            }
+
+            // Node names
+            let renames = if self.resolve_name_collisions {
+                let renames = unique_renames(
+                    scene.nodes.arena.iter().map(|node| node.name.as_str()),
+                    &node_names,
+                );
+                for node in &mut scene.nodes.arena {
+                    if let Some(new_name) = renames.get(&node.name) {
+                        node.name = new_name.clone();
+                    }
+                }
+                renames
+            } else {
+                HashMap::new()
+            };
+            node_names.extend(scene.nodes.arena.iter().map(|node| node.name.clone()));
+
+            // Mesh names
+            let mesh_renames = if self.resolve_name_collisions {
+                unique_renames(
+                    scene.meshes.iter().map(|mesh| mesh.name.as_str()),
+                    &mesh_names,
+                )
+            } else {
+                HashMap::new()
+            };
+
+            // Meshes
+            let mesh_offset = output.meshes.len();
+            let node_offset = output.nodes.arena.len();
+            for mut mesh in scene.meshes {
+                let material_index = mesh.material_index as usize;
+                mesh.material_index = match material_map.get(material_index) {
+                    Some(&index) => index,
+                    None => {
+                        log::warn!(
+                            "Mesh {} references missing material {}, using a default material",
+                            mesh.name,
+                            material_index
+                        );
+                        *default_material.get_or_insert_with(|| {
+                            let mut material = AiMaterial::new();
+                            material.add_property(
+                                matkey::AI_MATKEY_NAME,
+                                Some(AiTextureType::None),
+                                AiPropertyTypeInfo::Binary,
+                                0,
+                                DEFAULT_MATERIAL_NAME.bytes().collect(),
+                            );
+                            output.materials.push(material);
+                            output.materials.len() - 1
+                        })
+                    }
+                } as u32;
+                for bone in &mut mesh.bones {
+                    bone.node_index += node_offset;
+                    bone.armature_index += node_offset;
+                    rename(&mut bone.name, &renames);
+                }
+                rename(&mut mesh.name, &mesh_renames);
+                mesh_names.insert(mesh.name.clone());
+                output.meshes.push(mesh);
+            }
+
+            // Nodes
+            for node in &mut scene.nodes.arena {
+                for mesh_index in &mut node.mesh_indexes {
+                    *mesh_index += mesh_offset;
+                }
+            }
+            output.nodes.merge(scene.nodes);
+
+            // Animations
+            for mut animation in scene.animations {
+                for channel in &mut animation.channels {
+                    rename(&mut channel.node_name, &renames);
+                }
+                for channel in &mut animation.mesh_channels {
+                    if let Some(new_name) = mesh_renames.get(channel.name()) {
+                        channel.set_name(new_name.clone());
+                    }
+                }
+                for channel in &mut animation.morph_channels {
+                    rename(&mut channel.name, &mesh_renames);
+                }
+                if self.resolve_name_collisions {
+                    let renames =
+                        unique_renames(std::iter::once(animation.name.as_str()), &animation_names);
+                    rename(&mut animation.name, &renames);
+                }
+                animation_names.insert(animation.name.clone());
+                output.animations.push(animation);
+            }
+
+            // Cameras & Lights
+            for mut camera in scene.cameras {
+                rename(&mut camera.name, &renames);
+                output.cameras.push(camera);
+            }
+            for mut light in scene.lights {
+                rename(&mut light.name, &renames);
+                output.lights.push(light);
+            }
+
+            // Skeletons
+            for mut skeleton in scene.skeletons {
+                for bone in skeleton.bones_mut() {
+                    bone.set_node_index(bone.node_index() + node_offset);
+                    bone.set_armature_index(bone.armature_index().map(|index| index + node_offset));
+                    bone.set_mesh_index(bone.mesh_index().map(|index| index + mesh_offset));
+                }
+                output.skeletons.push(skeleton);
+            }
+
+            for (key, value) in scene.metadata {
+                output.metadata.entry(key).or_insert(value);
+            }
+            output.flags |= scene.flags;
+        }
+
+        // The combined scene has not been validated as a whole
+        output.flags &= !(AiSceneFlag::Validated | AiSceneFlag::ValidationWarning);
+        output
+    }
+}
+
+fn rename(name: &mut String, renames: &HashMap<String, String>) {
+    if let Some(new_name) = renames.get(name) {
+        *name = new_name.clone();
+    }
+}
+
+/// Computes new names for every non-empty name that is already taken.
+///
+/// Names are renamed to `{name}_{n}` using the lowest `n` that is still free.
+/// Duplicate names within `names` map to the same new name.
+fn unique_renames<'a>(
+    names: impl Iterator<Item = &'a str>,
+    taken: &HashSet<String>,
+) -> HashMap<String, String> {
+    let names: HashSet<&str> = names.collect();
+    let mut renames = HashMap::new();
+    let mut reserved: HashSet<String> = HashSet::new();
+    for name in &names {
+        if name.is_empty() || !taken.contains(*name) {
+            continue;
+        }
+        let mut suffix = 1;
+        let new_name = loop {
+            let candidate = format!("{}_{}", name, suffix);
+            if !taken.contains(&candidate)
+                && !names.contains(candidate.as_str())
+                && !reserved.contains(&candidate)
+            {
+                break candidate;
+            }
+            suffix += 1;
+        };
+        reserved.insert(new_name.clone());
+        renames.insert(name.to_string(), new_name);
+    }
+    renames
+}
+
+/// Rewrites embedded texture references (`*N`) using the given index map.
+fn remap_texture_references(material: &mut AiMaterial, texture_map: &[usize]) {
+    for property in material.iter_mut() {
+        if property.key != matkey::_AI_MATKEY_TEXTURE_BASE {
+            continue;
+        }
+        let Some(new_index) = std::str::from_utf8(&property.data)
+            .ok()
+            .and_then(|path| path.strip_prefix('*'))
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| texture_map.get(index))
+        else {
+            continue;
+        };
+        property.data = format!("*{}", new_index).into_bytes();
+    }
+}
+
+fn texture_hash(texture: &AiTexture) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    texture.width.hash(&mut hasher);
+    texture.height.hash(&mut hasher);
+    for texel in &texture.texel {
+        [texel.r, texel.g, texel.b, texel.a].hash(&mut hasher);
+    }
+    texture.compressed_data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Textures are considered equal if their content matches, regardless of their filename.
+fn texture_eq(lhs: &AiTexture, rhs: &AiTexture) -> bool {
+    lhs.width == rhs.width
+        && lhs.height == rhs.height
+        && lhs.ach_format_hint == rhs.ach_format_hint
+        && lhs.texel == rhs.texel
+        && lhs.compressed_data == rhs.compressed_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimation, AiBone, AiLight, AiMatrix4x4, AiMesh, AiMeshAnim, AiMeshMorphAnim, AiNode,
+        AiNodeAnim, AiPropertyTypeInfo, AiSkeleton, AiSkeletonBone, AiTexel, AiTextureFormat,
+        AiTextureType,
+    };
+
+    fn create_scene(node_name: &str, texture_color: u8) -> AiScene {
+        let mut nodes = AiNodeTree::default();
+        let root = nodes
+            .insert(
+                AiNode {
+                    name: node_name.to_string(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        nodes
+            .insert(
+                AiNode {
+                    name: format!("{}_child", node_name),
+                    mesh_indexes: vec![0],
+                    ..Default::default()
+                },
+                Some(root),
+            )
+            .unwrap();
+
+        let mut material = AiMaterial::new();
+        material.add_property(
+            matkey::_AI_MATKEY_TEXTURE_BASE,
+            Some(AiTextureType::Diffuse),
+            AiPropertyTypeInfo::Binary,
+            0,
+            "*0".bytes().collect(),
+        );
+
+        AiScene {
+            nodes,
+            meshes: vec![AiMesh {
+                bones: vec![AiBone {
+                    name: node_name.to_string(),
+                    node_index: 1,
+                    armature_index: 0,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            materials: vec![material],
+            textures: vec![AiTexture::new(
+                "texture.png".to_string(),
+                1,
+                1,
+                AiTextureFormat::PNG,
+                vec![AiTexel::new(texture_color, 0, 0, 255)],
+            )],
+            animations: vec![AiAnimation {
+                name: "Idle".to_string(),
+                duration: 1.0,
+                ticks_per_second: 1.0,
+                channels: vec![AiNodeAnim {
+                    node_name: node_name.to_string(),
+                    ..Default::default()
+                }],
+                mesh_channels: Vec::new(),
+                morph_channels: Vec::new(),
+            }],
+            lights: vec![AiLight {
+                name: node_name.to_string(),
+                ..Default::default()
+            }],
+            skeletons: vec![
+                AiSkeleton::new(
+                    node_name.to_string(),
+                    vec![
+                        AiSkeletonBone::new(
+                            None,
+                            1,
+                            AiMatrix4x4::identity(),
+                            AiMatrix4x4::identity(),
+                        )
+                        .with_armature(0)
+                        .with_mesh(0, Vec::new()),
+                    ],
+                )
+                .unwrap(),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn texture_reference(material: &AiMaterial) -> String {
+        material
+            .get_property_ai_str(
+                matkey::_AI_MATKEY_TEXTURE_BASE,
+                Some(AiTextureType::Diffuse),
+                0,
+            )
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_combine_offsets_references() {
+        let scene =
+            SceneCombiner::default().combine(vec![create_scene("a", 0), create_scene("b", 255)]);
+
+        assert_eq!(scene.nodes.arena.len(), 5);
+        assert_eq!(scene.nodes.arena[0].name, "ROOT");
+        assert_eq!(scene.nodes.arena[0].children, vec![1, 3]);
+        assert_eq!(scene.nodes.arena[3].parent, Some(0));
+        assert_eq!(scene.nodes.arena[3].children, vec![4]);
+        assert_eq!(scene.nodes.arena[4].parent, Some(3));
+        assert_eq!(scene.nodes.arena[2].mesh_indexes, vec![0]);
+        assert_eq!(scene.nodes.arena[4].mesh_indexes, vec![1]);
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.meshes[1].bones[0].node_index, 4);
+        assert_eq!(scene.meshes[1].bones[0].armature_index, 3);
+        assert_eq!(scene.meshes[1].material_index, 1);
+
+        let bone = &scene.skeletons[1].bones()[0];
+        assert_eq!(bone.node_index(), 4);
+        assert_eq!(bone.armature_index(), Some(3));
+        assert_eq!(bone.mesh_index(), Some(1));
+
+        assert_eq!(scene.textures.len(), 2);
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(texture_reference(&scene.materials[0]), "*0");
+        assert_eq!(texture_reference(&scene.materials[1]), "*1");
+        assert_eq!(scene.lights.len(), 2);
+        assert_eq!(scene.animations.len(), 2);
+    }
+
+    #[test]
+    fn test_combine_replaces_invalid_material_index() {
+        let mut first = create_scene("a", 0);
+        first.meshes[0].material_index = 7;
+        let mut second = create_scene("b", 255);
+        second.meshes[0].material_index = 7;
+        let scene = SceneCombiner::default().combine(vec![first, second]);
+
+        assert_eq!(scene.materials.len(), 3);
+        assert_eq!(scene.meshes[0].material_index, 1);
+        assert_eq!(scene.meshes[1].material_index, 1);
+        assert_eq!(
+            scene.materials[1]
+                .get_property_ai_str(matkey::AI_MATKEY_NAME, Some(AiTextureType::None), 0)
+                .unwrap()
+                .unwrap(),
+            DEFAULT_MATERIAL_NAME
+        );
+    }
+
+    #[test]
+    fn test_combine_deduplicates_materials_and_textures() {
+        let scene =
+            SceneCombiner::default().combine(vec![create_scene("a", 0), create_scene("b", 0)]);
+
+        assert_eq!(scene.textures.len(), 1);
+        assert_eq!(scene.materials.len(), 1);
+        assert_eq!(scene.meshes[0].material_index, 0);
+        assert_eq!(scene.meshes[1].material_index, 0);
+
+        let combiner = SceneCombiner {
+            deduplicate_materials: false,
+            deduplicate_textures: false,
+            ..Default::default()
+        };
+        let scene = combiner.combine(vec![create_scene("a", 0), create_scene("b", 0)]);
+        assert_eq!(scene.textures.len(), 2);
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(texture_reference(&scene.materials[1]), "*1");
+    }
+
+    #[test]
+    fn test_combine_resolves_name_collisions() {
+        let scene =
+            SceneCombiner::default().combine(vec![create_scene("a", 0), create_scene("a", 0)]);
+
+        assert_eq!(scene.nodes.arena[1].name, "a");
+        assert_eq!(scene.nodes.arena[2].name, "a_child");
+        assert_eq!(scene.nodes.arena[3].name, "a_1");
+        assert_eq!(scene.nodes.arena[4].name, "a_child_1");
+        assert_eq!(scene.animations[0].name, "Idle");
+        assert_eq!(scene.animations[1].name, "Idle_1");
+        assert_eq!(scene.animations[1].channels[0].node_name, "a_1");
+        assert_eq!(scene.meshes[1].bones[0].name, "a_1");
+        assert_eq!(scene.lights[1].name, "a_1");
+
+        let combiner = SceneCombiner {
+            resolve_name_collisions: false,
+            ..Default::default()
+        };
+        let scene = combiner.combine(vec![create_scene("a", 0), create_scene("a", 0)]);
+        assert_eq!(scene.nodes.arena[3].name, "a");
+        assert_eq!(scene.animations[1].name, "Idle");
+    }
+
+    #[test]
+    fn test_combine_renames_colliding_meshes() {
+        let create_scene = |mesh_names: &[&str]| {
+            let mut scene = create_scene("a", 0);
+            scene.meshes = mesh_names
+                .iter()
+                .map(|name| AiMesh {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            let animation = &mut scene.animations[0];
+            animation.mesh_channels = mesh_names
+                .iter()
+                .map(|name| AiMeshAnim::new(name.to_string(), Vec::new()))
+                .collect();
+            animation.morph_channels = mesh_names
+                .iter()
+                .map(|name| AiMeshMorphAnim {
+                    name: name.to_string(),
+                    keys: Vec::new(),
+                })
+                .collect();
+            scene
+        };
+        let scene = SceneCombiner::default().combine(vec![
+            create_scene(&["body", "body_1"]),
+            create_scene(&["body", "head"]),
+        ]);
+
+        let mesh_names: Vec<&str> = scene.meshes.iter().map(|mesh| mesh.name.as_str()).collect();
+        assert_eq!(mesh_names, vec!["body", "body_1", "body_2", "head"]);
+        let animation = &scene.animations[1];
+        let mesh_channels: Vec<&str> = animation
+            .mesh_channels
+            .iter()
+            .map(AiMeshAnim::name)
+            .collect();
+        assert_eq!(mesh_channels, vec!["body_2", "head"]);
+        let morph_channels: Vec<&str> = animation
+            .morph_channels
+            .iter()
+            .map(|channel| channel.name.as_str())
+            .collect();
+        assert_eq!(morph_channels, vec!["body_2", "head"]);
+        assert_eq!(scene.animations[0].mesh_channels[0].name(), "body");
+
+        let combiner = SceneCombiner {
+            resolve_name_collisions: false,
+            ..Default::default()
+        };
+        let scene = combiner.combine(vec![create_scene(&["body"]), create_scene(&["body"])]);
+        assert_eq!(scene.meshes[1].name, "body");
+        assert_eq!(scene.animations[1].mesh_channels[0].name(), "body");
+    }
+}
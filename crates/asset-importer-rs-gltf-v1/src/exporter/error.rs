@@ -3,6 +3,8 @@ use std::{fmt::Display, io, string::FromUtf8Error};
 #[derive(Debug)]
 pub enum GltfExportError {
     MissingMaterial,
+    /// A mesh has more vertices than 16 bit indices can address, split large meshes first.
+    MeshTooLarge(String),
     UTFError(FromUtf8Error),
     Io(io::Error),
     Json(serde_json::Error),
@@ -12,6 +14,9 @@ impl Display for GltfExportError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GltfExportError::MissingMaterial => write!(f, "Missing Material"),
+            GltfExportError::MeshTooLarge(name) => {
+                write!(f, "Mesh {} has too many vertices for 16 bit indices", name)
+            }
             GltfExportError::UTFError(error) => write!(f, "UTF Error: {}", error),
             GltfExportError::Io(error) => write!(f, "IO Error: {}", error),
             GltfExportError::Json(error) => write!(f, "JSON Error: {}", error),
@@ -437,6 +437,7 @@ mod tests {
                 AiTexel::new(0, 0, 255, 255),
                 AiTexel::new(255, 255, 255, 255),
             ],
+            ..Default::default()
         };
         scene.textures.push(texture);
 
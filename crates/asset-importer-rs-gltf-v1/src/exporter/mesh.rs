@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
 use asset_importer_rs_scene::{
-    AI_MAX_NUMBER_OF_TEXTURECOORDS, AiPrimitiveType, AiQuaternion, AiScene, AiVector2D, AiVector3D,
-    ai_real_to_f32,
+    AI_MAX_NUMBER_OF_TEXTURECOORDS, AiMatrix4x4, AiPrimitiveType, AiQuaternion, AiReal, AiScene,
+    AiVector2D, AiVector3D, ai_real_to_f32,
 };
 use gltf_v1::json::{
     Accessor, BufferView, Mesh, Root, StringIndex,
@@ -14,7 +14,7 @@ use gltf_v1::json::{
 
 use crate::{
     GltfExporter,
-    exporter::{error::GltfExportError, generate_unique_name},
+    exporter::{error::GltfExportError, generate_unique_name, skin::bound_skeleton},
 };
 
 impl GltfExporter {
@@ -24,17 +24,18 @@ impl GltfExporter {
         root: &mut Root,
         body_buffer_data: &mut Vec<u8>,
         mesh_index_map: &HashMap<usize, String>,
+        node_index_map: &HashMap<usize, String>,
         material_index_map: &HashMap<usize, String>,
     ) -> Result<(), GltfExportError> {
         //@TODO: Add support for OPEN3DGC
-        //@TODO: Add support for skins
-        // let mut create_skin = false;
-        // for mesh in &scene.meshes {
-        //     if !mesh.bones.is_empty() {
-        //         create_skin = true;
-        //     }
-        // }
         let mut unique_names_map: HashMap<String, u32> = HashMap::new();
+        let skin_names = self.export_skins(
+            scene,
+            root,
+            body_buffer_data,
+            node_index_map,
+            &mut unique_names_map,
+        );
 
         for mesh_index in 0..scene.meshes.len() {
             let ai_mesh = &scene.meshes[mesh_index];
@@ -105,7 +106,48 @@ impl GltfExporter {
                     .insert(Checked::Valid(Semantic::TexCoords(i as u32)), uvs);
             }
 
-            // Indices
+            // Joints and Weights, from the skeleton bound to the mesh keeping the 4 strongest influences
+            if let Some(skeleton_index) = bound_skeleton(scene, mesh_index)
+                && skin_names[skeleton_index].is_some()
+            {
+                let num_verts = ai_mesh.vertices.len();
+                let mut vertex_influences: Vec<Vec<(usize, f32)>> = vec![Vec::new(); num_verts];
+                for (joint_index, bone) in scene.skeletons[skeleton_index]
+                    .bones()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bone)| bone.mesh_index() == Some(mesh_index))
+                {
+                    for weight in bone.weights() {
+                        if let Some(influences) = vertex_influences.get_mut(weight.vertex_id) {
+                            influences.push((joint_index, ai_real_to_f32(weight.weight)));
+                        }
+                    }
+                }
+                let mut joints: Vec<[f32; 4]> = vec![[0.0; 4]; num_verts];
+                let mut weights: Vec<[f32; 4]> = vec![[0.0; 4]; num_verts];
+                for (vertex_index, influences) in vertex_influences.iter_mut().enumerate() {
+                    influences.sort_by(|x, y| y.1.total_cmp(&x.1));
+                    for (i, (joint_index, weight)) in influences.iter().take(4).enumerate() {
+                        joints[vertex_index][i] = *joint_index as f32;
+                        weights[vertex_index][i] = *weight;
+                    }
+                }
+                let joints = export_float_4(root, body_buffer_data, &joints, &mut unique_names_map);
+                primitive
+                    .attributes
+                    .insert(Checked::Valid(Semantic::Joints(0)), joints);
+                let weights =
+                    export_float_4(root, body_buffer_data, &weights, &mut unique_names_map);
+                primitive
+                    .attributes
+                    .insert(Checked::Valid(Semantic::Weights(0)), weights);
+            }
+
+            // Indices, glTF 1.0 only guarantees support for 16 bit indices
+            if ai_mesh.vertices.len() > usize::from(u16::MAX) + 1 {
+                return Err(GltfExportError::MeshTooLarge(ai_mesh.name.clone()));
+            }
             if !ai_mesh.faces.is_empty() {
                 let indices_per_face = ai_mesh.faces[0].len();
                 let mut indices = Vec::with_capacity(ai_mesh.faces.len() * indices_per_face);
@@ -355,6 +397,98 @@ pub(crate) fn export_vector_4d(
     )
 }
 
+pub(crate) fn export_float_4(
+    root: &mut Root,
+    buffer_data: &mut Vec<u8>,
+    float_data: &[[f32; 4]],
+    unique_names_map: &mut HashMap<String, u32>,
+) -> StringIndex<Accessor> {
+    let mut min: [f32; 4] = if float_data.is_empty() {
+        [0.0; 4]
+    } else {
+        [f32::MAX; 4]
+    };
+    let mut max: [f32; 4] = if float_data.is_empty() {
+        [0.0; 4]
+    } else {
+        [f32::MIN; 4]
+    };
+    let mut data: Vec<u8> = Vec::with_capacity(float_data.len() * 4 * 4);
+    for values in float_data {
+        for (i, value) in values.iter().enumerate() {
+            if *value < min[i] {
+                min[i] = *value;
+            }
+            if *value > max[i] {
+                max[i] = *value;
+            }
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    export_data(
+        root,
+        buffer_data,
+        &data,
+        unique_names_map,
+        AccessorExporter {
+            target: BufferViewType::ArrayBuffer,
+            type_in: Type::VEC4,
+            type_out: Type::VEC4,
+            component_type: ComponentType::Float,
+            count: float_data.len() as u32,
+            min: min.to_vec(),
+            max: max.to_vec(),
+        },
+    )
+}
+
+pub(crate) fn export_matrix_4x4(
+    root: &mut Root,
+    buffer_data: &mut Vec<u8>,
+    matrix_data: &[AiMatrix4x4],
+    unique_names_map: &mut HashMap<String, u32>,
+) -> StringIndex<Accessor> {
+    let mut min: [f32; 16] = if matrix_data.is_empty() {
+        [0.0; 16]
+    } else {
+        [f32::MAX; 16]
+    };
+    let mut max: [f32; 16] = if matrix_data.is_empty() {
+        [0.0; 16]
+    } else {
+        [f32::MIN; 16]
+    };
+    let mut data: Vec<u8> = Vec::with_capacity(matrix_data.len() * 16 * 4);
+    for matrix in matrix_data {
+        let matrix: [AiReal; 16] = matrix.clone().into();
+        for (i, value) in matrix.iter().enumerate() {
+            let value = ai_real_to_f32(*value);
+            if value < min[i] {
+                min[i] = value;
+            }
+            if value > max[i] {
+                max[i] = value;
+            }
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    export_data(
+        root,
+        buffer_data,
+        &data,
+        unique_names_map,
+        AccessorExporter {
+            target: BufferViewType::ArrayBuffer,
+            type_in: Type::MAT4,
+            type_out: Type::MAT4,
+            component_type: ComponentType::Float,
+            count: matrix_data.len() as u32,
+            min: min.to_vec(),
+            max: max.to_vec(),
+        },
+    )
+}
+
 pub(crate) fn export_data(
     root: &mut Root,
     buffer_data: &mut Vec<u8>,
@@ -426,7 +560,10 @@ mod tests {
     use crate::Output;
 
     use super::*;
-    use asset_importer_rs_scene::{AiMesh, AiPrimitiveType, AiVector2D, AiVector3D};
+    use asset_importer_rs_scene::{
+        AiMesh, AiNode, AiPrimitiveType, AiSkeleton, AiSkeletonBone, AiVector2D, AiVector3D,
+        AiVertexWeight,
+    };
     use std::collections::HashMap;
 
     fn create_test_mesh() -> AiMesh {
@@ -476,6 +613,7 @@ mod tests {
             &mut root,
             &mut body_buffer_data,
             &mesh_index_map,
+            &HashMap::new(),
             &material_index_map,
         );
         assert!(result.is_ok());
@@ -541,6 +679,7 @@ mod tests {
             &mut root,
             &mut body_buffer_data,
             &mesh_index_map,
+            &HashMap::new(),
             &material_index_map,
         );
         assert!(result.is_ok());
@@ -556,6 +695,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_export_meshes_skinned() {
+        let mut scene = AiScene::default();
+        scene.meshes.push(create_test_mesh());
+        scene.nodes.arena.push(AiNode {
+            name: "root".to_string(),
+            mesh_indexes: vec![0],
+            children: vec![1],
+            ..Default::default()
+        });
+        scene.nodes.arena.push(AiNode {
+            name: "joint".to_string(),
+            parent: Some(0),
+            ..Default::default()
+        });
+        scene.nodes.root = Some(0);
+        let bone = AiSkeletonBone::new(None, 1, AiMatrix4x4::identity(), AiMatrix4x4::identity())
+            .with_mesh(
+                0,
+                vec![AiVertexWeight::new(0, 1.0), AiVertexWeight::new(2, 0.5)],
+            );
+        scene.skeletons = vec![AiSkeleton::new("armature".to_string(), vec![bone]).unwrap()];
+
+        let mut root = Root::default();
+        let mut body_buffer_data = Vec::new();
+        let mut material_index_map = HashMap::new();
+        material_index_map.insert(0, "material_0".to_string());
+
+        let exporter = GltfExporter::new(Output::Standard);
+        let (mesh_index_map, node_index_map) =
+            exporter.export_nodes(&scene, &mut root, 0.0001).unwrap();
+        exporter
+            .export_meshes(
+                &scene,
+                &mut root,
+                &mut body_buffer_data,
+                &mesh_index_map,
+                &node_index_map,
+                &material_index_map,
+            )
+            .unwrap();
+
+        let skin = root.skins.get("armature").unwrap();
+        assert_eq!(skin.joint_names.len(), 1);
+        assert_eq!(skin.joint_names[0].value(), "joint");
+        let inverse_bind_matrices = root
+            .accessors
+            .get(skin.inverse_bind_matrices.value())
+            .unwrap();
+        assert_eq!(inverse_bind_matrices.type_, Checked::Valid(Type::MAT4));
+        assert_eq!(inverse_bind_matrices.count, 1);
+
+        let joint = root.nodes.get("joint").unwrap();
+        assert_eq!(joint.joint_name.as_deref(), Some("joint"));
+        let node = root.nodes.get("root").unwrap();
+        assert_eq!(node.skin.as_ref().unwrap().value(), "armature");
+        assert_eq!(node.skeletons.len(), 1);
+        assert_eq!(node.skeletons[0].value(), "joint");
+
+        let primitive = &root.meshes.get(&mesh_index_map[&0]).unwrap().primitives[0];
+        let weights = primitive
+            .attributes
+            .get(&Checked::Valid(Semantic::Weights(0)))
+            .unwrap();
+        let weights = root.accessors.get(weights.value()).unwrap();
+        assert_eq!(weights.type_, Checked::Valid(Type::VEC4));
+        assert_eq!(weights.count, 3);
+        assert_eq!(weights.max, vec![1.0, 0.0, 0.0, 0.0]);
+        assert!(
+            primitive
+                .attributes
+                .contains_key(&Checked::Valid(Semantic::Joints(0)))
+        );
+    }
+
+    #[test]
+    fn test_export_meshes_too_large() {
+        let mut scene = AiScene::default();
+        let mut mesh = create_test_mesh();
+        mesh.vertices = vec![AiVector3D::new(0.0, 0.0, 0.0); usize::from(u16::MAX) + 2];
+        mesh.normals.clear();
+        mesh.texture_coords = [const { None }; AI_MAX_NUMBER_OF_TEXTURECOORDS];
+        scene.meshes.push(mesh);
+
+        let mut root = Root::default();
+        let mut body_buffer_data = Vec::new();
+        let mut mesh_index_map = HashMap::new();
+        mesh_index_map.insert(0, "mesh_0".to_string());
+        let mut material_index_map = HashMap::new();
+        material_index_map.insert(0, "material_0".to_string());
+
+        let exporter = GltfExporter::new(Output::Standard);
+
+        let result = exporter.export_meshes(
+            &scene,
+            &mut root,
+            &mut body_buffer_data,
+            &mesh_index_map,
+            &HashMap::new(),
+            &material_index_map,
+        );
+        assert!(matches!(result, Err(GltfExportError::MeshTooLarge(_))));
+    }
+
     #[test]
     fn test_export_data_vector2d() {
         let mut root = Root::default();
@@ -18,6 +18,7 @@ mod error;
 mod material;
 mod mesh;
 mod node;
+mod skin;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Default)]
@@ -51,7 +52,8 @@ impl AiExport for GltfExporter {
         properties: &ExportProperties,
         exporter: &DataExporter<'_>,
     ) -> Result<(), GltfExportError> {
-        //@TODO: GLTF should always have large meshes split on export.
+        // Meshes must fit 16 bit indices. The exporter entry enforces SplitLargeMeshes, which runs
+        // on a copy of the scene before it reaches this exporter.
         let mut body_buffer_data: Vec<u8> = Vec::new();
 
         let mut root = gltf_v1::json::Root {
@@ -90,13 +92,15 @@ impl AiExport for GltfExporter {
             })
             .unwrap_or(AI_CONFIG_CHECK_IDENTITY_MATRIX_EPSILON_DEFAULT);
 
-        let mesh_index_map = self.export_nodes(scene, &mut root, config_epsilon)?;
+        let (mesh_index_map, node_index_map) =
+            self.export_nodes(scene, &mut root, config_epsilon)?;
 
         self.export_meshes(
             scene,
             &mut root,
             &mut body_buffer_data,
             &mesh_index_map,
+            &node_index_map,
             &material_index_map,
         )?;
 
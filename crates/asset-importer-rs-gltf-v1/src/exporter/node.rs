@@ -8,18 +8,23 @@ use crate::{
     exporter::{error::GltfExportError, generate_unique_name},
 };
 
+/// Names of exported objects by their index in the scene.
+pub(crate) type IndexNameMap = HashMap<usize, String>;
+
 impl GltfExporter {
+    /// Exports the node hierarchy, returning the names given to the meshes and to the nodes.
     pub(crate) fn export_nodes(
         &self,
         scene: &AiScene,
         root: &mut Root,
         config_epsilon: f32,
-    ) -> Result<HashMap<usize, String>, GltfExportError> {
+    ) -> Result<(IndexNameMap, IndexNameMap), GltfExportError> {
         if scene.nodes.arena.is_empty() {
-            return Ok(HashMap::new());
+            return Ok((HashMap::new(), HashMap::new()));
         }
         let mut unique_names_map: HashMap<String, u32> = HashMap::new();
         let mut mesh_index_map = HashMap::new();
+        let mut node_index_map = HashMap::new();
         let mut queue: VecDeque<(usize, Option<String>)> = VecDeque::new();
         let mut checked_nodes: HashSet<usize> = HashSet::new();
         queue.push_back((scene.nodes.root.unwrap_or(0), None));
@@ -36,6 +41,7 @@ impl GltfExporter {
                 &ai_node.name
             };
             node.name = Some(generate_unique_name(base_name, &mut unique_names_map));
+            node_index_map.insert(node_index, node.name.clone().unwrap());
             if let Some(parent_name) = parent_name
                 && let Some(parent) = root.nodes.get_mut(&parent_name)
             {
@@ -62,7 +68,7 @@ impl GltfExporter {
             }
             root.nodes.insert(node.name.clone().unwrap(), node);
         }
-        Ok(mesh_index_map)
+        Ok((mesh_index_map, node_index_map))
     }
 }
 
@@ -79,7 +85,7 @@ mod tests {
 
         let result = exporter.export_nodes(&scene, &mut root, 0.0001);
         assert!(result.is_ok());
-        let mesh_index_map = result.unwrap();
+        let (mesh_index_map, _) = result.unwrap();
         assert!(mesh_index_map.is_empty());
         assert!(root.nodes.is_empty());
     }
@@ -103,7 +109,7 @@ mod tests {
 
         let result = exporter.export_nodes(&scene, &mut root, 0.0001);
         assert!(result.is_ok());
-        let mesh_index_map = result.unwrap();
+        let (mesh_index_map, _) = result.unwrap();
         assert!(mesh_index_map.is_empty());
         assert_eq!(root.nodes.len(), 1);
         assert!(root.nodes.contains_key("test_node"));
@@ -135,7 +141,7 @@ mod tests {
 
         let result = exporter.export_nodes(&scene, &mut root, 0.0001);
         assert!(result.is_ok());
-        let mesh_index_map = result.unwrap();
+        let (mesh_index_map, _) = result.unwrap();
         assert_eq!(mesh_index_map.len(), 1);
         assert!(mesh_index_map.contains_key(&0));
         assert_eq!(root.nodes.len(), 1);
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use asset_importer_rs_scene::{AiMatrix4x4, AiScene};
+use gltf_v1::json::{Root, Skin, StringIndex};
+
+use crate::exporter::{GltfExporter, generate_unique_name, mesh::export_matrix_4x4};
+
+impl GltfExporter {
+    /// Exports the scene's skeletons as skins, returning the skin name of each skeleton.
+    ///
+    /// Skeletons only differing in the mesh they deform share a skin. Joint nodes take their node
+    /// name as joint name, and nodes holding a mesh bound to a skeleton instance its skin.
+    /// Skeletons with bones on nodes that were not exported have no skin.
+    pub(crate) fn export_skins(
+        &self,
+        scene: &AiScene,
+        root: &mut Root,
+        body_buffer_data: &mut Vec<u8>,
+        node_index_map: &HashMap<usize, String>,
+        unique_names_map: &mut HashMap<String, u32>,
+    ) -> Vec<Option<String>> {
+        let mut skin_names: Vec<Option<String>> = Vec::with_capacity(scene.skeletons.len());
+        for (skeleton_index, skeleton) in scene.skeletons.iter().enumerate() {
+            if let Some(previous) = scene.skeletons[..skeleton_index]
+                .iter()
+                .position(|previous| previous.same_bones(skeleton))
+            {
+                skin_names.push(skin_names[previous].clone());
+                continue;
+            }
+            let Some(joint_names) = skeleton
+                .bones()
+                .iter()
+                .map(|bone| node_index_map.get(&bone.node_index()).cloned())
+                .collect::<Option<Vec<String>>>()
+            else {
+                skin_names.push(None);
+                continue;
+            };
+            for joint_name in &joint_names {
+                if let Some(node) = root.nodes.get_mut(joint_name) {
+                    node.joint_name = Some(joint_name.clone());
+                }
+            }
+
+            let inverse_bind_matrices: Vec<AiMatrix4x4> = skeleton
+                .bones()
+                .iter()
+                .map(|bone| bone.offset_matrix().clone())
+                .collect();
+            let inverse_bind_matrices = export_matrix_4x4(
+                root,
+                body_buffer_data,
+                &inverse_bind_matrices,
+                unique_names_map,
+            );
+            let base_name = if skeleton.name().is_empty() {
+                "skin"
+            } else {
+                skeleton.name()
+            };
+            let skin_name = generate_unique_name(base_name, unique_names_map);
+            let skin = Skin {
+                bind_shape_matrix: [
+                    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+                ],
+                inverse_bind_matrices,
+                joint_names: joint_names.into_iter().map(StringIndex::new).collect(),
+                name: Some(skin_name.clone()),
+            };
+            root.skins.insert(skin_name.clone(), skin);
+            skin_names.push(Some(skin_name));
+        }
+
+        //instance the skins on the nodes holding bound meshes, a node holds at most one skin
+        for (node_index, ai_node) in scene.nodes.arena.iter().enumerate() {
+            let Some(node_name) = node_index_map.get(&node_index) else {
+                continue;
+            };
+            let Some((skeleton_index, skin_name)) = ai_node
+                .mesh_indexes
+                .iter()
+                .filter_map(|mesh_index| bound_skeleton(scene, *mesh_index))
+                .find_map(|skeleton_index| {
+                    skin_names[skeleton_index]
+                        .as_ref()
+                        .map(|skin_name| (skeleton_index, skin_name))
+                })
+            else {
+                continue;
+            };
+            let skeletons = scene.skeletons[skeleton_index]
+                .bones()
+                .iter()
+                .filter(|bone| bone.parent().is_none())
+                .filter_map(|bone| node_index_map.get(&bone.node_index()))
+                .map(|name| StringIndex::new(name.clone()))
+                .collect();
+            if let Some(node) = root.nodes.get_mut(node_name) {
+                node.skin = Some(StringIndex::new(skin_name.clone()));
+                node.skeletons = skeletons;
+            }
+        }
+        skin_names
+    }
+}
+
+/// Finds the skeleton with bones bound to a mesh.
+pub(crate) fn bound_skeleton(scene: &AiScene, mesh_index: usize) -> Option<usize> {
+    scene.skeletons.iter().position(|skeleton| {
+        skeleton
+            .bones()
+            .iter()
+            .any(|bone| bone.mesh_index() == Some(mesh_index))
+    })
+}
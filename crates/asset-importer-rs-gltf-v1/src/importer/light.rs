@@ -6,7 +6,7 @@ use gltf_v1::{
 };
 
 use super::error::GLTFImportError;
-use asset_importer_rs_scene::{AiLight, AiLightSourceType};
+use asset_importer_rs_scene::{AiLight, AiLightFalloff, AiLightSourceType};
 
 use super::GltfImporter;
 
@@ -29,7 +29,6 @@ impl GltfImporter {
                 .name()
                 .map(|x| x.to_string())
                 .unwrap_or(format!("{}", index));
-            light_map.insert(name.clone(), index);
             if light_map.contains_key(&name) {
                 return Err(GLTFImportError::DuplicateName);
             }
@@ -45,6 +44,11 @@ impl GltfImporter {
                 attenuation: light.constant_attenuation(),
                 attenuation_linear: light.linear_attenuation(),
                 attenuation_quadratic: light.quadratic_attenuation(),
+                falloff: AiLightFalloff::from_attenuation(
+                    light.constant_attenuation(),
+                    light.linear_attenuation(),
+                    light.quadratic_attenuation(),
+                ),
                 ..AiLight::default()
             };
             ai_light.source_type = match light.kind() {
@@ -53,8 +57,11 @@ impl GltfImporter {
                 Kind::Point => AiLightSourceType::Point,
                 Kind::Spot => AiLightSourceType::Spot,
             };
+            if ai_light.source_type == AiLightSourceType::Directional {
+                ai_light.falloff = AiLightFalloff::None;
+            }
             lights.push(ai_light);
         }
-        Ok(ImportLights(Vec::new(), HashMap::new()))
+        Ok(ImportLights(lights, light_map))
     }
 }
@@ -19,7 +19,7 @@ use gltf::{
 
 use asset_importer_rs_scene::{
     AiColor4D, AiMatrix4x4, AiPrimitiveType, AiQuaternion, AiReal, AiScene, AiVector2D, AiVector3D,
-    ai_real_to_f32,
+    AiVertexWeight, ai_real_to_f32,
 };
 use serde_json::{Number, Value};
 
@@ -40,22 +40,49 @@ impl Gltf2Exporter {
         export_anim_normals: bool,
         //export_skeleton: bool,
     ) -> Result<(), Gltf2ExportError> {
-        let create_skin = scene.meshes.iter().any(|x| !x.bones.is_empty());
-        let mut inverse_bind_matrices_data: Vec<AiMatrix4x4> = Vec::new();
-        let mut skin = if create_skin {
-            Some(Skin {
+        //skins are created from the scene's skeletons, meshes not covered by any skeleton share a fallback skin
+        //skeletons only differing in the mesh they deform share a skin
+        let mut skins: Vec<(Skin, Vec<AiMatrix4x4>)> = Vec::new();
+        let mut skeleton_skins: Vec<usize> = Vec::with_capacity(scene.skeletons.len());
+        for (skeleton_index, skeleton) in scene.skeletons.iter().enumerate() {
+            if let Some(previous) = scene.skeletons[..skeleton_index]
+                .iter()
+                .position(|previous| previous.same_bones(skeleton))
+            {
+                skeleton_skins.push(skeleton_skins[previous]);
+                continue;
+            }
+            let name = if skeleton.name().is_empty() {
+                "skin"
+            } else {
+                skeleton.name()
+            };
+            let skin = Skin {
                 extensions: Default::default(),
                 extras: Default::default(),
                 inverse_bind_matrices: Default::default(),
-                joints: Default::default(),
-                name: Some(generate_unique_name("skin", unique_names_map)),
-                skeleton: Default::default(),
-            })
-        } else {
-            None
-        };
+                joints: skeleton
+                    .bones()
+                    .iter()
+                    .map(|bone| Index::new(bone.node_index() as u32))
+                    .collect(),
+                name: Some(generate_unique_name(name, unique_names_map)),
+                skeleton: skeleton.root_bone().map(|bone| {
+                    Index::new(bone.armature_index().unwrap_or(bone.node_index()) as u32)
+                }),
+            };
+            let inverse_bind_matrices = skeleton
+                .bones()
+                .iter()
+                .map(|bone| bone.offset_matrix().clone())
+                .collect();
+            skins.push((skin, inverse_bind_matrices));
+            skeleton_skins.push(skins.len() - 1);
+        }
+        let mut fallback_skin: Option<usize> = None;
+        let mut mesh_to_skin: HashMap<usize, usize> = HashMap::new();
         let mut meshes: Vec<Mesh> = Vec::new();
-        for ai_mesh in &scene.meshes {
+        for (mesh_index, ai_mesh) in scene.meshes.iter().enumerate() {
             let mut attributes: BTreeMap<Checked<Semantic>, Index<Accessor>> = BTreeMap::new();
 
             //handle positions
@@ -83,9 +110,9 @@ impl Gltf2Exporter {
                 root,
                 buffer_data,
                 &ai_mesh
-                    .tangents
-                    .iter()
-                    .map(|x| x.norm().to_quat(1.0))
+                    .tangents_with_handedness()
+                    .into_iter()
+                    .map(|[x, y, z, w]| AiVector3D::new(x, y, z).norm().to_quat(w))
                     .collect(),
             );
             if let Some(tangents) = tangents {
@@ -137,7 +164,7 @@ impl Gltf2Exporter {
                 }
             }
 
-            //handle indices
+            //handle indices, glTF 2.0 supports 32 bit indices so large meshes need no splitting
             let indices = if !ai_mesh.faces.is_empty() {
                 let mut indices: Vec<u32> = Vec::new();
                 let n_indices_per_face: u32 = ai_mesh.faces[0].len() as u32;
@@ -164,16 +191,42 @@ impl Gltf2Exporter {
                 Mode::Triangles
             };
 
-            //handle skin
-            if let Some(ref mut skin) = skin
-                && !ai_mesh.bones.is_empty()
-            {
-                let num_verts = ai_mesh.vertices.len();
-                let mut all_vertices_pairs: Vec<Vec<(usize, AiReal)>> = Vec::new();
-                let mut joints_per_vertex: Vec<u32> = Vec::new();
-                let mut max_joint_per_vertex: u32 = 0;
-                joints_per_vertex.resize(num_verts, 0);
-                all_vertices_pairs.resize(num_verts, Vec::new());
+            //handle skin, weights of skeleton bones bound to the mesh take precedence over mesh bones
+            let bound_skeleton = scene.skeletons.iter().position(|skeleton| {
+                skeleton
+                    .bones()
+                    .iter()
+                    .any(|bone| bone.mesh_index() == Some(mesh_index))
+            });
+            let mut joint_weights: Vec<(usize, &[AiVertexWeight])> = Vec::new();
+            let skin_index = if let Some(skeleton_index) = bound_skeleton {
+                joint_weights = scene.skeletons[skeleton_index]
+                    .bones()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bone)| bone.mesh_index() == Some(mesh_index))
+                    .map(|(joint_index, bone)| (joint_index, bone.weights()))
+                    .collect();
+                Some(skeleton_skins[skeleton_index])
+            } else if !ai_mesh.bones.is_empty() {
+                let skin_index = match find_skeleton(scene, mesh_index) {
+                    Some(skeleton_index) => skeleton_skins[skeleton_index],
+                    None => *fallback_skin.get_or_insert_with(|| {
+                        skins.push((
+                            Skin {
+                                extensions: Default::default(),
+                                extras: Default::default(),
+                                inverse_bind_matrices: Default::default(),
+                                joints: Default::default(),
+                                name: Some(generate_unique_name("skin", unique_names_map)),
+                                skeleton: Default::default(),
+                            },
+                            Vec::new(),
+                        ));
+                        skins.len() - 1
+                    }),
+                };
+                let (skin, inverse_bind_matrices_data) = &mut skins[skin_index];
                 for bone in &ai_mesh.bones {
                     //We do this Check to make sure nodes exist and to potentially get validate node names once GLTF-RS supports Node Names
                     if root.nodes.get(bone.node_index).is_some() {
@@ -194,14 +247,31 @@ impl Gltf2Exporter {
                             let last_index = skin.joints.len() - 1;
                             (last_index, &skin.joints[last_index])
                         };
-
-                        //Populate Data Structures
-                        for weight in &bone.weights {
-                            all_vertices_pairs[weight.vertex_id].push((joint_index, weight.weight));
-                            joints_per_vertex[weight.vertex_id] += 1;
-                            max_joint_per_vertex =
-                                u32::max(max_joint_per_vertex, joints_per_vertex[weight.vertex_id]);
+                        joint_weights.push((joint_index, &bone.weights));
+                    }
+                }
+                Some(skin_index)
+            } else {
+                None
+            };
+            if let Some(skin_index) = skin_index {
+                mesh_to_skin.insert(mesh_index, skin_index);
+                let num_verts = ai_mesh.vertices.len();
+                let mut all_vertices_pairs: Vec<Vec<(usize, AiReal)>> = Vec::new();
+                let mut joints_per_vertex: Vec<u32> = Vec::new();
+                let mut max_joint_per_vertex: u32 = 0;
+                joints_per_vertex.resize(num_verts, 0);
+                all_vertices_pairs.resize(num_verts, Vec::new());
+                //Populate Data Structures
+                for (joint_index, weights) in joint_weights {
+                    for weight in weights {
+                        if weight.vertex_id >= num_verts {
+                            continue;
                         }
+                        all_vertices_pairs[weight.vertex_id].push((joint_index, weight.weight));
+                        joints_per_vertex[weight.vertex_id] += 1;
+                        max_joint_per_vertex =
+                            u32::max(max_joint_per_vertex, joints_per_vertex[weight.vertex_id]);
                     }
                 }
 
@@ -341,30 +411,26 @@ impl Gltf2Exporter {
         }
 
         //finish skin export
-        if skin.is_some() {
-            let mut skin_ref = skin.unwrap();
+        let mut skin_indices: Vec<Index<Skin>> = Vec::with_capacity(skins.len());
+        for (mut skin, inverse_bind_matrices_data) in skins {
             //export inverse_bind_matrices
             let inverse_mat_data =
                 AccessorExporter::export_mat4(root, buffer_data, inverse_bind_matrices_data);
             if let Some(inverse_mat_data) = inverse_mat_data {
-                skin_ref.inverse_bind_matrices = Some(root.push(inverse_mat_data));
-            }
-
-            // Find nodes that contain a mesh with bones and add "skeletons" and "skin" attributes to those nodes.
-            // @todo: add skeleton support
-            let skin = root.push(skin_ref);
-            for node_index in 0..root.nodes.len() {
-                if let Some(node) = root.nodes.get_mut(node_index)
-                    && let Some(node_meshes) = node_index_to_meshes.get(&node_index)
-                {
-                    for mesh_index in node_meshes {
-                        if let Some(mesh) = meshes.get(*mesh_index)
-                            && mesh.weights.is_some()
-                        {
-                            node.skin = Some(skin);
-                        }
-                    }
-                }
+                skin.inverse_bind_matrices = Some(root.push(inverse_mat_data));
+            }
+            skin_indices.push(root.push(skin));
+        }
+
+        // Find nodes that contain a mesh with bones and add the "skin" attribute to those nodes.
+        for node_index in 0..root.nodes.len() {
+            if let Some(node) = root.nodes.get_mut(node_index)
+                && let Some(node_meshes) = node_index_to_meshes.get(&node_index)
+                && let Some(skin_index) = node_meshes
+                    .iter()
+                    .find_map(|mesh_index| mesh_to_skin.get(mesh_index))
+            {
+                node.skin = Some(skin_indices[*skin_index]);
             }
         }
 
@@ -399,6 +465,19 @@ impl Gltf2Exporter {
     }
 }
 
+/// Finds a skeleton for a mesh that no skeleton bone is bound to.
+///
+/// A skeleton matches if it contains a bone for every node the mesh's bones are attached to.
+fn find_skeleton(scene: &AiScene, mesh_index: usize) -> Option<usize> {
+    let ai_mesh = scene.meshes.get(mesh_index)?;
+    scene.skeletons.iter().position(|skeleton| {
+        ai_mesh
+            .bones
+            .iter()
+            .all(|bone| skeleton.find_bone_by_node(bone.node_index).is_some())
+    })
+}
+
 #[derive(Debug)]
 pub(crate) struct AccessorExporter;
 
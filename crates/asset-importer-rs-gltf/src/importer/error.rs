@@ -1,5 +1,7 @@
 use std::{error::Error, fmt::Display, io, path::PathBuf};
 
+use asset_importer_rs_scene::AiSkeletonError;
+
 #[derive(Debug)]
 pub enum MeshError {
     ExceedsBounds,
@@ -34,12 +36,14 @@ pub enum Gltf2ImportError {
     FileOpenError(io::Error, PathBuf),
     FileFormatError(gltf::Error),
     MeshError(MeshError),
+    SkeletonError(AiSkeletonError),
 }
 
 impl Display for Gltf2ImportError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Gltf2ImportError::MeshError(error) => write!(f, "Mesh Error: {}", error),
+            Gltf2ImportError::SkeletonError(error) => write!(f, "Skeleton Error: {}", error),
             Gltf2ImportError::FileOpenError(error, path) => {
                 write!(f, "File Open Error: '{}': {}", path.display(), error)
             }
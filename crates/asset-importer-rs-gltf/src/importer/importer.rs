@@ -177,6 +177,7 @@ impl AiImporter for Gltf2Importer {
         let mut lights = Gltf2Importer::import_lights(&document)?;
 
         //import nodes
+        let node_map = Gltf2Importer::import_node_map(&document);
         let (nodes, scene_name) = Gltf2Importer::import_nodes(
             &document,
             &buffer_data,
@@ -185,6 +186,16 @@ impl AiImporter for Gltf2Importer {
             &remapping_tables,
             &mut lights,
             &mut cameras,
+            &node_map,
+        )?;
+
+        //import skeletons
+        let skeletons = Gltf2Importer::import_skeletons(
+            &document,
+            &buffer_data,
+            &node_map,
+            &meshes,
+            &mesh_offsets,
         )?;
 
         //import animations
@@ -202,6 +213,7 @@ impl AiImporter for Gltf2Importer {
             materials: embedded_materials,
             textures: embedded_textures,
             nodes,
+            skeletons,
             metadata,
             ..AiScene::default()
         };
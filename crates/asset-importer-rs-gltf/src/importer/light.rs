@@ -1,4 +1,6 @@
-use asset_importer_rs_scene::{AiColor3D, AiLight, AiLightSourceType, AiVector3D};
+use asset_importer_rs_scene::{
+    AiColor3D, AiLight, AiLightFalloff, AiLightIntensityUnit, AiLightSourceType, AiVector3D,
+};
 
 use crate::importer::error::Gltf2ImportError;
 
@@ -26,6 +28,8 @@ impl Gltf2Importer {
                     ai_light.attenuation = 1.0;
                     ai_light.attenuation_linear = 0.0;
                     ai_light.attenuation_quadratic = 0.0;
+                    ai_light.intensity_unit = AiLightIntensityUnit::Lux;
+                    ai_light.falloff = AiLightFalloff::None;
                     ai_light.direction = AiVector3D::new(0.0, 0.0, -1.0);
                     ai_light.up = AiVector3D::new(0.0, 1.0, 0.0);
                     AiLightSourceType::Directional
@@ -34,6 +38,8 @@ impl Gltf2Importer {
                     ai_light.attenuation = 0.0;
                     ai_light.attenuation_linear = 0.0;
                     ai_light.attenuation_quadratic = 1.0;
+                    ai_light.intensity_unit = AiLightIntensityUnit::Candela;
+                    ai_light.falloff = AiLightFalloff::InverseSquare;
                     AiLightSourceType::Point
                 }
                 gltf::khr_lights_punctual::Kind::Spot {
@@ -43,6 +49,8 @@ impl Gltf2Importer {
                     ai_light.attenuation = 0.0;
                     ai_light.attenuation_linear = 0.0;
                     ai_light.attenuation_quadratic = 1.0;
+                    ai_light.intensity_unit = AiLightIntensityUnit::Candela;
+                    ai_light.falloff = AiLightFalloff::InverseSquare;
                     ai_light.inner_cone_angle = inner_cone_angle;
                     ai_light.outer_cone_angle = outer_cone_angle;
                     ai_light.direction = AiVector3D::new(0.0, 0.0, -1.0);
@@ -60,6 +68,8 @@ impl Gltf2Importer {
             ai_light.ambient_color = color_with_intensity;
             ai_light.diffuse_color = color_with_intensity;
             ai_light.specular_color = color_with_intensity;
+            ai_light.intensity = insensity;
+            ai_light.range = light.range();
 
             lights.push(ai_light);
         }
@@ -137,5 +147,10 @@ mod tests {
         let document = Document::from_json_without_validation(scene);
         let lights = Gltf2Importer::import_lights(&document).unwrap();
         assert_eq!(5, lights.len());
+        assert_eq!(lights[0].intensity, 15.0);
+        assert_eq!(lights[0].intensity_unit, AiLightIntensityUnit::Candela);
+        assert_eq!(lights[0].falloff, AiLightFalloff::InverseSquare);
+        assert_eq!(lights[0].range, None);
+        assert_eq!(lights[1].base_color(), AiColor3D::new(1.0, 1.0, 1.0));
     }
 }
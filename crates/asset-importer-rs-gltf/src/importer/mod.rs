@@ -7,6 +7,7 @@ mod material;
 mod mesh;
 mod metadata;
 mod node;
+mod skeleton;
 mod texture;
 
 pub use error::Gltf2ImportError;
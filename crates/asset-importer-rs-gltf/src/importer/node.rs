@@ -13,9 +13,36 @@ use asset_importer_rs_scene::{
 
 use crate::importer::error::Gltf2ImportError;
 
-use super::{importer::Gltf2Importer, mesh::ExtractData};
+use super::{
+    importer::Gltf2Importer,
+    mesh::ExtractData,
+    skeleton::{node_parents, skin_armature},
+};
 
 impl Gltf2Importer {
+    /// Maps glTF node indices of the default scene to their index in the imported node arena.
+    ///
+    /// This follows the same breadth-first order as [`Gltf2Importer::import_nodes`].
+    pub(crate) fn import_node_map(document: &Document) -> HashMap<usize, usize> {
+        let mut node_map = HashMap::new();
+        let Some(default_scene) = default_scene(document) else {
+            return node_map;
+        };
+        let asset_root_nodes: Vec<gltf::Node<'_>> = default_scene.nodes().collect();
+        // Multiple root nodes are placed below a generated root node
+        let mut next_index = if asset_root_nodes.len() > 1 { 1 } else { 0 };
+        for asset_root_node in asset_root_nodes {
+            let mut node_queue: VecDeque<gltf::Node<'_>> = VecDeque::from([asset_root_node]);
+            while let Some(node) = node_queue.pop_front() {
+                node_map.insert(node.index(), next_index);
+                next_index += 1;
+                node_queue.extend(node.children());
+            }
+        }
+        node_map
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn import_nodes(
         document: &Document,
         buffer_data: &[buffer::Data],
@@ -24,14 +51,13 @@ impl Gltf2Importer {
         remap_table: &[Vec<usize>],
         lights: &mut [AiLight],
         cameras: &mut [AiCamera],
+        node_map: &HashMap<usize, usize>,
     ) -> Result<(AiNodeTree, String), Gltf2ImportError> {
-        let mut default_scene = document.default_scene();
-        if default_scene.is_none() {
-            default_scene = document.scenes().next();
-        }
+        let default_scene = default_scene(document);
         if default_scene.is_none() {
             return Ok((AiNodeTree::default(), "".to_string()));
         }
+        let parents = node_parents(document);
 
         let asset_root_nodes: Vec<gltf::Node<'_>> =
             default_scene.as_ref().unwrap().nodes().collect();
@@ -46,6 +72,8 @@ impl Gltf2Importer {
                     remap_table,
                     lights,
                     cameras,
+                    node_map,
+                    &parents,
                 )?,
                 default_scene.unwrap().name().unwrap_or("").to_string(),
             )),
@@ -68,6 +96,8 @@ impl Gltf2Importer {
                         remap_table,
                         lights,
                         cameras,
+                        node_map,
+                        &parents,
                     )?);
                 }
                 Ok((
@@ -94,6 +124,13 @@ impl Gltf2Importer {
     }
 }
 
+fn default_scene(document: &Document) -> Option<gltf::Scene<'_>> {
+    document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+}
+
+#[allow(clippy::too_many_arguments)]
 fn import_node<'a>(
     root_node: gltf::Node<'a>,
     buffer_data: &'a [buffer::Data],
@@ -102,12 +139,20 @@ fn import_node<'a>(
     remap_table: &[Vec<usize>],
     lights: &mut [AiLight],
     cameras: &mut [AiCamera],
+    node_map: &HashMap<usize, usize>,
+    parents: &HashMap<usize, usize>,
 ) -> Result<AiNodeTree, Gltf2ImportError> {
     let mut ai_node_tree = AiNodeTree::default();
     let mut node_queue: VecDeque<(gltf::Node<'_>, Option<usize>)> = VecDeque::new();
     node_queue.push_back((root_node, None));
     while let Some((node, parent_index)) = node_queue.pop_front() {
-        let mut ai_node = AiNode::default();
+        let mut ai_node = AiNode {
+            name: node
+                .name()
+                .unwrap_or(node.index().to_string().as_str())
+                .to_string(),
+            ..AiNode::default()
+        };
 
         //handle extensions
         handle_extensions(&mut ai_node, &node);
@@ -129,6 +174,8 @@ fn import_node<'a>(
             ai_node.mesh_indexes.reserve(end - start);
             if let Some(skin) = node.skin() {
                 let asset_joints: Vec<Node<'_>> = skin.joints().collect();
+                let armature_index = skin_armature(&skin, parents)
+                    .and_then(|armature| node_map.get(&armature).copied());
                 let num_bones = asset_joints.len();
                 let bind_matrices = skin.inverse_bind_matrices().and_then(|x| {
                     let data_matrices: Vec<[f32; 16]> = x.extract_data(buffer_data, None).ok()?;
@@ -289,6 +336,12 @@ fn import_node<'a>(
                         if let Some(bind_matrix) = &bind_matrices {
                             ai_bone.offset_matrix = bind_matrix[i].clone();
                         }
+                        if let Some(node_index) = node_map.get(&joint.index()) {
+                            ai_bone.node_index = *node_index;
+                        }
+                        if let Some(armature_index) = armature_index {
+                            ai_bone.armature_index = armature_index;
+                        }
                         if !weights.is_empty() {
                             ai_bone.weights = weights.to_vec();
                         } else {
@@ -314,8 +367,9 @@ fn import_node<'a>(
         ai_node.parent = parent_index;
         ai_node_tree.arena.push(ai_node);
         let index = ai_node_tree.arena.len() - 1;
-        if parent_index.is_none() {
-            ai_node_tree.root = Some(index);
+        match parent_index {
+            Some(parent_index) => ai_node_tree.arena[parent_index].children.push(index),
+            None => ai_node_tree.root = Some(index),
         }
         for child in node.children() {
             node_queue.push_back((child, Some(index)));
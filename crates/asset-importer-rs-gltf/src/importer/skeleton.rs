@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use gltf::{Document, buffer};
+
+use asset_importer_rs_scene::{
+    AiMatrix4x4, AiMesh, AiReal, AiSkeleton, AiSkeletonBone, AiVertexWeight,
+};
+
+use crate::importer::error::Gltf2ImportError;
+
+use super::{importer::Gltf2Importer, mesh::ExtractData};
+
+impl Gltf2Importer {
+    /// Builds the skeletons of every skin.
+    ///
+    /// A skeleton bone deforms a single mesh, so a skin gets one skeleton per mesh it deforms,
+    /// with the weights of the mesh bones imported for its joints. Skins not used by any node
+    /// get a single skeleton without mesh bindings. Skins whose joints are not part of the
+    /// imported node tree are skipped.
+    pub(crate) fn import_skeletons(
+        document: &Document,
+        buffer_data: &[buffer::Data],
+        node_map: &HashMap<usize, usize>,
+        meshes: &[AiMesh],
+        mesh_offsets: &[u32],
+    ) -> Result<Vec<AiSkeleton>, Gltf2ImportError> {
+        let parents = node_parents(document);
+        // Meshes deformed by each skin, in order of first use
+        let mut skin_meshes: HashMap<usize, Vec<usize>> = HashMap::new();
+        for node in document.nodes() {
+            if !node_map.contains_key(&node.index()) {
+                continue;
+            }
+            let (Some(skin), Some(mesh)) = (node.skin(), node.mesh()) else {
+                continue;
+            };
+            let (Some(&start), Some(&end)) = (
+                mesh_offsets.get(mesh.index()),
+                mesh_offsets.get(mesh.index() + 1),
+            ) else {
+                continue;
+            };
+            let bound = skin_meshes.entry(skin.index()).or_default();
+            for mesh_index in start as usize..end as usize {
+                if !bound.contains(&mesh_index) {
+                    bound.push(mesh_index);
+                }
+            }
+        }
+
+        let mut skeletons = Vec::new();
+        for skin in document.skins() {
+            let joints: Vec<gltf::Node<'_>> = skin.joints().collect();
+            if joints
+                .iter()
+                .any(|joint| !node_map.contains_key(&joint.index()))
+            {
+                continue;
+            }
+            let joint_indices: HashMap<usize, usize> = joints
+                .iter()
+                .enumerate()
+                .map(|(index, joint)| (joint.index(), index))
+                .collect();
+
+            let bind_matrices: Vec<[f32; 16]> = match skin.inverse_bind_matrices() {
+                Some(accessor) => accessor
+                    .extract_data(buffer_data, None)
+                    .map_err(Gltf2ImportError::MeshError)?,
+                None => Vec::new(),
+            };
+
+            let armature =
+                skin_armature(&skin, &parents).and_then(|node| node_map.get(&node).copied());
+
+            let mut bones = Vec::with_capacity(joints.len());
+            for (index, joint) in joints.iter().enumerate() {
+                let offset_matrix = bind_matrices
+                    .get(index)
+                    .map(|matrix| AiMatrix4x4::from(matrix.map(|x| x as AiReal)))
+                    .unwrap_or_else(AiMatrix4x4::identity);
+                let local_matrix: AiMatrix4x4 = joint
+                    .transform()
+                    .matrix()
+                    .map(|x| x.map(|y| y as AiReal))
+                    .into();
+                let mut bone = AiSkeletonBone::new(
+                    joint_parent(joint.index(), &parents, &joint_indices),
+                    node_map[&joint.index()],
+                    offset_matrix,
+                    local_matrix,
+                );
+                if let Some(armature) = armature {
+                    bone = bone.with_armature(armature);
+                }
+                bones.push(bone);
+            }
+
+            let name = skin
+                .name()
+                .unwrap_or(skin.index().to_string().as_str())
+                .to_string();
+            let Some(mesh_indices) = skin_meshes.get(&skin.index()) else {
+                skeletons
+                    .push(AiSkeleton::new(name, bones).map_err(Gltf2ImportError::SkeletonError)?);
+                continue;
+            };
+            for &mesh_index in mesh_indices {
+                let Some(mesh) = meshes.get(mesh_index) else {
+                    continue;
+                };
+                let bound_bones = bones
+                    .iter()
+                    .map(|bone| {
+                        let weights: Vec<AiVertexWeight> = mesh
+                            .bones
+                            .iter()
+                            .find(|mesh_bone| mesh_bone.node_index == bone.node_index())
+                            .map(|mesh_bone| {
+                                mesh_bone
+                                    .weights
+                                    .iter()
+                                    .filter(|weight| weight.weight > 0.0)
+                                    .cloned()
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        bone.clone().with_mesh(mesh_index, weights)
+                    })
+                    .collect();
+                skeletons.push(
+                    AiSkeleton::new(name.clone(), bound_bones)
+                        .map_err(Gltf2ImportError::SkeletonError)?,
+                );
+            }
+        }
+        Ok(skeletons)
+    }
+}
+
+/// Maps every node index to the index of its parent node.
+pub(crate) fn node_parents(document: &Document) -> HashMap<usize, usize> {
+    let mut parents = HashMap::new();
+    for node in document.nodes() {
+        for child in node.children() {
+            parents.insert(child.index(), node.index());
+        }
+    }
+    parents
+}
+
+/// Returns the node index of the armature of a skin.
+///
+/// This is the skin's skeleton root if given, otherwise the first joint without a joint parent.
+pub(crate) fn skin_armature(
+    skin: &gltf::Skin<'_>,
+    parents: &HashMap<usize, usize>,
+) -> Option<usize> {
+    if let Some(skeleton) = skin.skeleton() {
+        return Some(skeleton.index());
+    }
+    let joint_indices: HashMap<usize, usize> = skin
+        .joints()
+        .enumerate()
+        .map(|(index, joint)| (joint.index(), index))
+        .collect();
+    skin.joints()
+        .find(|joint| joint_parent(joint.index(), parents, &joint_indices).is_none())
+        .map(|joint| joint.index())
+}
+
+/// Finds the closest ancestor of `node` that is also a joint, returning its joint index.
+fn joint_parent(
+    node: usize,
+    parents: &HashMap<usize, usize>,
+    joint_indices: &HashMap<usize, usize>,
+) -> Option<usize> {
+    let mut current = parents.get(&node);
+    let mut depth = 0;
+    while let Some(parent) = current {
+        if let Some(joint_index) = joint_indices.get(parent) {
+            return Some(*joint_index);
+        }
+        depth += 1;
+        if depth > parents.len() {
+            return None;
+        }
+        current = parents.get(parent);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::AiBone;
+
+    #[test]
+    fn test_gltf2_skeleton_import() {
+        let gltf_data = r#"{
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [
+                { "name": "Armature", "children": [1] },
+                { "name": "Hip", "children": [2] },
+                { "name": "Spine", "children": [3] },
+                { "name": "Head" }
+            ],
+            "skins": [
+                { "name": "Rig", "joints": [1, 3, 2] }
+            ],
+            "asset" : {
+                "version" : "2.0"
+            }
+        }"#;
+        let scene = serde_json::from_str(gltf_data).unwrap();
+        let document = Document::from_json_without_validation(scene);
+        let node_map = Gltf2Importer::import_node_map(&document);
+        let skeletons =
+            Gltf2Importer::import_skeletons(&document, &[], &node_map, &[], &[]).unwrap();
+        assert_eq!(1, skeletons.len());
+
+        let skeleton = &skeletons[0];
+        assert_eq!("Rig", skeleton.name());
+        let bones = skeleton.bones();
+        assert_eq!(3, bones.len());
+        assert_eq!(None, bones[0].parent());
+        assert_eq!(Some(2), bones[1].parent());
+        assert_eq!(Some(0), bones[2].parent());
+        assert_eq!(
+            vec![1, 3, 2],
+            bones.iter().map(|x| x.node_index()).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(1), bones[0].armature_index());
+    }
+
+    #[test]
+    fn test_gltf2_skeleton_import_mesh_binding() {
+        let gltf_data = r#"{
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [
+                { "name": "Armature", "children": [1, 3] },
+                { "name": "Hip", "children": [2] },
+                { "name": "Spine" },
+                { "name": "Body", "mesh": 0, "skin": 0 },
+                { "name": "Unused", "mesh": 0, "skin": 0 }
+            ],
+            "meshes": [
+                { "primitives": [{ "attributes": {} }, { "attributes": {} }] }
+            ],
+            "skins": [
+                { "name": "Rig", "joints": [1, 2] }
+            ],
+            "asset" : {
+                "version" : "2.0"
+            }
+        }"#;
+        let scene = serde_json::from_str(gltf_data).unwrap();
+        let document = Document::from_json_without_validation(scene);
+        let node_map = Gltf2Importer::import_node_map(&document);
+        let mesh = |hip_weight: AiReal| AiMesh {
+            bones: vec![
+                AiBone {
+                    node_index: node_map[&1],
+                    weights: vec![AiVertexWeight::new(0, hip_weight)],
+                    ..Default::default()
+                },
+                AiBone {
+                    node_index: node_map[&2],
+                    weights: vec![AiVertexWeight::new(0, 0.0)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let meshes = vec![mesh(1.0), mesh(0.5)];
+        let skeletons =
+            Gltf2Importer::import_skeletons(&document, &[], &node_map, &meshes, &[0, 2]).unwrap();
+
+        // One skeleton per primitive of the skinned mesh, node 4 is not part of the scene
+        assert_eq!(2, skeletons.len());
+        assert!(skeletons[0].same_bones(&skeletons[1]));
+        for (mesh_index, skeleton) in skeletons.iter().enumerate() {
+            assert_eq!("Rig", skeleton.name());
+            let bones = skeleton.bones();
+            assert_eq!(Some(mesh_index), bones[0].mesh_index());
+            assert_eq!(Some(mesh_index), bones[1].mesh_index());
+            assert_eq!(&meshes[mesh_index].bones[0].weights, bones[0].weights());
+            assert!(bones[1].weights().is_empty());
+        }
+    }
+}
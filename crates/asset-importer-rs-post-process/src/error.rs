@@ -6,6 +6,8 @@ use crate::steps::flip_uvs::FlipUVsError;
 use crate::steps::gen_normals::GenNormalsError;
 #[cfg(feature = "gen-smooth-normals")]
 use crate::steps::gen_smooth_normals::GenSmoothNormalsError;
+#[cfg(feature = "validate-data-structure")]
+use crate::steps::validate_data_structure::ValidateDataStructureError;
 
 #[non_exhaustive]
 #[derive(Debug)]
@@ -16,6 +18,8 @@ pub enum AiPostProcessError {
     GenSmoothNormalsError(GenSmoothNormalsError),
     #[cfg(feature = "flip-uvs")]
     FlipUVsError(FlipUVsError),
+    #[cfg(feature = "validate-data-structure")]
+    ValidateDataStructureError(ValidateDataStructureError),
 
     PostProcessError(String),
 }
@@ -34,6 +38,10 @@ impl Display for AiPostProcessError {
             AiPostProcessError::FlipUVsError(error) => {
                 write!(f, "FlipUVsError: {}", error)
             }
+            #[cfg(feature = "validate-data-structure")]
+            AiPostProcessError::ValidateDataStructureError(error) => {
+                write!(f, "ValidateDataStructureError: {}", error)
+            }
         }
     }
 }
@@ -52,6 +60,13 @@ impl From<FlipUVsError> for AiPostProcessError {
     }
 }
 
+#[cfg(feature = "validate-data-structure")]
+impl From<ValidateDataStructureError> for AiPostProcessError {
+    fn from(error: ValidateDataStructureError) -> Self {
+        AiPostProcessError::ValidateDataStructureError(error)
+    }
+}
+
 #[cfg(feature = "gen-normals")]
 impl From<GenNormalsError> for AiPostProcessError {
     fn from(error: GenNormalsError) -> Self {
@@ -1,4 +1,11 @@
-use asset_importer_rs_scene::{AiMesh, AiReal, AiVector3D};
+use std::collections::HashMap;
+
+use asset_importer_rs_scene::{
+    AI_MAX_NUMBER_OF_COLORS_SETS, AI_MAX_NUMBER_OF_TEXTURECOORDS, AiAABB, AiAnimMesh, AiBone,
+    AiFace, AiMaterial, AiMatrix4x4, AiMesh, AiPrimitiveType, AiPropertyTypeInfo, AiReal, AiScene,
+    AiSkeleton, AiTextureType, AiVector3D, AiVertexWeight,
+};
+use enumflags2::BitFlags;
 
 pub trait EpsilonCompute {
     fn epsilon(&self) -> AiReal;
@@ -31,3 +38,591 @@ impl EpsilonCompute for AiMesh {
         (max_vec - min_vec).len() * AiReal::from(1e-4)
     }
 }
+
+/// Appends copies of the `sources` vertices to a mesh.
+///
+/// Every per-vertex attribute is copied, including anim meshes and the weights of mesh bones and
+/// of skeleton bones bound to `mesh_index`. Copy `i` gets the index `vertex_count + i`.
+pub fn duplicate_vertices(
+    mesh: &mut AiMesh,
+    mesh_index: usize,
+    skeletons: &mut [AiSkeleton],
+    sources: &[usize],
+) {
+    if sources.is_empty() {
+        return;
+    }
+    let vertex_count = mesh.vertices.len();
+
+    fn extend<T: Clone>(values: &mut Vec<T>, vertex_count: usize, sources: &[usize]) {
+        if values.len() != vertex_count {
+            return;
+        }
+        values.reserve(sources.len());
+        for &source in sources {
+            values.push(values[source].clone());
+        }
+    }
+
+    extend(&mut mesh.normals, vertex_count, sources);
+    extend(&mut mesh.tangents, vertex_count, sources);
+    extend(&mut mesh.bi_tangents, vertex_count, sources);
+    for colors in mesh.colors.iter_mut().flatten() {
+        extend(colors, vertex_count, sources);
+    }
+    for coords in mesh.texture_coords.iter_mut().flatten() {
+        extend(coords, vertex_count, sources);
+    }
+    for anim_mesh in mesh.anim_meshes.iter_mut() {
+        extend(&mut anim_mesh.vertices, vertex_count, sources);
+        extend(&mut anim_mesh.normals, vertex_count, sources);
+        extend(&mut anim_mesh.tangents, vertex_count, sources);
+        extend(&mut anim_mesh.bi_tangents, vertex_count, sources);
+        for colors in anim_mesh.colors.iter_mut().flatten() {
+            extend(colors, vertex_count, sources);
+        }
+        for coords in anim_mesh.texture_coords.iter_mut().flatten() {
+            extend(coords, vertex_count, sources);
+        }
+    }
+    extend(&mut mesh.vertices, vertex_count, sources);
+
+    let mut copies: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (offset, &source) in sources.iter().enumerate() {
+        copies
+            .entry(source)
+            .or_default()
+            .push(vertex_count + offset);
+    }
+    let copy_weights = |weights: &mut Vec<AiVertexWeight>| {
+        let mut new_weights = Vec::new();
+        for weight in weights.iter() {
+            if let Some(targets) = copies.get(&weight.vertex_id) {
+                new_weights.extend(
+                    targets
+                        .iter()
+                        .map(|&target| AiVertexWeight::new(target, weight.weight)),
+                );
+            }
+        }
+        weights.extend(new_weights);
+    };
+    for bone in mesh.bones.iter_mut() {
+        copy_weights(&mut bone.weights);
+    }
+    for skeleton in skeletons.iter_mut() {
+        for bone in skeleton.bones_mut() {
+            if bone.mesh_index() == Some(mesh_index) {
+                copy_weights(bone.weights_mut());
+            }
+        }
+    }
+}
+
+/// Returns the primitive types of the faces of a mesh.
+///
+/// `AiPrimitiveType::NgonEncodingFlag` is kept if the mesh already had it.
+pub fn face_primitive_types(mesh: &AiMesh) -> BitFlags<AiPrimitiveType> {
+    let mut primitive_types = BitFlags::empty();
+    for face in &mesh.faces {
+        primitive_types |= AiPrimitiveType::primitive_type_for_n_indices(face.len());
+    }
+    primitive_types.remove(AiPrimitiveType::None);
+    if mesh
+        .primitive_types
+        .contains(AiPrimitiveType::NgonEncodingFlag)
+    {
+        primitive_types |= AiPrimitiveType::NgonEncodingFlag;
+    }
+    primitive_types
+}
+
+/// Removes the meshes flagged in `removed` from a scene.
+///
+/// Node mesh indices and skeleton bones are remapped, skeleton bones bound to a removed mesh
+/// lose their mesh and weights.
+pub fn remove_meshes(scene: &mut AiScene, removed: &[bool]) {
+    if !removed.iter().any(|&removed| removed) {
+        return;
+    }
+    let mut new_indices: Vec<Option<usize>> = Vec::with_capacity(scene.meshes.len());
+    let mut next = 0;
+    for index in 0..scene.meshes.len() {
+        if removed.get(index).copied().unwrap_or(false) {
+            new_indices.push(None);
+        } else {
+            new_indices.push(Some(next));
+            next += 1;
+        }
+    }
+
+    let mut index = 0;
+    scene.meshes.retain(|_| {
+        let keep = new_indices[index].is_some();
+        index += 1;
+        keep
+    });
+    for node in scene.nodes.arena.iter_mut() {
+        node.mesh_indexes = node
+            .mesh_indexes
+            .iter()
+            .filter_map(|&mesh| new_indices.get(mesh).copied().flatten())
+            .collect();
+    }
+    for skeleton in scene.skeletons.iter_mut() {
+        for bone in skeleton.bones_mut() {
+            let Some(mesh) = bone.mesh_index() else {
+                continue;
+            };
+            match new_indices.get(mesh).copied().flatten() {
+                Some(new_index) => bone.set_mesh_index(Some(new_index)),
+                None => {
+                    bone.set_mesh_index(None);
+                    bone.weights_mut().clear();
+                }
+            }
+        }
+    }
+}
+
+/// Parses a whitespace separated list of names, names containing spaces are enclosed in single
+/// quotes, e.g. `"Skin 'Car Paint' Glass"`.
+pub fn parse_name_list(list: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut chars = list.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' {
+            chars.next();
+            let name: String = chars.by_ref().take_while(|&c| c != '\'').collect();
+            names.push(name);
+        } else {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Transforms the vertex data of a mesh by `transform`.
+///
+/// Positions are transformed as points, normals by the inverse transpose and tangents as
+/// vectors. Anim meshes are transformed the same way, and the winding order of faces is
+/// flipped if the transform mirrors the mesh. A computed bounding box is recomputed, bone offset
+/// matrices are not changed.
+pub fn transform_mesh(mesh: &mut AiMesh, transform: &AiMatrix4x4) {
+    let normal_transform = transform
+        .inverse()
+        .map(|inverse| inverse.transpose())
+        .unwrap_or_else(|| transform.clone());
+    let transform_all = |vertices: &mut Vec<AiVector3D>,
+                         normals: &mut Vec<AiVector3D>,
+                         tangents: &mut Vec<AiVector3D>,
+                         bi_tangents: &mut Vec<AiVector3D>| {
+        for vertex in vertices.iter_mut() {
+            *vertex = transform.transform_point(vertex);
+        }
+        for normal in normals.iter_mut() {
+            *normal = normal_transform.transform_vector(normal).norm();
+        }
+        for tangent in tangents.iter_mut().chain(bi_tangents.iter_mut()) {
+            *tangent = transform.transform_vector(tangent).norm();
+        }
+    };
+    transform_all(
+        &mut mesh.vertices,
+        &mut mesh.normals,
+        &mut mesh.tangents,
+        &mut mesh.bi_tangents,
+    );
+    for anim_mesh in mesh.anim_meshes.iter_mut() {
+        transform_all(
+            &mut anim_mesh.vertices,
+            &mut anim_mesh.normals,
+            &mut anim_mesh.tangents,
+            &mut anim_mesh.bi_tangents,
+        );
+    }
+    if transform.determinant() < 0.0 {
+        for face in mesh.faces.iter_mut() {
+            face.reverse();
+        }
+    }
+    if mesh.aabb != AiAABB::default() {
+        mesh.aabb = AiAABB::from_points(&mesh.vertices);
+    }
+}
+
+/// Reorders the vertices of a mesh, vertex `i` becomes the old vertex `order[i]`.
+///
+/// `order` must be a permutation of the vertex indices. Faces, every per-vertex attribute,
+/// anim meshes and the weights of mesh bones and of skeleton bones bound to `mesh_index` are
+/// remapped.
+pub fn reorder_vertices(
+    mesh: &mut AiMesh,
+    mesh_index: usize,
+    skeletons: &mut [AiSkeleton],
+    order: &[usize],
+) {
+    let vertex_count = mesh.vertices.len();
+    if order.len() != vertex_count {
+        return;
+    }
+
+    fn permute<T: Clone>(values: &mut Vec<T>, order: &[usize]) {
+        if values.len() != order.len() {
+            return;
+        }
+        *values = order.iter().map(|&old| values[old].clone()).collect();
+    }
+
+    permute(&mut mesh.vertices, order);
+    permute(&mut mesh.normals, order);
+    permute(&mut mesh.tangents, order);
+    permute(&mut mesh.bi_tangents, order);
+    for colors in mesh.colors.iter_mut().flatten() {
+        permute(colors, order);
+    }
+    for coords in mesh.texture_coords.iter_mut().flatten() {
+        permute(coords, order);
+    }
+    for anim_mesh in mesh.anim_meshes.iter_mut() {
+        permute(&mut anim_mesh.vertices, order);
+        permute(&mut anim_mesh.normals, order);
+        permute(&mut anim_mesh.tangents, order);
+        permute(&mut anim_mesh.bi_tangents, order);
+        for colors in anim_mesh.colors.iter_mut().flatten() {
+            permute(colors, order);
+        }
+        for coords in anim_mesh.texture_coords.iter_mut().flatten() {
+            permute(coords, order);
+        }
+    }
+
+    let mut new_indices = vec![0; vertex_count];
+    for (new, &old) in order.iter().enumerate() {
+        new_indices[old] = new;
+    }
+    for face in mesh.faces.iter_mut() {
+        for index in face.iter_mut() {
+            if let Some(&new) = new_indices.get(*index) {
+                *index = new;
+            }
+        }
+    }
+    let remap_weights = |weights: &mut Vec<AiVertexWeight>| {
+        for weight in weights.iter_mut() {
+            if let Some(&new) = new_indices.get(weight.vertex_id) {
+                weight.vertex_id = new;
+            }
+        }
+    };
+    for bone in mesh.bones.iter_mut() {
+        remap_weights(&mut bone.weights);
+    }
+    for skeleton in skeletons.iter_mut() {
+        for bone in skeleton.bones_mut() {
+            if bone.mesh_index() == Some(mesh_index) {
+                remap_weights(bone.weights_mut());
+            }
+        }
+    }
+}
+
+/// Copies the faces `face_indices` of a mesh into a new mesh.
+///
+/// The new mesh only holds the vertices used by these faces, in order of first use, with every
+/// per-vertex attribute and anim mesh. Mesh bones keep their non-zero weights on these
+/// vertices, bones left without weights are dropped. Returns the new mesh and the old index of each of its
+/// vertices.
+pub fn extract_sub_mesh(mesh: &AiMesh, face_indices: &[usize]) -> (AiMesh, Vec<usize>) {
+    let vertex_count = mesh.vertices.len();
+    let mut new_indices: HashMap<usize, usize> = HashMap::new();
+    let mut old_indices: Vec<usize> = Vec::new();
+    let mut faces: Vec<AiFace> = Vec::with_capacity(face_indices.len());
+    for face in face_indices
+        .iter()
+        .filter_map(|&index| mesh.faces.get(index))
+    {
+        faces.push(
+            face.iter()
+                .map(|&index| {
+                    *new_indices.entry(index).or_insert_with(|| {
+                        old_indices.push(index);
+                        old_indices.len() - 1
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    fn pick<T: Clone>(values: &[T], vertex_count: usize, old_indices: &[usize]) -> Vec<T> {
+        if values.len() != vertex_count {
+            return Vec::new();
+        }
+        old_indices.iter().map(|&old| values[old].clone()).collect()
+    }
+    fn pick_channels<T: Clone, const N: usize>(
+        channels: &[Option<Vec<T>>; N],
+        vertex_count: usize,
+        old_indices: &[usize],
+    ) -> [Option<Vec<T>>; N] {
+        channels.each_ref().map(|channel| {
+            channel
+                .as_ref()
+                .map(|values| pick(values, vertex_count, old_indices))
+        })
+    }
+
+    let bones = mesh
+        .bones
+        .iter()
+        .filter_map(|bone| {
+            let weights: Vec<AiVertexWeight> = bone
+                .weights
+                .iter()
+                .filter(|weight| weight.weight > 0.0)
+                .filter_map(|weight| {
+                    new_indices
+                        .get(&weight.vertex_id)
+                        .map(|&new| AiVertexWeight::new(new, weight.weight))
+                })
+                .collect();
+            (!weights.is_empty()).then(|| AiBone {
+                weights,
+                ..bone.clone()
+            })
+        })
+        .collect();
+    let anim_meshes = mesh
+        .anim_meshes
+        .iter()
+        .map(|anim_mesh| AiAnimMesh {
+            name: anim_mesh.name.clone(),
+            vertices: pick(&anim_mesh.vertices, vertex_count, &old_indices),
+            normals: pick(&anim_mesh.normals, vertex_count, &old_indices),
+            tangents: pick(&anim_mesh.tangents, vertex_count, &old_indices),
+            bi_tangents: pick(&anim_mesh.bi_tangents, vertex_count, &old_indices),
+            colors: pick_channels(&anim_mesh.colors, vertex_count, &old_indices),
+            texture_coords: pick_channels(&anim_mesh.texture_coords, vertex_count, &old_indices),
+            weight: anim_mesh.weight,
+        })
+        .collect();
+
+    let mut sub_mesh = AiMesh {
+        name: mesh.name.clone(),
+        primitive_types: mesh.primitive_types,
+        vertices: pick(&mesh.vertices, vertex_count, &old_indices),
+        normals: pick(&mesh.normals, vertex_count, &old_indices),
+        tangents: pick(&mesh.tangents, vertex_count, &old_indices),
+        bi_tangents: pick(&mesh.bi_tangents, vertex_count, &old_indices),
+        colors: pick_channels(&mesh.colors, vertex_count, &old_indices),
+        texture_coords: pick_channels(&mesh.texture_coords, vertex_count, &old_indices),
+        faces,
+        bones,
+        material_index: mesh.material_index,
+        anim_meshes,
+        method: mesh.method,
+        aabb: AiAABB::default(),
+        texture_coordinate_names: mesh.texture_coordinate_names.clone(),
+    };
+    sub_mesh.primitive_types = face_primitive_types(&sub_mesh);
+    if mesh.aabb != AiAABB::default() {
+        sub_mesh.aabb = AiAABB::from_points(&sub_mesh.vertices);
+    }
+    (sub_mesh, old_indices)
+}
+
+/// Returns for every mesh of a scene whether skeleton bones are bound to it.
+///
+/// A skeleton bone holds a single mesh binding, so these meshes cannot be split into parts.
+pub fn skeleton_bound_meshes(scene: &AiScene) -> Vec<bool> {
+    let mut bound = vec![false; scene.meshes.len()];
+    for bone in scene.skeletons.iter().flat_map(AiSkeleton::bones) {
+        if let Some(bound) = bone.mesh_index().and_then(|index| bound.get_mut(index)) {
+            *bound = true;
+        }
+    }
+    bound
+}
+
+/// Replaces every mesh of a scene by its parts, as returned by [`extract_sub_mesh`].
+///
+/// Nodes referencing a mesh reference all of its parts instead. Meshes bound to skeleton bones
+/// must be kept in a single part, see [`skeleton_bound_meshes`], their bones are rebound to it.
+/// Otherwise the bones are bound to the first part holding one of their weighted vertices, and
+/// the weights on other parts are dropped with a warning.
+pub fn replace_meshes(scene: &mut AiScene, parts: Vec<Vec<(AiMesh, Vec<usize>)>>) {
+    let mut new_indices: Vec<Vec<usize>> = Vec::with_capacity(parts.len());
+    let mut vertex_maps: Vec<Vec<HashMap<usize, usize>>> = Vec::with_capacity(parts.len());
+    scene.meshes.clear();
+    for mesh_parts in parts {
+        let mut indices = Vec::with_capacity(mesh_parts.len());
+        let mut maps = Vec::with_capacity(mesh_parts.len());
+        for (mesh, old_indices) in mesh_parts {
+            indices.push(scene.meshes.len());
+            maps.push(
+                old_indices
+                    .into_iter()
+                    .enumerate()
+                    .map(|(new, old)| (old, new))
+                    .collect(),
+            );
+            scene.meshes.push(mesh);
+        }
+        new_indices.push(indices);
+        vertex_maps.push(maps);
+    }
+
+    for node in scene.nodes.arena.iter_mut() {
+        node.mesh_indexes = node
+            .mesh_indexes
+            .iter()
+            .filter_map(|&mesh_index| new_indices.get(mesh_index))
+            .flatten()
+            .copied()
+            .collect();
+    }
+    let mut dropped = 0;
+    for skeleton in scene.skeletons.iter_mut() {
+        for bone in skeleton.bones_mut() {
+            let Some(mesh_index) = bone.mesh_index() else {
+                continue;
+            };
+            let Some(maps) = vertex_maps.get(mesh_index) else {
+                continue;
+            };
+            let part = maps.iter().position(|map| {
+                bone.weights()
+                    .iter()
+                    .any(|weight| map.contains_key(&weight.vertex_id))
+            });
+            let Some(part) = part.or((!maps.is_empty()).then_some(0)) else {
+                bone.set_mesh_index(None);
+                bone.weights_mut().clear();
+                continue;
+            };
+            let map = &maps[part];
+            let count = bone.weights().len();
+            let weights: Vec<AiVertexWeight> = bone
+                .weights()
+                .iter()
+                .filter_map(|weight| {
+                    map.get(&weight.vertex_id)
+                        .map(|&new| AiVertexWeight::new(new, weight.weight))
+                })
+                .collect();
+            dropped += count - weights.len();
+            *bone.weights_mut() = weights;
+            bone.set_mesh_index(Some(new_indices[mesh_index][part]));
+        }
+    }
+    if dropped > 0 {
+        log::warn!(
+            "{} skeleton bone weights were dropped while splitting their meshes",
+            dropped
+        );
+    }
+}
+
+/// Everything two meshes must share to be merged.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MeshFormat {
+    material_index: u32,
+    primitive_types: BitFlags<AiPrimitiveType>,
+    normals: bool,
+    tangents: bool,
+    bi_tangents: bool,
+    texture_coords: [bool; AI_MAX_NUMBER_OF_TEXTURECOORDS],
+    colors: [bool; AI_MAX_NUMBER_OF_COLORS_SETS],
+}
+
+impl MeshFormat {
+    pub fn new(mesh: &AiMesh) -> Self {
+        Self {
+            material_index: mesh.material_index,
+            primitive_types: mesh.primitive_types,
+            normals: !mesh.normals.is_empty(),
+            tangents: !mesh.tangents.is_empty(),
+            bi_tangents: !mesh.bi_tangents.is_empty(),
+            texture_coords: mesh.texture_coords.each_ref().map(Option::is_some),
+            colors: mesh.colors.each_ref().map(Option::is_some),
+        }
+    }
+}
+
+/// Appends `source` to `target`, offsetting its faces and bone weights.
+pub fn append_mesh(target: &mut AiMesh, source: AiMesh) {
+    let offset = target.vertices.len();
+    target.vertices.extend(source.vertices);
+    target.normals.extend(source.normals);
+    target.tangents.extend(source.tangents);
+    target.bi_tangents.extend(source.bi_tangents);
+    for (target, source) in target.texture_coords.iter_mut().zip(source.texture_coords) {
+        if let (Some(target), Some(source)) = (target, source) {
+            target.extend(source);
+        }
+    }
+    for (target, source) in target.colors.iter_mut().zip(source.colors) {
+        if let (Some(target), Some(source)) = (target, source) {
+            target.extend(source);
+        }
+    }
+    target.faces.extend(
+        source
+            .faces
+            .into_iter()
+            .map(|face| face.into_iter().map(|index| index + offset).collect()),
+    );
+    for mut bone in source.bones {
+        for weight in bone.weights.iter_mut() {
+            weight.vertex_id += offset;
+        }
+        // Bones are shared if they deform the meshes the same way
+        match target.bones.iter_mut().find(|other| {
+            other.name == bone.name
+                && other.node_index == bone.node_index
+                && other.offset_matrix == bone.offset_matrix
+        }) {
+            Some(other) => other.weights.extend(bone.weights),
+            None => target.bones.push(bone),
+        }
+    }
+    target.aabb = target.aabb.union(&source.aabb);
+}
+
+/// Returns the first texture coordinate channel that none of `mesh_indices` uses.
+pub fn free_uv_channel(meshes: &[AiMesh], mesh_indices: &[usize]) -> Option<usize> {
+    (0..AI_MAX_NUMBER_OF_TEXTURECOORDS).find(|&channel| {
+        mesh_indices
+            .iter()
+            .all(|&mesh_index| meshes[mesh_index].texture_coords[channel].is_none())
+    })
+}
+
+/// Sets the data of a binary texture property, adding the property if it is missing.
+pub fn set_texture_property(
+    material: &mut AiMaterial,
+    key: &str,
+    semantic: AiTextureType,
+    index: u32,
+    data: Vec<u8>,
+) {
+    match material.get_property_mut(key, Some(semantic), index) {
+        Some(property) => {
+            property.property_type = AiPropertyTypeInfo::Binary;
+            property.data = data;
+        }
+        None => {
+            material.add_property(key, Some(semantic), AiPropertyTypeInfo::Binary, index, data);
+        }
+    }
+}
@@ -156,35 +156,37 @@ impl AiPostProcesser {
     pub fn post_process() -> PostProcess {
         PostProcess::new(vec![
             #[cfg(feature = "calc-tangent-spaces")]
-            Box::new(AiPostProcesserWrapper::new(CalcTangentSpaces)),
+            Box::new(AiPostProcesserWrapper::new(CalcTangentSpaces::default())),
             #[cfg(feature = "find-degenerates")]
-            Box::new(AiPostProcesserWrapper::new(FindDegenerates)),
+            Box::new(AiPostProcesserWrapper::new(FindDegenerates::default())),
             #[cfg(feature = "find-invalid-data")]
-            Box::new(AiPostProcesserWrapper::new(FindInvalidData)),
+            Box::new(AiPostProcesserWrapper::new(FindInvalidData::default())),
             #[cfg(feature = "gen-normals")]
             Box::new(AiPostProcesserWrapper::new(GenNormals::default())),
             #[cfg(feature = "gen-smooth-normals")]
             Box::new(AiPostProcesserWrapper::new(GenSmoothNormals::default())),
             #[cfg(feature = "join-identical-vertices")]
-            Box::new(AiPostProcesserWrapper::new(JoinIdenticalVertices)),
+            Box::new(AiPostProcesserWrapper::new(JoinIdenticalVertices::default())),
             #[cfg(feature = "optimize-graph")]
-            Box::new(AiPostProcesserWrapper::new(OptimizeGraph)),
+            Box::new(AiPostProcesserWrapper::new(OptimizeGraph::default())),
             #[cfg(feature = "optimize-meshes")]
-            Box::new(AiPostProcesserWrapper::new(OptimizeMeshes)),
+            Box::new(AiPostProcesserWrapper::new(OptimizeMeshes::default())),
             #[cfg(feature = "remove-redundant-materials")]
-            Box::new(AiPostProcesserWrapper::new(RemoveRedundantMaterials)),
+            Box::new(AiPostProcesserWrapper::new(
+                RemoveRedundantMaterials::default(),
+            )),
             #[cfg(feature = "triangulate")]
-            Box::new(AiPostProcesserWrapper::new(Triangulate)),
+            Box::new(AiPostProcesserWrapper::new(Triangulate::default())),
             #[cfg(feature = "validate-data-structure")]
             Box::new(AiPostProcesserWrapper::new(ValidateDataStructure)),
             #[cfg(feature = "debone")]
-            Box::new(AiPostProcesserWrapper::new(Debone)),
+            Box::new(AiPostProcesserWrapper::new(Debone::default())),
             #[cfg(feature = "drop-normals")]
             Box::new(AiPostProcesserWrapper::new(DropNormals)),
             #[cfg(feature = "embed-textures")]
-            Box::new(AiPostProcesserWrapper::new(EmbedTextures)),
+            Box::new(AiPostProcesserWrapper::new(EmbedTextures::default())),
             #[cfg(feature = "find-instances")]
-            Box::new(AiPostProcesserWrapper::new(FindInstances)),
+            Box::new(AiPostProcesserWrapper::new(FindInstances::default())),
             #[cfg(feature = "fix-infacing-normals")]
             Box::new(AiPostProcesserWrapper::new(FixInfacingNormals)),
             #[cfg(feature = "flip-uvs")]
@@ -198,25 +200,25 @@ impl AiPostProcesser {
             #[cfg(feature = "gen-uv-coords")]
             Box::new(AiPostProcesserWrapper::new(GenUVCoords)),
             #[cfg(feature = "global-scale")]
-            Box::new(AiPostProcesserWrapper::new(GlobalScale)),
+            Box::new(AiPostProcesserWrapper::new(GlobalScale::default())),
             #[cfg(feature = "improve-cache-locality")]
-            Box::new(AiPostProcesserWrapper::new(ImproveCacheLocality)),
+            Box::new(AiPostProcesserWrapper::new(ImproveCacheLocality::default())),
             #[cfg(feature = "limit-bone-weights")]
-            Box::new(AiPostProcesserWrapper::new(LimitBoneWeights)),
+            Box::new(AiPostProcesserWrapper::new(LimitBoneWeights::default())),
             #[cfg(feature = "make-left-handed")]
             Box::new(AiPostProcesserWrapper::new(MakeLeftHanded)),
             #[cfg(feature = "populate-armature-data")]
             Box::new(AiPostProcesserWrapper::new(PopulateArmatureData)),
             #[cfg(feature = "pre-transform-vertices")]
-            Box::new(AiPostProcesserWrapper::new(PreTransformVertices)),
+            Box::new(AiPostProcesserWrapper::new(PreTransformVertices::default())),
             #[cfg(feature = "remove-component")]
             Box::new(AiPostProcesserWrapper::new(RemoveComponent)),
             #[cfg(feature = "sort-by-p-type")]
-            Box::new(AiPostProcesserWrapper::new(SortByPType)),
+            Box::new(AiPostProcesserWrapper::new(SortByPType::default())),
             #[cfg(feature = "split-by-bone-count")]
-            Box::new(AiPostProcesserWrapper::new(SplitByBoneCount)),
+            Box::new(AiPostProcesserWrapper::new(SplitByBoneCount::default())),
             #[cfg(feature = "split-large-meshes")]
-            Box::new(AiPostProcesserWrapper::new(SplitLargeMeshes)),
+            Box::new(AiPostProcesserWrapper::new(SplitLargeMeshes::default())),
             #[cfg(feature = "transform-uv-coords")]
             Box::new(AiPostProcesserWrapper::new(TransformUVCoords)),
         ])
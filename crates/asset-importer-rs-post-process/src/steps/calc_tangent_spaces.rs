@@ -1,10 +1,400 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::collections::{HashMap, HashSet};
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_CT_MAX_SMOOTHING_ANGLE_DEFAULT, AI_CONFIG_PP_CT_TEXTURE_CHANNEL_INDEX_DEFAULT,
+    AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{
+    AiMesh, AiReal, AiScene, AiSkeleton, AiVector3D, degrees_to_radians,
+};
 use enumflags2::BitFlags;
 
+use crate::helper::duplicate_vertices;
+
 /// Calculate tangent spaces for meshes
-#[derive(Default)]
-pub struct CalcTangentSpaces;
+///
+/// Follows the MikkTSpace algorithm: per-triangle tangents are projected onto the tangent plane
+/// of every corner and averaged with angle weights over the corners that share a vertex, are
+/// connected through shared edges and agree on the orientation of their texture mapping.
+/// Mirrored texture coordinates get a negative handedness, see
+/// [`AiMesh::tangents_with_handedness`], and vertices whose corners end up with different
+/// tangent frames are split. Triangles without area take the tangent frame of their neighbours.
+///
+/// Meshes without normals or without the selected texture coordinate channel are skipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalcTangentSpaces {
+    /// Texture coordinate channel the tangents follow.
+    pub uv_channel: usize,
+    /// Maximum angle in radians between corner tangents that are averaged.
+    pub max_smoothing_angle: AiReal,
+}
+
+impl Default for CalcTangentSpaces {
+    fn default() -> Self {
+        Self {
+            uv_channel: AI_CONFIG_PP_CT_TEXTURE_CHANNEL_INDEX_DEFAULT,
+            max_smoothing_angle: degrees_to_radians(AI_CONFIG_PP_CT_MAX_SMOOTHING_ANGLE_DEFAULT),
+        }
+    }
+}
+
+/// Tangent frame of a triangle, computed from its positions and texture coordinates.
+struct TriangleFrame {
+    /// Normalized tangent, negated if the texture mapping is mirrored
+    tangent: AiVector3D,
+    /// Normalized bitangent, negated if the texture mapping is mirrored
+    bi_tangent: AiVector3D,
+    orientation_preserving: bool,
+    /// The texture mapping has no area, the triangle may join any neighbouring group
+    group_with_any: bool,
+    /// The triangle has no area and does not contribute to any group
+    degenerate: bool,
+}
+
+/// Union-find over the corners of all triangles.
+struct Groups {
+    parents: Vec<usize>,
+}
+
+impl Groups {
+    fn new(count: usize) -> Self {
+        Self {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, mut corner: usize) -> usize {
+        while self.parents[corner] != corner {
+            self.parents[corner] = self.parents[self.parents[corner]];
+            corner = self.parents[corner];
+        }
+        corner
+    }
+
+    fn union(&mut self, lhs: usize, rhs: usize) {
+        let lhs = self.find(lhs);
+        let rhs = self.find(rhs);
+        if lhs != rhs {
+            self.parents[rhs.max(lhs)] = rhs.min(lhs);
+        }
+    }
+}
+
+/// Projects `vector` onto the plane with the normal `normal` and normalizes it.
+fn project(vector: &AiVector3D, normal: &AiVector3D) -> AiVector3D {
+    let projected = *vector - *normal * (*normal * *vector);
+    if projected.square_length() > 0.0 {
+        projected.norm()
+    } else {
+        projected
+    }
+}
+
+/// Any unit vector perpendicular to `normal`.
+fn perpendicular(normal: &AiVector3D) -> AiVector3D {
+    let axis = if normal.x.abs() < 0.9 {
+        AiVector3D::new(1.0, 0.0, 0.0)
+    } else {
+        AiVector3D::new(0.0, 1.0, 0.0)
+    };
+    project(&axis, normal)
+}
+
+impl CalcTangentSpaces {
+    fn triangle_frame(
+        &self,
+        mesh: &AiMesh,
+        uvs: &[AiVector3D],
+        triangle: &[usize; 3],
+    ) -> TriangleFrame {
+        let [i0, i1, i2] = *triangle;
+        let d1 = mesh.vertices[i1] - mesh.vertices[i0];
+        let d2 = mesh.vertices[i2] - mesh.vertices[i0];
+        let degenerate = i0 == i1 || i1 == i2 || i0 == i2 || d1.cross(&d2).square_length() == 0.0;
+
+        let t21 = uvs[i1] - uvs[i0];
+        let t31 = uvs[i2] - uvs[i0];
+        let signed_area = t21.x * t31.y - t21.y * t31.x;
+        let orientation_preserving = signed_area > 0.0;
+        let sign = if orientation_preserving { 1.0 } else { -1.0 };
+
+        let tangent = d1 * t31.y - d2 * t21.y;
+        let bi_tangent = d2 * t21.x - d1 * t31.x;
+        let group_with_any = signed_area == 0.0
+            || tangent.square_length() == 0.0
+            || bi_tangent.square_length() == 0.0;
+        TriangleFrame {
+            tangent: if group_with_any {
+                AiVector3D::zero()
+            } else {
+                tangent.norm() * sign
+            },
+            bi_tangent: if group_with_any {
+                AiVector3D::zero()
+            } else {
+                bi_tangent.norm() * sign
+            },
+            orientation_preserving,
+            group_with_any,
+            degenerate,
+        }
+    }
+
+    /// Computes the tangent frames of a mesh, returning `false` if the mesh was skipped.
+    fn process_mesh(
+        &self,
+        mesh: &mut AiMesh,
+        mesh_index: usize,
+        skeletons: &mut [AiSkeleton],
+    ) -> bool {
+        let vertex_count = mesh.vertices.len();
+        if !mesh.faces.iter().any(|face| face.len() >= 3) {
+            return false;
+        }
+        if mesh.normals.len() != vertex_count {
+            log::warn!(
+                "Mesh {} has no normals, tangent space calculation skipped",
+                mesh.name
+            );
+            return false;
+        }
+        let uvs = match mesh.texture_coords.get(self.uv_channel) {
+            Some(Some(uvs)) if uvs.len() == vertex_count => uvs.clone(),
+            _ => {
+                log::warn!(
+                    "Mesh {} has no texture coordinates in channel {}, tangent space calculation skipped",
+                    mesh.name,
+                    self.uv_channel
+                );
+                return false;
+            }
+        };
+
+        // Triangles of every face, polygons are split into fans
+        let mut triangles: Vec<[usize; 3]> = Vec::new();
+        // For every triangle corner, its face and position in the face
+        let mut slots: Vec<(usize, usize)> = Vec::new();
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            if face.len() < 3 || face.iter().any(|&index| index >= vertex_count) {
+                continue;
+            }
+            for i in 1..face.len() - 1 {
+                triangles.push([face[0], face[i], face[i + 1]]);
+                slots.extend([(face_index, 0), (face_index, i), (face_index, i + 1)]);
+            }
+        }
+
+        // Vertices sharing position, normal and texture coordinate are the same vertex to MikkTSpace
+        let mut welded: Vec<usize> = Vec::with_capacity(vertex_count);
+        let mut welded_map: HashMap<[u64; 8], usize> = HashMap::new();
+        for (index, t) in uvs.iter().enumerate() {
+            let (p, n) = (mesh.vertices[index], mesh.normals[index]);
+            let key =
+                [p.x, p.y, p.z, n.x, n.y, n.z, t.x, t.y].map(|value| u64::from(value.to_bits()));
+            welded.push(*welded_map.entry(key).or_insert(index));
+        }
+
+        let frames: Vec<TriangleFrame> = triangles
+            .iter()
+            .map(|triangle| self.triangle_frame(mesh, &uvs, triangle))
+            .collect();
+
+        // Group the corners of triangles sharing an edge and orientation
+        let mut groups = Groups::new(triangles.len() * 3);
+        let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            if frames[triangle_index].degenerate {
+                continue;
+            }
+            for corner in 0..3 {
+                let a = welded[triangle[corner]];
+                let b = welded[triangle[(corner + 1) % 3]];
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push((triangle_index, corner));
+            }
+        }
+        for sharing in edges.values() {
+            for (i, &(lhs, lhs_corner)) in sharing.iter().enumerate() {
+                for &(rhs, _) in &sharing[i + 1..] {
+                    let (lhs_frame, rhs_frame) = (&frames[lhs], &frames[rhs]);
+                    if lhs_frame.orientation_preserving != rhs_frame.orientation_preserving
+                        && !lhs_frame.group_with_any
+                        && !rhs_frame.group_with_any
+                    {
+                        continue;
+                    }
+                    let lhs_corners = [lhs_corner, (lhs_corner + 1) % 3];
+                    for lhs_corner in lhs_corners {
+                        let vertex = welded[triangles[lhs][lhs_corner]];
+                        if let Some(rhs_corner) =
+                            (0..3).find(|&corner| welded[triangles[rhs][corner]] == vertex)
+                        {
+                            groups.union(lhs * 3 + lhs_corner, rhs * 3 + rhs_corner);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Project the triangle frames onto every corner and weight them by the corner angle
+        let corner_count = triangles.len() * 3;
+        let mut corner_tangents = vec![AiVector3D::zero(); corner_count];
+        let mut corner_bi_tangents = vec![AiVector3D::zero(); corner_count];
+        let mut corner_weights: Vec<AiReal> = vec![0.0; corner_count];
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let frame = &frames[triangle_index];
+            if frame.degenerate || frame.group_with_any {
+                continue;
+            }
+            for corner in 0..3 {
+                let vertex = triangle[corner];
+                let normal = mesh.normals[vertex];
+                let position = mesh.vertices[vertex];
+                let to_next = project(
+                    &(mesh.vertices[triangle[(corner + 1) % 3]] - position),
+                    &normal,
+                );
+                let to_prev = project(
+                    &(mesh.vertices[triangle[(corner + 2) % 3]] - position),
+                    &normal,
+                );
+                let angle = (to_next * to_prev).clamp(-1.0, 1.0).acos();
+                let slot = triangle_index * 3 + corner;
+                corner_tangents[slot] = project(&frame.tangent, &normal);
+                corner_bi_tangents[slot] = project(&frame.bi_tangent, &normal);
+                corner_weights[slot] = angle;
+            }
+        }
+
+        // Split groups into subgroups whose tangents lie within the smoothing angle
+        let threshold = self.max_smoothing_angle.cos();
+        let mut subgroups: HashMap<usize, Vec<(usize, Vec<usize>)>> = HashMap::new();
+        for slot in 0..corner_count {
+            let root = groups.find(slot);
+            let entries = subgroups.entry(root).or_default();
+            let tangent = corner_tangents[slot];
+            let bi_tangent = corner_bi_tangents[slot];
+            let existing = entries.iter_mut().find(|(representative, _)| {
+                corner_weights[slot] == 0.0
+                    || corner_weights[*representative] == 0.0
+                    || (corner_tangents[*representative] * tangent >= threshold
+                        && corner_bi_tangents[*representative] * bi_tangent >= threshold)
+            });
+            match existing {
+                Some((representative, members)) => {
+                    if corner_weights[*representative] == 0.0 && corner_weights[slot] != 0.0 {
+                        *representative = slot;
+                    }
+                    members.push(slot);
+                }
+                None => entries.push((slot, vec![slot])),
+            }
+        }
+
+        // Sum every subgroup into the tangent frame of its corners
+        let mut corner_results: Vec<Option<(AiVector3D, bool)>> = vec![None; corner_count];
+        for entries in subgroups.values() {
+            for (_, members) in entries {
+                let mut tangent = AiVector3D::zero();
+                let mut orientation_preserving = None;
+                for &slot in members {
+                    tangent += corner_tangents[slot] * corner_weights[slot];
+                    let frame = &frames[slot / 3];
+                    if orientation_preserving.is_none()
+                        && !frame.group_with_any
+                        && !frame.degenerate
+                    {
+                        orientation_preserving = Some(frame.orientation_preserving);
+                    }
+                }
+                if tangent.square_length() == 0.0 {
+                    continue;
+                }
+                let tangent = tangent.norm();
+                for &slot in members {
+                    corner_results[slot] = Some((tangent, orientation_preserving.unwrap_or(true)));
+                }
+            }
+        }
+
+        // Corners of degenerate triangles take the frame of another corner of the same vertex
+        let mut vertex_results: HashMap<usize, (AiVector3D, bool)> = HashMap::new();
+        for (slot, result) in corner_results.iter().enumerate() {
+            if let Some(result) = result {
+                vertex_results
+                    .entry(welded[triangles[slot / 3][slot % 3]])
+                    .or_insert(*result);
+            }
+        }
+        for (slot, result) in corner_results.iter_mut().enumerate() {
+            if result.is_none() {
+                let vertex = triangles[slot / 3][slot % 3];
+                *result = Some(
+                    vertex_results
+                        .get(&welded[vertex])
+                        .copied()
+                        .unwrap_or_else(|| (perpendicular(&mesh.normals[vertex]), true)),
+                );
+            }
+        }
+
+        // Write the frames, splitting vertices whose corners disagree
+        let mut tangents: Vec<Option<(AiVector3D, bool)>> = vec![None; vertex_count];
+        let mut copies: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut sources: Vec<usize> = Vec::new();
+        let mut face_updates: Vec<(usize, usize, usize)> = Vec::new();
+        let mut assigned: HashSet<(usize, usize)> = HashSet::new();
+        for (slot, result) in corner_results.iter().enumerate() {
+            let (face_index, position) = slots[slot];
+            if !assigned.insert((face_index, position)) {
+                continue;
+            }
+            let result = result.unwrap_or((AiVector3D::zero(), true));
+            let vertex = triangles[slot / 3][slot % 3];
+            let same = |existing: &(AiVector3D, bool)| {
+                existing.1 == result.1 && (existing.0 - result.0).square_length() < 1e-10
+            };
+            match tangents[vertex] {
+                None => tangents[vertex] = Some(result),
+                Some(existing) if same(&existing) => {}
+                Some(_) => {
+                    let copy = copies
+                        .entry(vertex)
+                        .or_default()
+                        .iter()
+                        .copied()
+                        .find(|&copy| tangents[copy].as_ref().is_some_and(same));
+                    let copy = copy.unwrap_or_else(|| {
+                        let copy = vertex_count + sources.len();
+                        sources.push(vertex);
+                        tangents.push(Some(result));
+                        copies.entry(vertex).or_default().push(copy);
+                        copy
+                    });
+                    face_updates.push((face_index, position, copy));
+                }
+            }
+        }
+        duplicate_vertices(mesh, mesh_index, skeletons, &sources);
+        for (face_index, position, copy) in face_updates {
+            mesh.faces[face_index][position] = copy;
+        }
+
+        mesh.tangents = Vec::with_capacity(mesh.vertices.len());
+        mesh.bi_tangents = Vec::with_capacity(mesh.vertices.len());
+        for (vertex, result) in tangents.into_iter().enumerate() {
+            let normal = mesh.normals[vertex];
+            let (tangent, orientation_preserving) =
+                result.unwrap_or_else(|| (perpendicular(&normal), true));
+            let sign = if orientation_preserving { 1.0 } else { -1.0 };
+            mesh.tangents.push(tangent);
+            mesh.bi_tangents.push(normal.cross(&tangent) * sign);
+        }
+        true
+    }
+}
 
 impl AiPostProcess for CalcTangentSpaces {
     type Error = String;
@@ -13,8 +403,143 @@ impl AiPostProcess for CalcTangentSpaces {
         steps.contains(AiPostProcessSteps::CalcTangentSpaces)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement tangent space calculation
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            self.process_mesh(mesh, mesh_index, &mut scene.skeletons);
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::AiPrimitiveType;
+
+    /// A quad in the XY plane facing +Z.
+    fn create_quad(uvs: Vec<AiVector3D>) -> AiMesh {
+        AiMesh {
+            vertices: vec![
+                [0.0, 0.0, 0.0].into(),
+                [1.0, 0.0, 0.0].into(),
+                [1.0, 1.0, 0.0].into(),
+                [0.0, 1.0, 0.0].into(),
+            ],
+            normals: vec![[0.0, 0.0, 1.0].into(); 4],
+            texture_coords: [Some(uvs), None, None, None, None, None, None, None],
+            faces: vec![vec![0, 1, 2], vec![0, 2, 3]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(lhs: AiVector3D, rhs: AiVector3D) {
+        assert!((lhs - rhs).len() < 1e-5, "{:?} != {:?}", lhs, rhs);
+    }
+
+    #[test]
+    fn test_calc_tangent_spaces() {
+        let mut scene = AiScene {
+            meshes: vec![create_quad(vec![
+                [0.0, 0.0, 0.0].into(),
+                [1.0, 0.0, 0.0].into(),
+                [1.0, 1.0, 0.0].into(),
+                [0.0, 1.0, 0.0].into(),
+            ])],
+            ..Default::default()
+        };
+        let mut step = CalcTangentSpaces::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::CalcTangentSpaces)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.tangents.len(), 4);
+        for (tangent, bi_tangent) in mesh.tangents.iter().zip(mesh.bi_tangents.iter()) {
+            assert_close(*tangent, AiVector3D::new(1.0, 0.0, 0.0));
+            assert_close(*bi_tangent, AiVector3D::new(0.0, 1.0, 0.0));
+        }
+        assert!(mesh.tangents_with_handedness().iter().all(|t| t[3] == 1.0));
+    }
+
+    #[test]
+    fn test_calc_tangent_spaces_mirrored_uvs() {
+        // U runs against X, the tangent follows U and the handedness flips
+        let mut mesh = create_quad(vec![
+            [1.0, 0.0, 0.0].into(),
+            [0.0, 0.0, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+            [1.0, 1.0, 0.0].into(),
+        ]);
+        assert!(CalcTangentSpaces::default().process_mesh(&mut mesh, 0, &mut []));
+        for tangent in &mesh.tangents {
+            assert_close(*tangent, AiVector3D::new(-1.0, 0.0, 0.0));
+        }
+        for bi_tangent in &mesh.bi_tangents {
+            assert_close(*bi_tangent, AiVector3D::new(0.0, 1.0, 0.0));
+        }
+        assert!(mesh.tangents_with_handedness().iter().all(|t| t[3] == -1.0));
+    }
+
+    #[test]
+    fn test_calc_tangent_spaces_splits_mirror_seam() {
+        // Two quads sharing the edge 1-4, the right quad mirrors U around the shared edge
+        let mut mesh = AiMesh {
+            vertices: vec![
+                [0.0, 0.0, 0.0].into(),
+                [1.0, 0.0, 0.0].into(),
+                [2.0, 0.0, 0.0].into(),
+                [0.0, 1.0, 0.0].into(),
+                [1.0, 1.0, 0.0].into(),
+                [2.0, 1.0, 0.0].into(),
+            ],
+            normals: vec![[0.0, 0.0, 1.0].into(); 6],
+            faces: vec![vec![0, 1, 4, 3], vec![1, 2, 5, 4]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Polygon),
+            ..Default::default()
+        };
+        mesh.texture_coords[0] = Some(vec![
+            [0.0, 0.0, 0.0].into(),
+            [1.0, 0.0, 0.0].into(),
+            [0.0, 0.0, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+            [1.0, 1.0, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+        ]);
+        assert!(CalcTangentSpaces::default().process_mesh(&mut mesh, 0, &mut []));
+
+        // The seam vertices are split between both handednesses
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.texture_coords[0].as_ref().unwrap().len(), 8);
+        let handedness = mesh.tangents_with_handedness();
+        for index in &mesh.faces[0] {
+            assert_eq!(handedness[*index][3], 1.0);
+        }
+        for index in &mesh.faces[1] {
+            assert_eq!(handedness[*index][3], -1.0);
+        }
+    }
+
+    #[test]
+    fn test_calc_tangent_spaces_degenerate_triangle() {
+        let mut mesh = create_quad(vec![
+            [0.0, 0.0, 0.0].into(),
+            [1.0, 0.0, 0.0].into(),
+            [1.0, 1.0, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+        ]);
+        mesh.faces.push(vec![0, 0, 1]);
+        assert!(CalcTangentSpaces::default().process_mesh(&mut mesh, 0, &mut []));
+        assert_eq!(mesh.vertices.len(), 4);
+        for tangent in &mesh.tangents {
+            assert_close(*tangent, AiVector3D::new(1.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_calc_tangent_spaces_requires_uvs() {
+        let mut mesh = create_quad(Vec::new());
+        mesh.texture_coords[0] = None;
+        assert!(!CalcTangentSpaces::default().process_mesh(&mut mesh, 0, &mut []));
+        assert!(mesh.tangents.is_empty());
+    }
+}
@@ -1,10 +1,128 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::{collections::HashSet, mem};
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_DB_ALL_OR_NONE_DEFAULT, AI_CONFIG_PP_DB_INFLUENCE_THRESHOLD_DEFAULT,
+    AI_CONFIG_PP_DB_THRESHOLD_DEFAULT, AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiMesh, AiReal, AiScene};
 use enumflags2::BitFlags;
 
+use crate::helper::{extract_sub_mesh, replace_meshes, skeleton_bound_meshes, transform_mesh};
+
+/// Bones driving a vertex with a weight of at least the threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VertexOwner {
+    None,
+    Bone(usize),
+    Shared,
+}
+
 /// Debone meshes
-#[derive(Default)]
-pub struct Debone;
+///
+/// Splits the faces of a mesh driven entirely by a single bone into a static mesh attached to
+/// the node of that bone. A vertex is driven by a bone if its weight is at least `threshold`,
+/// smaller weights on such vertices are dropped. The static meshes are moved to the space of
+/// their bone by its offset matrix. Faces mixing bones stay in the skinned mesh, unless
+/// `all_or_none` is set, which only debones meshes that are split completely.
+///
+/// Bones of a deboned mesh without a weight above `influence_threshold` are removed from its
+/// skinned part, renormalizing the weights of the vertices they influenced. Meshes that are not
+/// deboned keep all their bones.
+///
+/// Like skinning, this assumes skinned meshes are attached to nodes without a transform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Debone {
+    pub threshold: AiReal,
+    pub influence_threshold: AiReal,
+    pub all_or_none: bool,
+}
+
+impl Default for Debone {
+    fn default() -> Self {
+        Self {
+            threshold: AI_CONFIG_PP_DB_THRESHOLD_DEFAULT,
+            influence_threshold: AI_CONFIG_PP_DB_INFLUENCE_THRESHOLD_DEFAULT,
+            all_or_none: AI_CONFIG_PP_DB_ALL_OR_NONE_DEFAULT,
+        }
+    }
+}
+
+impl Debone {
+    /// Removes the bones of a mesh without a weight above `influence_threshold`, and
+    /// renormalizes the weights of the vertices they influenced. Returns the number of removed
+    /// bones.
+    fn remove_weak_bones(&self, mesh: &mut AiMesh) -> usize {
+        let bone_count = mesh.bones.len();
+        let mut touched = vec![false; mesh.vertices.len()];
+        mesh.bones.retain(|bone| {
+            let keep = bone
+                .weights
+                .iter()
+                .any(|weight| weight.weight > self.influence_threshold);
+            if !keep {
+                for weight in bone.weights.iter() {
+                    if let Some(touched) = touched.get_mut(weight.vertex_id) {
+                        *touched = true;
+                    }
+                }
+            }
+            keep
+        });
+        let removed = bone_count - mesh.bones.len();
+        if removed == 0 {
+            return 0;
+        }
+
+        let mut totals: Vec<AiReal> = vec![0.0; mesh.vertices.len()];
+        for weight in mesh.bones.iter().flat_map(|bone| bone.weights.iter()) {
+            if let Some(total) = totals.get_mut(weight.vertex_id) {
+                *total += weight.weight;
+            }
+        }
+        for weight in mesh
+            .bones
+            .iter_mut()
+            .flat_map(|bone| bone.weights.iter_mut())
+        {
+            let vertex = weight.vertex_id;
+            if touched.get(vertex) == Some(&true) && totals[vertex] > 0.0 {
+                weight.weight /= totals[vertex];
+            }
+        }
+        removed
+    }
+
+    /// Returns the bone driving each face on its own, if any.
+    fn face_owners(&self, mesh: &AiMesh) -> Vec<Option<usize>> {
+        let mut owners = vec![VertexOwner::None; mesh.vertices.len()];
+        for (bone_index, bone) in mesh.bones.iter().enumerate() {
+            for weight in bone.weights.iter() {
+                if weight.weight < self.threshold {
+                    continue;
+                }
+                if let Some(owner) = owners.get_mut(weight.vertex_id) {
+                    *owner = match *owner {
+                        VertexOwner::None => VertexOwner::Bone(bone_index),
+                        VertexOwner::Bone(other) if other == bone_index => VertexOwner::Bone(other),
+                        _ => VertexOwner::Shared,
+                    };
+                }
+            }
+        }
+        mesh.faces
+            .iter()
+            .map(|face| {
+                let owner = *owners.get(*face.first()?)?;
+                let VertexOwner::Bone(bone_index) = owner else {
+                    return None;
+                };
+                face.iter()
+                    .all(|&index| owners.get(index) == Some(&owner))
+                    .then_some(bone_index)
+            })
+            .collect()
+    }
+}
 
 impl AiPostProcess for Debone {
     type Error = String;
@@ -14,7 +132,250 @@ impl AiPostProcess for Debone {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement deboning
+        let node_count = scene.nodes.arena.len();
+        let mut parts = Vec::with_capacity(scene.meshes.len());
+        // New mesh index and bone node of every static mesh
+        let mut bone_parts: Vec<(usize, usize)> = Vec::new();
+        let mut mesh_count = 0;
+        let mut removed_bones = 0;
+        let bound = skeleton_bound_meshes(scene);
+        for (mesh, bound) in mem::take(&mut scene.meshes).into_iter().zip(bound) {
+            let mut bone_faces: Vec<Vec<usize>> = vec![Vec::new(); mesh.bones.len()];
+            let mut skinned_faces: Vec<usize> = Vec::new();
+            for (face_index, owner) in self.face_owners(&mesh).into_iter().enumerate() {
+                match owner {
+                    Some(bone_index) if mesh.bones[bone_index].node_index < node_count => {
+                        bone_faces[bone_index].push(face_index)
+                    }
+                    _ => skinned_faces.push(face_index),
+                }
+            }
+            if bound && bone_faces.iter().any(|faces| !faces.is_empty()) {
+                log::warn!(
+                    "Mesh {} is bound to a skeleton and is not deboned",
+                    mesh.name
+                );
+            }
+            if bound
+                || bone_faces.iter().all(Vec::is_empty)
+                || (self.all_or_none && !skinned_faces.is_empty())
+            {
+                let vertex_count = mesh.vertices.len();
+                parts.push(vec![(mesh, (0..vertex_count).collect())]);
+                mesh_count += 1;
+                continue;
+            }
+
+            let mut mesh_parts = Vec::new();
+            if !skinned_faces.is_empty() {
+                let (mut part, vertices) = extract_sub_mesh(&mesh, &skinned_faces);
+                removed_bones += self.remove_weak_bones(&mut part);
+                mesh_parts.push((part, vertices));
+            }
+            for (bone, faces) in mesh.bones.iter().zip(&bone_faces) {
+                if faces.is_empty() {
+                    continue;
+                }
+                let (mut part, vertices) = extract_sub_mesh(&mesh, faces);
+                part.name = format!("{}_{}", mesh.name, bone.name);
+                part.bones.clear();
+                transform_mesh(&mut part, &bone.offset_matrix);
+                bone_parts.push((mesh_count + mesh_parts.len(), bone.node_index));
+                mesh_parts.push((part, vertices));
+            }
+            mesh_count += mesh_parts.len();
+            parts.push(mesh_parts);
+        }
+
+        if bone_parts.is_empty() {
+            scene.meshes = parts.into_iter().flatten().map(|(mesh, _)| mesh).collect();
+        } else {
+            replace_meshes(scene, parts);
+            let static_meshes: HashSet<usize> = bone_parts
+                .iter()
+                .map(|&(mesh_index, _)| mesh_index)
+                .collect();
+            for node in scene.nodes.arena.iter_mut() {
+                node.mesh_indexes
+                    .retain(|mesh_index| !static_meshes.contains(mesh_index));
+            }
+            for &(mesh_index, node_index) in &bone_parts {
+                scene.nodes.arena[node_index].mesh_indexes.push(mesh_index);
+            }
+        }
+        log::info!(
+            "Debone finished. Removed {} weak bones and split off {} static meshes",
+            removed_bones,
+            bone_parts.len()
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiBone, AiMatrix4x4, AiNode, AiVector3D, AiVertexWeight};
+
+    fn create_bone(name: &str, node_index: usize, weights: &[(usize, AiReal)]) -> AiBone {
+        AiBone {
+            name: name.to_string(),
+            node_index,
+            weights: weights
+                .iter()
+                .map(|&(vertex, weight)| AiVertexWeight::new(vertex, weight))
+                .collect(),
+            offset_matrix: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, -1.0)),
+            ..Default::default()
+        }
+    }
+
+    fn create_scene(bones: Vec<AiBone>, faces: Vec<Vec<usize>>) -> AiScene {
+        let mesh = AiMesh {
+            name: "mesh".to_string(),
+            vertices: (0..9)
+                .map(|index| AiVector3D::new(index as AiReal, 0.0, 1.0))
+                .collect(),
+            faces,
+            bones,
+            ..Default::default()
+        };
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        let root = scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: "root".to_string(),
+                    mesh_indexes: vec![0],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        for name in ["a", "b", "c"] {
+            scene
+                .nodes
+                .insert(
+                    AiNode {
+                        name: name.to_string(),
+                        ..Default::default()
+                    },
+                    Some(root),
+                )
+                .unwrap();
+        }
+        scene
+    }
+
+    #[test]
+    fn test_split_static_meshes() {
+        let mut scene = create_scene(
+            vec![
+                create_bone("a", 1, &[(0, 1.0), (1, 1.0), (2, 1.0)]),
+                create_bone("b", 2, &[(3, 1.0), (4, 1.0), (5, 1.0)]),
+                create_bone("c", 3, &[]),
+            ],
+            vec![vec![0, 1, 2], vec![3, 4, 5]],
+        );
+        assert!(Debone::default().process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert!(scene.meshes.iter().all(|mesh| mesh.bones.is_empty()));
+        assert_eq!(scene.meshes[0].name, "mesh_a");
+        assert_eq!(scene.meshes[0].vertices[1], AiVector3D::new(1.0, 0.0, 0.0));
+        let node_meshes: Vec<Vec<usize>> = scene
+            .nodes
+            .arena
+            .iter()
+            .map(|node| node.mesh_indexes.clone())
+            .collect();
+        assert_eq!(node_meshes, vec![vec![], vec![0], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_weak_bones_removed() {
+        let mut scene = create_scene(
+            vec![
+                create_bone("a", 1, &[(0, 1.0), (1, 1.0), (2, 1.0), (3, 0.5), (4, 0.5)]),
+                create_bone("b", 2, &[(3, 0.45), (4, 0.5), (5, 1.0)]),
+                create_bone("c", 3, &[(3, 0.05)]),
+            ],
+            vec![vec![0, 1, 2], vec![3, 4, 5]],
+        );
+        let step = Debone {
+            influence_threshold: 0.1,
+            ..Default::default()
+        };
+        assert!(step.process(&mut scene).is_ok());
+
+        // Bone c stays below the influence threshold in the skinned part
+        assert_eq!(scene.meshes.len(), 2);
+        let skinned = &scene.meshes[0];
+        assert_eq!(skinned.name, "mesh");
+        let names: Vec<&str> = skinned
+            .bones
+            .iter()
+            .map(|bone| bone.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!((skinned.bones[0].weights[0].weight - 0.5 / 0.95).abs() < 1e-6);
+        assert!((skinned.bones[1].weights[0].weight - 0.45 / 0.95).abs() < 1e-6);
+        // Vertex 4 was not influenced by the removed bone
+        assert_eq!(skinned.bones[0].weights[1].weight, 0.5);
+        assert_eq!(scene.meshes[1].name, "mesh_a");
+    }
+
+    #[test]
+    fn test_blended_bones_kept_by_default() {
+        let mut scene = create_scene(
+            vec![
+                create_bone("a", 1, &[(0, 0.5), (1, 0.5), (2, 0.5)]),
+                create_bone("b", 2, &[(0, 0.5), (1, 0.5), (2, 0.5)]),
+                create_bone("c", 3, &[(0, 0.0)]),
+            ],
+            vec![vec![0, 1, 2]],
+        );
+        assert!(Debone::default().process(&mut scene).is_ok());
+
+        // No bone drives a face on its own, so the mesh is not deboned and keeps every bone
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].bones.len(), 3);
+        assert!(
+            scene.meshes[0].bones[..2]
+                .iter()
+                .flat_map(|bone| bone.weights.iter())
+                .all(|weight| weight.weight == 0.5)
+        );
+    }
+
+    #[test]
+    fn test_shared_faces_stay_skinned() {
+        let bones = vec![
+            create_bone("a", 1, &[(0, 1.0), (1, 1.0), (2, 1.0), (6, 0.5)]),
+            create_bone("b", 2, &[(3, 1.0), (4, 0.5), (5, 0.5), (6, 0.5)]),
+        ];
+        let faces = vec![vec![0, 1, 2], vec![3, 4, 5], vec![0, 3, 6]];
+
+        let mut scene = create_scene(bones.clone(), faces.clone());
+        assert!(Debone::default().process(&mut scene).is_ok());
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.meshes[0].name, "mesh");
+        assert_eq!(scene.meshes[0].faces.len(), 2);
+        assert_eq!(scene.meshes[0].bones.len(), 2);
+        assert_eq!(scene.meshes[1].name, "mesh_a");
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0]);
+        assert_eq!(scene.nodes.arena[1].mesh_indexes, vec![1]);
+
+        let mut scene = create_scene(bones, faces);
+        let step = Debone {
+            all_or_none: true,
+            ..Default::default()
+        };
+        assert!(step.process(&mut scene).is_ok());
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].faces.len(), 3);
+    }
+}
@@ -1,10 +1,77 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::{
+    collections::HashMap,
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use asset_importer_rs_core::{
+    AI_METADATA_SOURCE_FILE_PATH, AiPostProcess, AiPostProcessSteps, DataLoader, ReadSeek,
+    default_file_loader,
+};
+use asset_importer_rs_scene::{AiMetadataEntry, AiScene, AiTexture, AiTextureFormat, matkey};
 use enumflags2::BitFlags;
+use image::{ImageFormat, ImageReader};
 
 /// Embed textures
-#[derive(Default)]
-pub struct EmbedTextures;
+///
+/// Loads the files referenced by the `$tex.file` properties of every material through `loader`,
+/// appends them to the scene textures and rewrites the paths to `*N` references. Relative paths
+/// are resolved against the directory of the file the scene was imported from, as stored in the
+/// `SourceAsset_FilePath` metadata. Files referenced several times are embedded once.
+///
+/// Textures keep the compressed file contents, so exporters write them without re-encoding. Only
+/// the image header is read, to find the format and size of the texture. Files that cannot be
+/// loaded or whose format is not supported are left referenced by path with a warning.
+pub struct EmbedTextures {
+    loader: Box<DataLoader<'static>>,
+}
+
+impl Default for EmbedTextures {
+    fn default() -> Self {
+        Self::with_loader(|path| {
+            default_file_loader(path).map(|reader| Box::new(reader) as Box<dyn ReadSeek>)
+        })
+    }
+}
+
+impl EmbedTextures {
+    /// Creates the step with a loader to read texture files from.
+    pub fn with_loader(loader: impl Fn(&Path) -> io::Result<Box<dyn ReadSeek>> + 'static) -> Self {
+        Self {
+            loader: Box::new(loader),
+        }
+    }
+
+    /// Loads a texture file, keeping its compressed contents.
+    fn load_texture(&self, path: &Path) -> Result<AiTexture, String> {
+        let mut buffer = Vec::new();
+        (self.loader)(path)
+            .and_then(|mut reader| reader.read_to_end(&mut buffer))
+            .map_err(|error| error.to_string())?;
+        let format = image::guess_format(&buffer)
+            .or_else(|error| {
+                path.extension()
+                    .and_then(ImageFormat::from_extension)
+                    .ok_or(error)
+            })
+            .map_err(|error| error.to_string())?;
+        let texture_format = AiTextureFormat::try_from(format)
+            .map_err(|_| format!("Unsupported texture format {:?}", format))?;
+        let (width, height) = ImageReader::with_format(Cursor::new(&buffer), format)
+            .into_dimensions()
+            .map_err(|error| error.to_string())?;
+        Ok(AiTexture::from_compressed(
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            width,
+            height,
+            texture_format,
+            buffer,
+        ))
+    }
+}
 
 impl AiPostProcess for EmbedTextures {
     type Error = String;
@@ -14,7 +81,149 @@ impl AiPostProcess for EmbedTextures {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement texture embedding
+        let base_directory = match scene.metadata.get(AI_METADATA_SOURCE_FILE_PATH) {
+            Some(AiMetadataEntry::AiStr(source)) => Path::new(source)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            _ => PathBuf::new(),
+        };
+
+        let mut embedded: HashMap<String, Option<usize>> = HashMap::new();
+        let mut count = 0;
+        for material in scene.materials.iter_mut() {
+            for property in material.iter_mut() {
+                if property.key != matkey::_AI_MATKEY_TEXTURE_BASE {
+                    continue;
+                }
+                let Ok(file) = String::from_utf8(property.data.clone()) else {
+                    continue;
+                };
+                if file.is_empty() || file.starts_with('*') {
+                    continue;
+                }
+                let index = *embedded.entry(file.clone()).or_insert_with(|| {
+                    let path = base_directory.join(&file);
+                    match self.load_texture(&path) {
+                        Ok(texture) => {
+                            scene.textures.push(texture);
+                            Some(scene.textures.len() - 1)
+                        }
+                        Err(error) => {
+                            log::warn!("Failed to embed texture {}: {}", path.display(), error);
+                            None
+                        }
+                    }
+                });
+                if let Some(index) = index {
+                    property.data = format!("*{}", index).into_bytes();
+                    count += 1;
+                }
+            }
+        }
+        log::info!(
+            "EmbedTextures finished. Embedded {} textures for {} references",
+            embedded.values().flatten().count(),
+            count
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiMaterial, AiPropertyTypeInfo, AiTextureType};
+
+    fn create_material(file: &str) -> AiMaterial {
+        let mut material = AiMaterial::new();
+        material.add_property(
+            matkey::_AI_MATKEY_TEXTURE_BASE,
+            Some(AiTextureType::Diffuse),
+            AiPropertyTypeInfo::Binary,
+            0,
+            file.bytes().collect(),
+        );
+        material
+    }
+
+    fn texture_file(scene: &AiScene, material: usize) -> String {
+        scene.materials[material]
+            .get_property_ai_str(
+                matkey::_AI_MATKEY_TEXTURE_BASE,
+                Some(AiTextureType::Diffuse),
+                0,
+            )
+            .unwrap()
+            .unwrap()
+    }
+
+    fn create_png() -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(2, 1, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn create_step() -> EmbedTextures {
+        let png = create_png();
+        EmbedTextures::with_loader(move |path| {
+            if path == Path::new("models/textures/red.png") {
+                Ok(Box::new(Cursor::new(png.clone())))
+            } else {
+                Err(io::Error::new(io::ErrorKind::NotFound, "File not found"))
+            }
+        })
+    }
+
+    #[test]
+    fn test_embed_textures() {
+        let mut scene = AiScene {
+            materials: vec![
+                create_material("textures/red.png"),
+                create_material("textures/red.png"),
+                create_material("*0"),
+            ],
+            textures: vec![AiTexture::default()],
+            ..Default::default()
+        };
+        scene.metadata.insert(
+            AI_METADATA_SOURCE_FILE_PATH.to_string(),
+            AiMetadataEntry::AiStr("models/scene.obj".to_string()),
+        );
+        assert!(create_step().process(&mut scene).is_ok());
+
+        assert_eq!(scene.textures.len(), 2);
+        assert_eq!(scene.textures[1].filename, "red.png");
+        assert_eq!(scene.textures[1].width, 2);
+        assert_eq!(scene.textures[1].height, 1);
+        assert_eq!(scene.textures[1].ach_format_hint, AiTextureFormat::PNG);
+        assert_eq!(scene.textures[1].compressed_data, create_png());
+        assert!(scene.textures[1].texel.is_empty());
+        // Exporters write the file as it was loaded
+        let export = scene.textures[1].export(&[AiTextureFormat::PNG]).unwrap();
+        assert_eq!(export.data, create_png());
+        let export = scene.textures[1].export(&[AiTextureFormat::JPEG]).unwrap();
+        assert_eq!(export.format, AiTextureFormat::JPEG);
+        assert_eq!(
+            image::guess_format(&export.data).unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert_eq!(texture_file(&scene, 0), "*1");
+        assert_eq!(texture_file(&scene, 1), "*1");
+        assert_eq!(texture_file(&scene, 2), "*0");
+    }
+
+    #[test]
+    fn test_missing_texture_kept() {
+        let mut scene = AiScene {
+            materials: vec![create_material("missing.png")],
+            ..Default::default()
+        };
+        assert!(create_step().process(&mut scene).is_ok());
+
+        assert!(scene.textures.is_empty());
+        assert_eq!(texture_file(&scene, 0), "missing.png");
+    }
+}
@@ -1,10 +1,94 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_FD_CHECKAREA_DEFAULT, AI_CONFIG_PP_FD_REMOVE_DEFAULT, AiPostProcess,
+    AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiFace, AiMesh, AiScene};
 use enumflags2::BitFlags;
 
-/// Find degenerate triangles
-#[derive(Default)]
-pub struct FindDegenerates;
+use crate::helper::{EpsilonCompute, face_primitive_types, remove_meshes};
+
+/// Find degenerate faces
+///
+/// A face is degenerate if several of its indices point at the same position, or, with
+/// `check_area`, if it is a triangle without area. Repeated positions are dropped from the face
+/// and triangles without area lose their middle vertex, turning them into lines and points.
+/// With `remove`, faces left with fewer than three indices are removed instead, and so are
+/// meshes left without faces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FindDegenerates {
+    pub remove: bool,
+    pub check_area: bool,
+}
+
+impl Default for FindDegenerates {
+    fn default() -> Self {
+        Self {
+            remove: AI_CONFIG_PP_FD_REMOVE_DEFAULT,
+            check_area: AI_CONFIG_PP_FD_CHECKAREA_DEFAULT,
+        }
+    }
+}
+
+impl FindDegenerates {
+    /// Fixes the degenerate faces of a mesh, returning how many were found.
+    fn process_mesh(&self, mesh: &mut AiMesh) -> usize {
+        let epsilon = mesh.epsilon();
+        let same_position = |lhs: usize, rhs: usize| {
+            lhs == rhs
+                || matches!(
+                    (mesh.vertices.get(lhs), mesh.vertices.get(rhs)),
+                    (Some(a), Some(b)) if a == b
+                )
+        };
+
+        let mut degenerates = 0;
+        let mut faces: Vec<AiFace> = Vec::with_capacity(mesh.faces.len());
+        for face in &mesh.faces {
+            let mut compact: AiFace = Vec::with_capacity(face.len());
+            for &index in face {
+                if !compact.iter().any(|&other| same_position(other, index)) {
+                    compact.push(index);
+                }
+            }
+            let mut degenerate = compact.len() < face.len();
+
+            if self.check_area
+                && compact.len() == 3
+                && compact.iter().all(|&index| index < mesh.vertices.len())
+            {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[compact[i]]);
+                let area = (b - a).cross(&(c - a)).len() * 0.5;
+                if area <= epsilon * epsilon {
+                    degenerate = true;
+                    // The middle vertex is the one opposite to the longest edge
+                    let lengths = [
+                        (c - b).square_length(),
+                        (a - c).square_length(),
+                        (b - a).square_length(),
+                    ];
+                    let middle = (0..3)
+                        .max_by(|&lhs, &rhs| lengths[lhs].total_cmp(&lengths[rhs]))
+                        .unwrap_or(0);
+                    compact.remove(middle);
+                }
+            }
+
+            if degenerate {
+                degenerates += 1;
+                if self.remove && compact.len() < 3 {
+                    continue;
+                }
+            }
+            faces.push(compact);
+        }
+
+        if degenerates > 0 {
+            mesh.faces = faces;
+            mesh.primitive_types = face_primitive_types(mesh);
+        }
+        degenerates
+    }
+}
 
 impl AiPostProcess for FindDegenerates {
     type Error = String;
@@ -13,8 +97,120 @@ impl AiPostProcess for FindDegenerates {
         steps.contains(AiPostProcessSteps::FindDegenerates)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement degenerate triangle detection
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let mut removed = vec![false; scene.meshes.len()];
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            let degenerates = self.process_mesh(mesh);
+            if degenerates > 0 {
+                log::debug!("Mesh {} has {} degenerate faces", mesh.name, degenerates);
+            }
+            if self.remove && degenerates > 0 && mesh.faces.is_empty() {
+                log::warn!(
+                    "Mesh {} only has degenerate faces and is removed",
+                    mesh.name
+                );
+                removed[mesh_index] = true;
+            }
+        }
+        remove_meshes(scene, &removed);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiNode, AiPrimitiveType, AiVector3D};
+
+    fn create_mesh() -> AiMesh {
+        AiMesh {
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 1.0, 0.0),
+                AiVector3D::new(2.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+            ],
+            faces: vec![
+                vec![0, 1, 2],
+                // Vertex 4 shares the position of vertex 1
+                vec![0, 1, 4],
+                // Collinear, vertex 1 lies between vertices 0 and 3
+                vec![0, 3, 1],
+                vec![0, 1, 2, 4],
+            ],
+            primitive_types: AiPrimitiveType::Triangle | AiPrimitiveType::Polygon,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_degenerates_convert() {
+        let mut scene = AiScene {
+            meshes: vec![create_mesh()],
+            ..Default::default()
+        };
+        let mut step = FindDegenerates::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::FindDegenerates)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(
+            mesh.faces,
+            vec![vec![0, 1, 2], vec![0, 1], vec![0, 3], vec![0, 1, 2]]
+        );
+        assert_eq!(
+            mesh.primitive_types,
+            AiPrimitiveType::Triangle | AiPrimitiveType::Line
+        );
+    }
+
+    #[test]
+    fn test_find_degenerates_without_area_check() {
+        let mut mesh = create_mesh();
+        let step = FindDegenerates {
+            check_area: false,
+            ..Default::default()
+        };
+        assert_eq!(step.process_mesh(&mut mesh), 2);
+        assert_eq!(mesh.faces[2], vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn test_find_degenerates_remove() {
+        let mut scene = AiScene {
+            meshes: vec![
+                AiMesh {
+                    vertices: vec![AiVector3D::zero(); 3],
+                    faces: vec![vec![0, 1, 2]],
+                    ..Default::default()
+                },
+                create_mesh(),
+            ],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0, 1],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        let step = FindDegenerates {
+            remove: true,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0]);
+        assert_eq!(scene.meshes[0].faces, vec![vec![0, 1, 2], vec![0, 1, 2]]);
+        assert_eq!(
+            scene.meshes[0].primitive_types,
+            BitFlags::from(AiPrimitiveType::Triangle)
+        );
+    }
+}
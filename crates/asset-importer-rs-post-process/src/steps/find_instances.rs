@@ -1,10 +1,131 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use asset_importer_rs_core::{AI_CONFIG_PP_FI_EPSILON_DEFAULT, AiPostProcess, AiPostProcessSteps};
+use asset_importer_rs_scene::{AiColor4D, AiMesh, AiReal, AiScene, AiVector3D, ai_real_to_f32};
 use enumflags2::BitFlags;
 
+use crate::helper::remove_meshes;
+
 /// Find instances
-#[derive(Default)]
-pub struct FindInstances;
+///
+/// Detects meshes that are identical to an earlier mesh, with the same material, faces and
+/// vertex data within `epsilon`, and replaces node references to them with references to the
+/// earlier mesh. The duplicates are then removed.
+///
+/// Meshes with bones, skeleton bindings, morph targets or mesh animations are never instanced,
+/// since they are deformed individually.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FindInstances {
+    pub epsilon: AiReal,
+}
+
+impl Default for FindInstances {
+    fn default() -> Self {
+        Self {
+            epsilon: AI_CONFIG_PP_FI_EPSILON_DEFAULT,
+        }
+    }
+}
+
+impl FindInstances {
+    /// Returns whether the mesh can be shared between nodes.
+    fn can_instance(scene: &AiScene, mesh_index: usize) -> bool {
+        let mesh = &scene.meshes[mesh_index];
+        mesh.bones.is_empty()
+            && mesh.anim_meshes.is_empty()
+            && !scene.skeletons.iter().any(|skeleton| {
+                skeleton
+                    .bones()
+                    .iter()
+                    .any(|bone| bone.mesh_index() == Some(mesh_index))
+            })
+            && !scene.animations.iter().any(|animation| {
+                animation
+                    .mesh_channels
+                    .iter()
+                    .any(|channel| channel.name() == mesh.name)
+                    || animation
+                        .morph_channels
+                        .iter()
+                        .any(|channel| channel.name == mesh.name)
+            })
+    }
+
+    /// Hashes everything two instances must share exactly.
+    fn hash_mesh(&self, mesh: &AiMesh) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        mesh.material_index.hash(&mut hasher);
+        mesh.primitive_types.bits().hash(&mut hasher);
+        mesh.vertices.len().hash(&mut hasher);
+        mesh.normals.len().hash(&mut hasher);
+        mesh.tangents.len().hash(&mut hasher);
+        mesh.bi_tangents.len().hash(&mut hasher);
+        for channel in &mesh.texture_coords {
+            channel.as_ref().map(Vec::len).hash(&mut hasher);
+        }
+        for channel in &mesh.colors {
+            channel.as_ref().map(Vec::len).hash(&mut hasher);
+        }
+        mesh.faces.hash(&mut hasher);
+        if self.epsilon == 0.0 {
+            for vertex in &mesh.vertices {
+                [vertex.x, vertex.y, vertex.z]
+                    .map(AiReal::to_bits)
+                    .hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    fn vectors_match(&self, a: &[AiVector3D], b: &[AiVector3D]) -> bool {
+        let epsilon = self.epsilon * self.epsilon;
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| (a - b).square_length() <= epsilon)
+    }
+
+    fn colors_match(&self, a: &[AiColor4D], b: &[AiColor4D]) -> bool {
+        let epsilon = ai_real_to_f32(self.epsilon);
+        a.len() == b.len()
+            && a.iter().zip(b).all(|(a, b)| {
+                (a.r - b.r).abs() <= epsilon
+                    && (a.g - b.g).abs() <= epsilon
+                    && (a.b - b.b).abs() <= epsilon
+                    && (a.a - b.a).abs() <= epsilon
+            })
+    }
+
+    /// Returns whether `instance` can be replaced by `mesh`.
+    fn is_instance(&self, mesh: &AiMesh, instance: &AiMesh) -> bool {
+        mesh.material_index == instance.material_index
+            && mesh.primitive_types == instance.primitive_types
+            && mesh.faces == instance.faces
+            && self.vectors_match(&mesh.vertices, &instance.vertices)
+            && self.vectors_match(&mesh.normals, &instance.normals)
+            && self.vectors_match(&mesh.tangents, &instance.tangents)
+            && self.vectors_match(&mesh.bi_tangents, &instance.bi_tangents)
+            && mesh
+                .texture_coords
+                .iter()
+                .zip(&instance.texture_coords)
+                .all(|channels| match channels {
+                    (Some(a), Some(b)) => self.vectors_match(a, b),
+                    (a, b) => a.is_none() && b.is_none(),
+                })
+            && mesh
+                .colors
+                .iter()
+                .zip(&instance.colors)
+                .all(|channels| match channels {
+                    (Some(a), Some(b)) => self.colors_match(a, b),
+                    (a, b) => a.is_none() && b.is_none(),
+                })
+    }
+}
 
 impl AiPostProcess for FindInstances {
     type Error = String;
@@ -14,7 +135,149 @@ impl AiPostProcess for FindInstances {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement instance finding
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut instance_of: Vec<usize> = (0..scene.meshes.len()).collect();
+        for (mesh_index, instance) in instance_of.iter_mut().enumerate() {
+            if !Self::can_instance(scene, mesh_index) {
+                continue;
+            }
+            let mesh = &scene.meshes[mesh_index];
+            let bucket = buckets.entry(self.hash_mesh(mesh)).or_default();
+            match bucket
+                .iter()
+                .find(|&&other| self.is_instance(&scene.meshes[other], mesh))
+            {
+                Some(&other) => *instance = other,
+                None => bucket.push(mesh_index),
+            }
+        }
+
+        let removed: Vec<bool> = instance_of
+            .iter()
+            .enumerate()
+            .map(|(index, &instance)| index != instance)
+            .collect();
+        let removed_count = removed.iter().filter(|&&removed| removed).count();
+        if removed_count == 0 {
+            log::debug!("FindInstances finished. No instanced meshes found");
+            return Ok(());
+        }
+        for node in scene.nodes.arena.iter_mut() {
+            for mesh_index in node.mesh_indexes.iter_mut() {
+                if let Some(&instance) = instance_of.get(*mesh_index) {
+                    *mesh_index = instance;
+                }
+            }
+        }
+        remove_meshes(scene, &removed);
+        log::info!(
+            "FindInstances finished. Found {} instances of other meshes",
+            removed_count
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiBone, AiNode};
+
+    fn create_mesh(name: &str, offset: AiReal) -> AiMesh {
+        AiMesh {
+            name: name.to_string(),
+            vertices: vec![
+                AiVector3D::new(offset, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+            ],
+            faces: vec![vec![0, 1, 2]],
+            ..Default::default()
+        }
+    }
+
+    fn create_scene(meshes: Vec<AiMesh>) -> AiScene {
+        let mut scene = AiScene {
+            meshes,
+            ..Default::default()
+        };
+        let root = scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: "root".to_string(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        for mesh_index in 0..scene.meshes.len() {
+            scene
+                .nodes
+                .insert(
+                    AiNode {
+                        name: format!("node{}", mesh_index),
+                        mesh_indexes: vec![mesh_index],
+                        ..Default::default()
+                    },
+                    Some(root),
+                )
+                .unwrap();
+        }
+        scene
+    }
+
+    fn node_meshes(scene: &AiScene) -> Vec<Vec<usize>> {
+        scene.nodes.arena[1..]
+            .iter()
+            .map(|node| node.mesh_indexes.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_instances_merged() {
+        let mut scene = create_scene(vec![
+            create_mesh("bolt", 0.0),
+            create_mesh("nut", 0.5),
+            create_mesh("bolt.001", 0.0),
+            create_mesh("bolt.002", 0.0),
+        ]);
+        assert!(FindInstances::default().process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.meshes[0].name, "bolt");
+        assert_eq!(scene.meshes[1].name, "nut");
+        assert_eq!(
+            node_meshes(&scene),
+            vec![vec![0], vec![1], vec![0], vec![0]]
+        );
+    }
+
+    #[test]
+    fn test_epsilon() {
+        let meshes = vec![create_mesh("a", 0.0), create_mesh("b", 1e-4)];
+
+        let mut scene = create_scene(meshes.clone());
+        assert!(FindInstances::default().process(&mut scene).is_ok());
+        assert_eq!(scene.meshes.len(), 2);
+
+        let mut scene = create_scene(meshes);
+        let step = FindInstances { epsilon: 1e-3 };
+        assert!(step.process(&mut scene).is_ok());
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(node_meshes(&scene), vec![vec![0], vec![0]]);
+    }
+
+    #[test]
+    fn test_skinned_meshes_kept() {
+        let mut skinned = create_mesh("skinned", 0.0);
+        skinned.bones.push(AiBone {
+            name: "bone".to_string(),
+            ..Default::default()
+        });
+        let mut scene = create_scene(vec![skinned.clone(), skinned]);
+        assert!(FindInstances::default().process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes.len(), 2);
+    }
+}
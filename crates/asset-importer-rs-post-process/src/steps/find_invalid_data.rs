@@ -1,10 +1,135 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_FID_ANIM_ACCURACY_DEFAULT, AI_CONFIG_PP_FID_IGNORE_TEXTURECOORDS_DEFAULT,
+    AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{
+    AiColor4D, AiFace, AiNodeAnim, AiQuaternion, AiReal, AiScene, AiVector3D,
+};
 use enumflags2::BitFlags;
 
+use crate::helper::remove_meshes;
+
 /// Find invalid data
-#[derive(Default)]
-pub struct FindInvalidData;
+///
+/// Meshes with non-finite positions are removed. Normals, tangents, texture coordinates and
+/// colors holding invalid values are removed from the mesh, removed texture coordinate channels
+/// leave an empty slot behind so channel indices stay stable. Animation keys with non-finite
+/// values are dropped, and tracks whose keys are all within `anim_accuracy` of the first key are
+/// reduced to a single key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FindInvalidData {
+    pub anim_accuracy: AiReal,
+    /// Keeps texture coordinate channels that hold a single repeated value.
+    pub ignore_texture_coords: bool,
+}
+
+impl Default for FindInvalidData {
+    fn default() -> Self {
+        Self {
+            anim_accuracy: AI_CONFIG_PP_FID_ANIM_ACCURACY_DEFAULT,
+            ignore_texture_coords: AI_CONFIG_PP_FID_IGNORE_TEXTURECOORDS_DEFAULT,
+        }
+    }
+}
+
+fn is_finite(vector: &AiVector3D) -> bool {
+    vector.x.is_finite() && vector.y.is_finite() && vector.z.is_finite()
+}
+
+fn is_finite_color(color: &AiColor4D) -> bool {
+    color.r.is_finite() && color.g.is_finite() && color.b.is_finite() && color.a.is_finite()
+}
+
+fn is_finite_quaternion(quaternion: &AiQuaternion) -> bool {
+    quaternion.w.is_finite()
+        && quaternion.x.is_finite()
+        && quaternion.y.is_finite()
+        && quaternion.z.is_finite()
+}
+
+/// Checks a normal-like array, vertices only used by points and lines may have zero-length
+/// normals.
+fn are_normals_valid(normals: &[AiVector3D], faces: &[AiFace]) -> bool {
+    if !normals.iter().all(is_finite) {
+        return false;
+    }
+    faces
+        .iter()
+        .filter(|face| face.len() >= 3)
+        .flatten()
+        .all(|&index| {
+            normals
+                .get(index)
+                .is_none_or(|normal| normal.square_length() > 0.0)
+        })
+}
+
+fn are_texture_coords_valid(coords: &[AiVector3D], ignore_repeated: bool) -> bool {
+    if !coords.iter().all(is_finite) {
+        return false;
+    }
+    ignore_repeated || coords.len() <= 1 || coords.iter().any(|coord| *coord != coords[0])
+}
+
+impl FindInvalidData {
+    /// Reduces a track to its first key if all keys are within `anim_accuracy` of it.
+    fn is_constant<T>(&self, keys: &[T], distance: impl Fn(&T, &T) -> AiReal) -> bool {
+        match keys.split_first() {
+            Some((first, rest)) => rest
+                .iter()
+                .all(|key| distance(first, key) <= self.anim_accuracy),
+            None => false,
+        }
+    }
+
+    /// Cleans the keys of a channel, returning how many were dropped.
+    fn process_channel(&self, channel: &mut AiNodeAnim) -> usize {
+        let before =
+            channel.position_keys.len() + channel.rotation_keys.len() + channel.scaling_keys.len();
+
+        channel
+            .position_keys
+            .retain(|key| key.time.is_finite() && is_finite(&key.value));
+        channel
+            .rotation_keys
+            .retain(|key| key.time.is_finite() && is_finite_quaternion(&key.value));
+        channel
+            .scaling_keys
+            .retain(|key| key.time.is_finite() && is_finite(&key.value));
+
+        let vector_distance = |lhs: &AiVector3D, rhs: &AiVector3D| {
+            (lhs.x - rhs.x)
+                .abs()
+                .max((lhs.y - rhs.y).abs())
+                .max((lhs.z - rhs.z).abs())
+        };
+        if self.is_constant(&channel.position_keys, |lhs, rhs| {
+            vector_distance(&lhs.value, &rhs.value)
+        }) {
+            channel.position_keys.truncate(1);
+        }
+        if self.is_constant(&channel.scaling_keys, |lhs, rhs| {
+            vector_distance(&lhs.value, &rhs.value)
+        }) {
+            channel.scaling_keys.truncate(1);
+        }
+        if self.is_constant(&channel.rotation_keys, |lhs, rhs| {
+            let (lhs, rhs) = (&lhs.value, &rhs.value);
+            (lhs.w - rhs.w)
+                .abs()
+                .max((lhs.x - rhs.x).abs())
+                .max((lhs.y - rhs.y).abs())
+                .max((lhs.z - rhs.z).abs())
+        }) {
+            channel.rotation_keys.truncate(1);
+        }
+
+        before
+            - (channel.position_keys.len()
+                + channel.rotation_keys.len()
+                + channel.scaling_keys.len())
+    }
+}
 
 impl AiPostProcess for FindInvalidData {
     type Error = String;
@@ -13,8 +138,236 @@ impl AiPostProcess for FindInvalidData {
         steps.contains(AiPostProcessSteps::FindInvalidData)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement invalid data detection
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let mut removed = vec![false; scene.meshes.len()];
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            if !mesh.vertices.iter().all(is_finite) {
+                log::warn!(
+                    "Mesh {} has invalid vertex positions and is removed",
+                    mesh.name
+                );
+                removed[mesh_index] = true;
+                continue;
+            }
+
+            if !are_normals_valid(&mesh.normals, &mesh.faces) {
+                log::warn!("Mesh {} has invalid normals, they are removed", mesh.name);
+                mesh.normals.clear();
+            }
+            if !are_normals_valid(&mesh.tangents, &mesh.faces)
+                || !are_normals_valid(&mesh.bi_tangents, &mesh.faces)
+            {
+                log::warn!("Mesh {} has invalid tangents, they are removed", mesh.name);
+                mesh.tangents.clear();
+                mesh.bi_tangents.clear();
+            }
+            for (channel, coords) in mesh.texture_coords.iter_mut().enumerate() {
+                if coords.as_ref().is_some_and(|coords| {
+                    !are_texture_coords_valid(coords, self.ignore_texture_coords)
+                }) {
+                    log::warn!(
+                        "Mesh {} has invalid texture coordinates in channel {}, they are removed",
+                        mesh.name,
+                        channel
+                    );
+                    *coords = None;
+                    mesh.texture_coordinate_names[channel].clear();
+                }
+            }
+            for colors in mesh.colors.iter_mut() {
+                if colors
+                    .as_ref()
+                    .is_some_and(|colors| !colors.iter().all(is_finite_color))
+                {
+                    log::warn!(
+                        "Mesh {} has invalid vertex colors, they are removed",
+                        mesh.name
+                    );
+                    *colors = None;
+                }
+            }
+
+            for anim_mesh in mesh.anim_meshes.iter_mut() {
+                if !anim_mesh.vertices.iter().all(is_finite) {
+                    anim_mesh.vertices.clear();
+                }
+                if !are_normals_valid(&anim_mesh.normals, &mesh.faces) {
+                    anim_mesh.normals.clear();
+                }
+                if !are_normals_valid(&anim_mesh.tangents, &mesh.faces)
+                    || !are_normals_valid(&anim_mesh.bi_tangents, &mesh.faces)
+                {
+                    anim_mesh.tangents.clear();
+                    anim_mesh.bi_tangents.clear();
+                }
+                for coords in anim_mesh.texture_coords.iter_mut() {
+                    if coords.as_ref().is_some_and(|coords| {
+                        !are_texture_coords_valid(coords, self.ignore_texture_coords)
+                    }) {
+                        *coords = None;
+                    }
+                }
+                for colors in anim_mesh.colors.iter_mut() {
+                    if colors
+                        .as_ref()
+                        .is_some_and(|colors| !colors.iter().all(is_finite_color))
+                    {
+                        *colors = None;
+                    }
+                }
+            }
+        }
+        remove_meshes(scene, &removed);
+
+        for animation in scene.animations.iter_mut() {
+            for channel in animation.channels.iter_mut() {
+                let dropped = self.process_channel(channel);
+                if dropped > 0 {
+                    log::debug!(
+                        "Animation {} dropped {} keys of channel {}",
+                        animation.name,
+                        dropped,
+                        channel.node_name
+                    );
+                }
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiAnimation, AiMesh, AiNode, AiQuatKey, AiVectorKey};
+
+    fn create_mesh() -> AiMesh {
+        let mut mesh = AiMesh {
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![AiVector3D::new(0.0, 0.0, 1.0); 3],
+            faces: vec![vec![0, 1, 2]],
+            ..Default::default()
+        };
+        mesh.texture_coords[0] = Some(vec![
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(1.0, 0.0, 0.0),
+            AiVector3D::new(0.0, 1.0, 0.0),
+        ]);
+        mesh.texture_coords[1] = Some(vec![AiVector3D::new(0.5, 0.5, 0.0); 3]);
+        mesh.texture_coordinate_names[1] = "Repeated".to_string();
+        mesh
+    }
+
+    #[test]
+    fn test_find_invalid_mesh_data() {
+        let mut mesh = create_mesh();
+        mesh.normals[1] = AiVector3D::zero();
+        mesh.colors[0] = Some(vec![AiColor4D::new(f32::NAN, 0.0, 0.0, 1.0); 3]);
+
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        let mut step = FindInvalidData::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::FindInvalidData)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert!(mesh.normals.is_empty());
+        assert!(mesh.colors[0].is_none());
+        assert!(mesh.texture_coords[0].is_some());
+        assert!(mesh.texture_coords[1].is_none());
+        assert!(mesh.texture_coordinate_names[1].is_empty());
+
+        let mut scene = AiScene {
+            meshes: vec![create_mesh()],
+            ..Default::default()
+        };
+        let step = FindInvalidData {
+            ignore_texture_coords: true,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+        assert!(!scene.meshes[0].normals.is_empty());
+        assert!(scene.meshes[0].texture_coords[1].is_some());
+    }
+
+    #[test]
+    fn test_find_invalid_positions() {
+        let mut invalid = create_mesh();
+        invalid.vertices[0].x = AiReal::INFINITY;
+        let mut scene = AiScene {
+            meshes: vec![invalid, create_mesh()],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0, 1],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        FindInvalidData::default().process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0]);
+    }
+
+    #[test]
+    fn test_find_invalid_animation_keys() {
+        let position_key = |time: f64, x: AiReal| AiVectorKey {
+            time,
+            value: AiVector3D::new(x, 0.0, 0.0),
+            ..Default::default()
+        };
+        let mut scene = AiScene {
+            animations: vec![AiAnimation {
+                channels: vec![AiNodeAnim {
+                    node_name: "Node".to_string(),
+                    position_keys: vec![
+                        position_key(0.0, 0.0),
+                        position_key(f64::NAN, 1.0),
+                        position_key(1.0, AiReal::NAN),
+                        position_key(2.0, 2.0),
+                    ],
+                    rotation_keys: vec![
+                        AiQuatKey {
+                            time: 0.0,
+                            value: AiQuaternion::default(),
+                            ..Default::default()
+                        },
+                        AiQuatKey {
+                            time: 1.0,
+                            value: AiQuaternion::default(),
+                            ..Default::default()
+                        },
+                    ],
+                    scaling_keys: vec![position_key(0.0, 1.0), position_key(1.0, 1.05)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let step = FindInvalidData {
+            anim_accuracy: 0.1,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        let channel = &scene.animations[0].channels[0];
+        assert_eq!(
+            channel.position_keys,
+            vec![position_key(0.0, 0.0), position_key(2.0, 2.0)]
+        );
+        assert_eq!(channel.rotation_keys.len(), 1);
+        assert_eq!(channel.scaling_keys, vec![position_key(0.0, 1.0)]);
+    }
+}
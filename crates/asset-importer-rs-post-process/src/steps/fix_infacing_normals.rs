@@ -1,11 +1,93 @@
+use std::collections::HashMap;
+
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{AiAABB, AiMesh, AiReal, AiScene, AiVector3D};
 use enumflags2::BitFlags;
 
 /// Fix infacing normals
+///
+/// Detects meshes whose normals point inward and flips their normals and winding order. The
+/// bounding box of the vertices is compared with the bounding box of the vertices moved along
+/// their normals, which is smaller if most normals point inward.
+///
+/// Only closed meshes are checked, where every edge is shared by exactly two faces. Open and
+/// planar meshes have no inside, so their normals are left as they are.
 #[derive(Default)]
 pub struct FixInfacingNormals;
 
+impl FixInfacingNormals {
+    /// Returns whether every edge of the mesh is shared by exactly two faces.
+    ///
+    /// Vertices are compared by position, so seams splitting vertices do not open the mesh.
+    fn is_closed(mesh: &AiMesh) -> bool {
+        let mut positions: HashMap<[u64; 3], usize> = HashMap::new();
+        let welded: Vec<usize> = mesh
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let key = [vertex.x, vertex.y, vertex.z].map(|value| u64::from(value.to_bits()));
+                let next = positions.len();
+                *positions.entry(key).or_insert(next)
+            })
+            .collect();
+
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for face in mesh.faces.iter() {
+            if face.len() < 3 {
+                return false;
+            }
+            for (index, &start) in face.iter().enumerate() {
+                let end = face[(index + 1) % face.len()];
+                let (Some(&start), Some(&end)) = (welded.get(start), welded.get(end)) else {
+                    return false;
+                };
+                *edges.entry((start.min(end), start.max(end))).or_default() += 1;
+            }
+        }
+        !edges.is_empty() && edges.values().all(|&count| count == 2)
+    }
+
+    /// Returns whether the normals of the mesh point inward.
+    fn is_infacing(mesh: &AiMesh) -> bool {
+        if mesh.normals.len() != mesh.vertices.len() || !Self::is_closed(mesh) {
+            return false;
+        }
+        let size = AiAABB::from_points(&mesh.vertices).size();
+        let offset: Vec<AiVector3D> = mesh
+            .vertices
+            .iter()
+            .zip(mesh.normals.iter())
+            .map(|(vertex, normal)| *vertex + *normal)
+            .collect();
+        let offset_size = AiAABB::from_points(&offset).size();
+
+        // Planar meshes have no inside
+        let is_flat = |a: AiReal, b: AiReal, c: AiReal| a < 0.05 * (b * c).sqrt();
+        if is_flat(offset_size.x, offset_size.y, offset_size.z)
+            || is_flat(offset_size.y, offset_size.z, offset_size.x)
+            || is_flat(offset_size.z, offset_size.x, offset_size.y)
+        {
+            return false;
+        }
+        (size.x * size.y * size.z).abs() > (offset_size.x * offset_size.y * offset_size.z).abs()
+    }
+
+    /// Flips the normals and winding order of a mesh.
+    fn flip(mesh: &mut AiMesh) {
+        for normal in mesh.normals.iter_mut() {
+            *normal = -*normal;
+        }
+        for anim_mesh in mesh.anim_meshes.iter_mut() {
+            for normal in anim_mesh.normals.iter_mut() {
+                *normal = -*normal;
+            }
+        }
+        for face in mesh.faces.iter_mut() {
+            face.reverse();
+        }
+    }
+}
+
 impl AiPostProcess for FixInfacingNormals {
     type Error = String;
 
@@ -14,7 +96,101 @@ impl AiPostProcess for FixInfacingNormals {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement infacing normal fixing
+        let mut fixed = 0;
+        for mesh in scene.meshes.iter_mut() {
+            if Self::is_infacing(mesh) {
+                log::debug!("Mesh {}: normals are facing inwards", mesh.name);
+                Self::flip(mesh);
+                fixed += 1;
+            }
+        }
+        if fixed > 0 {
+            log::info!(
+                "FixInfacingNormals finished. Flipped the normals of {} meshes",
+                fixed
+            );
+        } else {
+            log::debug!("FixInfacingNormals finished. No changes to the scene");
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::AiFace;
+
+    /// A cube around the origin with normals pointing away from its center.
+    fn create_cube() -> AiMesh {
+        let vertices: Vec<AiVector3D> = (0..8)
+            .map(|corner| {
+                let coordinate = |bit: usize| if corner & bit != 0 { 1.0 } else { -1.0 };
+                AiVector3D::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect();
+        let normals = vertices.iter().map(|vertex| vertex.norm()).collect();
+        let quads: [[usize; 4]; 6] = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let faces: Vec<AiFace> = quads
+            .iter()
+            .flat_map(|quad| {
+                [
+                    vec![quad[0], quad[1], quad[2]],
+                    vec![quad[0], quad[2], quad[3]],
+                ]
+            })
+            .collect();
+        AiMesh {
+            vertices,
+            normals,
+            faces,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_infacing_normals_flipped() {
+        let mut mesh = create_cube();
+        FixInfacingNormals::flip(&mut mesh);
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        assert!(FixInfacingNormals.process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes[0], create_cube());
+    }
+
+    #[test]
+    fn test_outfacing_normals_kept() {
+        let mut scene = AiScene {
+            meshes: vec![create_cube()],
+            ..Default::default()
+        };
+        assert!(FixInfacingNormals.process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes[0], create_cube());
+    }
+
+    #[test]
+    fn test_open_mesh_kept() {
+        let mut mesh = create_cube();
+        mesh.faces.truncate(10);
+        FixInfacingNormals::flip(&mut mesh);
+        let expected = mesh.clone();
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        assert!(FixInfacingNormals.process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes[0], expected);
+    }
+}
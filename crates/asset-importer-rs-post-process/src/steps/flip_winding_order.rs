@@ -3,6 +3,9 @@ use asset_importer_rs_scene::AiScene;
 use enumflags2::BitFlags;
 
 /// Flip winding order
+///
+/// Reverses the index order of every face, turning counter-clockwise faces into clockwise faces
+/// and vice versa. Lines are reversed as well, points are unaffected.
 #[derive(Default)]
 pub struct FlipWindingOrder;
 
@@ -14,7 +17,35 @@ impl AiPostProcess for FlipWindingOrder {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement winding order flipping
+        for mesh in scene.meshes.iter_mut() {
+            for face in mesh.faces.iter_mut() {
+                face.reverse();
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::AiMesh;
+
+    #[test]
+    fn test_flip_winding_order() {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                faces: vec![vec![0, 1, 2], vec![0, 2, 3, 4], vec![5]],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut step = FlipWindingOrder;
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::FlipWindingOrder)));
+        step.process(&mut scene).unwrap();
+        assert_eq!(
+            scene.meshes[0].faces,
+            vec![vec![2, 1, 0], vec![4, 3, 2, 0], vec![5]]
+        );
+    }
+}
@@ -1,11 +1,136 @@
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{
+    AI_MATH_PI, AI_MATH_TWO_PI, AiAABB, AiMesh, AiReal, AiScene, AiTextureMapping, AiTextureType,
+    AiVector3D, matkey,
+};
 use enumflags2::BitFlags;
 
+use crate::helper::{free_uv_channel, set_texture_property};
+
+/// Orthonormal frame whose Y axis is the mapping axis.
+struct AxisFrame {
+    x: AiVector3D,
+    y: AiVector3D,
+    z: AiVector3D,
+}
+
+impl AxisFrame {
+    fn new(axis: AiVector3D) -> Self {
+        let reference = if axis.z.abs() < 0.9 {
+            AiVector3D::new(0.0, 0.0, 1.0)
+        } else {
+            AiVector3D::new(1.0, 0.0, 0.0)
+        };
+        let x = axis.cross(&reference).norm();
+        let z = x.cross(&axis);
+        Self { x, y: axis, z }
+    }
+
+    fn local(&self, vector: &AiVector3D) -> AiVector3D {
+        AiVector3D::new(vector * self.x, vector * self.y, vector * self.z)
+    }
+}
+
+/// Maps `value` from `[min, max]` to `[0, 1]`.
+fn unit_range(value: AiReal, min: AiReal, max: AiReal) -> AiReal {
+    if max > min {
+        (value - min) / (max - min)
+    } else {
+        0.0
+    }
+}
+
+/// Angle of a direction around the Y axis, mapped to `[0, 1]`.
+fn azimuth(direction: &AiVector3D) -> AiReal {
+    (direction.x.atan2(direction.z) + AI_MATH_PI) / AI_MATH_TWO_PI
+}
+
 /// Generate UV coordinates
+///
+/// Computes texture coordinates for textures whose `$tex.mapping` is a sphere, cylinder, box or
+/// plane projection around `$tex.mapaxis` (Y if missing). The coordinates are written into the
+/// first channel free in every mesh using the material, the texture's `$tex.uvwsrc` is pointed at
+/// that channel and its mapping becomes UV. Textures sharing a projection share the channel.
 #[derive(Default)]
 pub struct GenUVCoords;
 
+impl GenUVCoords {
+    fn compute(mapping: AiTextureMapping, axis: AiVector3D, mesh: &AiMesh) -> Vec<AiVector3D> {
+        let frame = AxisFrame::new(axis);
+        let center = AiAABB::from_points(&mesh.vertices).center();
+        let local: Vec<AiVector3D> = mesh
+            .vertices
+            .iter()
+            .map(|vertex| frame.local(&(vertex - &center)))
+            .collect();
+        let bounds = AiAABB::from_points(&local);
+        let (min, max) = (bounds.min, bounds.max);
+        match mapping {
+            AiTextureMapping::Sphere => local
+                .iter()
+                .map(|position| {
+                    let direction = position.norm();
+                    AiVector3D::new(
+                        azimuth(&direction),
+                        (direction.y.clamp(-1.0, 1.0).asin() + AI_MATH_PI / 2.0) / AI_MATH_PI,
+                        0.0,
+                    )
+                })
+                .collect(),
+            AiTextureMapping::Cylinder => local
+                .iter()
+                .map(|position| {
+                    AiVector3D::new(
+                        azimuth(&position.norm()),
+                        unit_range(position.y, min.y, max.y),
+                        0.0,
+                    )
+                })
+                .collect(),
+            AiTextureMapping::Plane => local
+                .iter()
+                .map(|position| {
+                    AiVector3D::new(
+                        unit_range(position.x, min.x, max.x),
+                        unit_range(position.z, min.z, max.z),
+                        0.0,
+                    )
+                })
+                .collect(),
+            AiTextureMapping::Box => local
+                .iter()
+                .enumerate()
+                .map(|(vertex, position)| {
+                    // Project onto the box face the vertex points at
+                    let direction = match mesh.normals.get(vertex) {
+                        Some(normal) => frame.local(normal),
+                        None => *position,
+                    };
+                    let (x, y, z) = (direction.x.abs(), direction.y.abs(), direction.z.abs());
+                    let (u, v) = if x >= y && x >= z {
+                        (
+                            unit_range(position.z, min.z, max.z),
+                            unit_range(position.y, min.y, max.y),
+                        )
+                    } else if y >= z {
+                        (
+                            unit_range(position.x, min.x, max.x),
+                            unit_range(position.z, min.z, max.z),
+                        )
+                    } else {
+                        (
+                            unit_range(position.x, min.x, max.x),
+                            unit_range(position.y, min.y, max.y),
+                        )
+                    };
+                    AiVector3D::new(u, v, 0.0)
+                })
+                .collect(),
+            AiTextureMapping::UV | AiTextureMapping::Other => Vec::new(),
+        }
+    }
+}
+
 impl AiPostProcess for GenUVCoords {
     type Error = String;
 
@@ -14,7 +139,193 @@ impl AiPostProcess for GenUVCoords {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement UV coordinate generation
+        for (material_index, material) in scene.materials.iter_mut().enumerate() {
+            let textures: Vec<(AiTextureType, u32, AiTextureMapping)> = material
+                .iter()
+                .filter(|property| property.key == matkey::_AI_MATKEY_MAPPING_BASE)
+                .filter_map(|property| {
+                    let mapping = AiTextureMapping::try_from(*property.data.first()?).ok()?;
+                    Some((property.semantic, property.index, mapping))
+                })
+                .filter(|(_, _, mapping)| *mapping != AiTextureMapping::UV)
+                .collect();
+            if textures.is_empty() {
+                continue;
+            }
+            let mesh_indices: Vec<usize> = scene
+                .meshes
+                .iter()
+                .enumerate()
+                .filter(|(_, mesh)| mesh.material_index as usize == material_index)
+                .map(|(mesh_index, _)| mesh_index)
+                .collect();
+            if mesh_indices.is_empty() {
+                continue;
+            }
+
+            let mut generated: Vec<(AiTextureMapping, AiVector3D, usize)> = Vec::new();
+            for (semantic, index, mapping) in textures {
+                if mapping == AiTextureMapping::Other {
+                    log::warn!("Cannot generate UVs for {} texture {}", semantic, index);
+                    continue;
+                }
+                let axis = material
+                    .get_property_as::<AiVector3D>(
+                        matkey::_AI_MATKEY_TEXMAP_AXIS_BASE,
+                        Some(semantic),
+                        index,
+                    )
+                    .filter(|axis| axis.square_length() > 0.0)
+                    .map(AiVector3D::norm)
+                    .unwrap_or(AiVector3D::new(0.0, 1.0, 0.0));
+
+                let channel = match generated.iter().find(|(other_mapping, other_axis, _)| {
+                    *other_mapping == mapping && *other_axis == axis
+                }) {
+                    Some((_, _, channel)) => *channel,
+                    None => {
+                        let Some(channel) = free_uv_channel(&scene.meshes, &mesh_indices) else {
+                            log::warn!(
+                                "No free UV channel to generate UVs for {} texture {}",
+                                semantic,
+                                index
+                            );
+                            continue;
+                        };
+                        for &mesh_index in &mesh_indices {
+                            let mesh = &mut scene.meshes[mesh_index];
+                            mesh.texture_coords[channel] = Some(Self::compute(mapping, axis, mesh));
+                        }
+                        generated.push((mapping, axis, channel));
+                        channel
+                    }
+                };
+                log::debug!(
+                    "Generated {:?} UVs for {} texture {} in channel {}",
+                    mapping,
+                    semantic,
+                    index,
+                    channel
+                );
+                set_texture_property(
+                    material,
+                    matkey::_AI_MATKEY_UVWSRC_BASE,
+                    semantic,
+                    index,
+                    (channel as u32).to_le_bytes().to_vec(),
+                );
+                set_texture_property(
+                    material,
+                    matkey::_AI_MATKEY_MAPPING_BASE,
+                    semantic,
+                    index,
+                    vec![AiTextureMapping::UV.into()],
+                );
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiMaterial, AiPropertyTypeInfo};
+
+    fn create_scene(mapping: AiTextureMapping) -> AiScene {
+        let mut material = AiMaterial::new();
+        material.add_property(
+            matkey::_AI_MATKEY_MAPPING_BASE,
+            Some(AiTextureType::Diffuse),
+            AiPropertyTypeInfo::Binary,
+            0,
+            vec![mapping.into()],
+        );
+        let mesh = AiMesh {
+            vertices: vec![
+                AiVector3D::new(-1.0, 0.0, -1.0),
+                AiVector3D::new(1.0, 0.0, -1.0),
+                AiVector3D::new(1.0, 0.0, 1.0),
+                AiVector3D::new(-1.0, 0.0, 1.0),
+            ],
+            faces: vec![vec![0, 1, 2], vec![0, 2, 3]],
+            texture_coords: [
+                Some(vec![AiVector3D::zero(); 4]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ],
+            ..Default::default()
+        };
+        AiScene {
+            meshes: vec![mesh],
+            materials: vec![material],
+            ..Default::default()
+        }
+    }
+
+    fn uv_source(scene: &AiScene) -> Option<u32> {
+        scene.materials[0].get_property_as::<u32>(
+            matkey::_AI_MATKEY_UVWSRC_BASE,
+            Some(AiTextureType::Diffuse),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_plane_mapping() {
+        let mut scene = create_scene(AiTextureMapping::Plane);
+        assert!(GenUVCoords.process(&mut scene).is_ok());
+
+        assert_eq!(uv_source(&scene), Some(1));
+        assert_eq!(
+            scene.materials[0].get_property_byte(
+                matkey::_AI_MATKEY_MAPPING_BASE,
+                Some(AiTextureType::Diffuse),
+                0
+            ),
+            Some(AiTextureMapping::UV.into())
+        );
+        assert_eq!(
+            scene.meshes[0].texture_coords[1],
+            Some(vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 1.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_sphere_mapping() {
+        let mut scene = create_scene(AiTextureMapping::Sphere);
+        scene.meshes[0].vertices = vec![
+            AiVector3D::new(0.0, 1.0, 0.0),
+            AiVector3D::new(0.0, -1.0, 0.0),
+            AiVector3D::new(1.0, 0.0, 0.0),
+            AiVector3D::new(-1.0, 0.0, 0.0),
+        ];
+        assert!(GenUVCoords.process(&mut scene).is_ok());
+
+        let uvs = scene.meshes[0].texture_coords[1].as_ref().unwrap();
+        assert!((uvs[0].y - 1.0).abs() < 1e-5);
+        assert!(uvs[1].y.abs() < 1e-5);
+        assert!((uvs[2].y - 0.5).abs() < 1e-5);
+        assert!((uvs[2].x - 0.75).abs() < 1e-5);
+        assert!((uvs[3].x - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_uv_mapping_untouched() {
+        let mut scene = create_scene(AiTextureMapping::UV);
+        assert!(GenUVCoords.process(&mut scene).is_ok());
+
+        assert_eq!(uv_source(&scene), None);
+        assert!(scene.meshes[0].texture_coords[1].is_none());
+    }
+}
@@ -1,10 +1,122 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_GS_CONVERT_UNITS_DEFAULT, AI_CONFIG_PP_GS_SCALE_FACTOR_DEFAULT,
+    AI_METADATA_UNIT_METER, AI_METADATA_UNIT_SCALE_FACTOR, AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{
+    AiAABB, AiMatrix4x4, AiMetadata, AiMetadataEntry, AiReal, AiScene, ai_real_to_f32,
+};
 use enumflags2::BitFlags;
 
+/// Reads a numeric metadata entry.
+fn metadata_real(metadata: &AiMetadata, key: &str) -> Option<AiReal> {
+    match metadata.get(key)? {
+        AiMetadataEntry::AiF32(value) => Some(*value as AiReal),
+        AiMetadataEntry::AiF64(value) => Some(*value as AiReal),
+        AiMetadataEntry::AiI32(value) => Some(*value as AiReal),
+        AiMetadataEntry::AiU32(value) => Some(*value as AiReal),
+        AiMetadataEntry::AiI64(value) => Some(*value as AiReal),
+        AiMetadataEntry::AiU64(value) => Some(*value as AiReal),
+        _ => None,
+    }
+    .filter(|value| *value > 0.0 && value.is_finite())
+}
+
+/// Scales the translation of a matrix, conjugating it by a uniform scaling.
+fn scale_matrix(matrix: &mut AiMatrix4x4, scale: AiReal) {
+    matrix.a4 *= scale;
+    matrix.b4 *= scale;
+    matrix.c4 *= scale;
+    matrix.d1 /= scale;
+    matrix.d2 /= scale;
+    matrix.d3 /= scale;
+}
+
 /// Apply global scale
-#[derive(Default)]
-pub struct GlobalScale;
+///
+/// Scales the scene by `scale`. If `convert_units` is set, the scene is first converted from
+/// the unit stored in the `UnitMeter` or `UnitScaleFactor` scene metadata to meters, and the
+/// unit metadata is removed.
+///
+/// The scale is baked into the scene rather than added to the root node, so it reaches vertices,
+/// node and bone translations, animation position keys, bounding boxes, light and camera
+/// positions, camera clipping planes and light attenuation. Light falloffs other than the legacy
+/// attenuation coefficients are physical and left as they are, only their range is scaled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalScale {
+    pub scale: AiReal,
+    pub convert_units: bool,
+}
+
+impl Default for GlobalScale {
+    fn default() -> Self {
+        Self {
+            scale: AI_CONFIG_PP_GS_SCALE_FACTOR_DEFAULT,
+            convert_units: AI_CONFIG_PP_GS_CONVERT_UNITS_DEFAULT,
+        }
+    }
+}
+
+impl GlobalScale {
+    /// Returns the size of a scene unit in meters, if the scene metadata describes it.
+    pub fn unit_in_meters(metadata: &AiMetadata) -> Option<AiReal> {
+        metadata_real(metadata, AI_METADATA_UNIT_METER)
+            .or_else(|| metadata_real(metadata, AI_METADATA_UNIT_SCALE_FACTOR).map(|cm| cm / 100.0))
+    }
+
+    fn scale_scene(scene: &mut AiScene, scale: AiReal) {
+        for node in scene.nodes.arena.iter_mut() {
+            scale_matrix(&mut node.transformation, scale);
+        }
+        for mesh in scene.meshes.iter_mut() {
+            for vertex in mesh.vertices.iter_mut() {
+                *vertex = *vertex * scale;
+            }
+            for anim_mesh in mesh.anim_meshes.iter_mut() {
+                for vertex in anim_mesh.vertices.iter_mut() {
+                    *vertex = *vertex * scale;
+                }
+            }
+            for bone in mesh.bones.iter_mut() {
+                scale_matrix(&mut bone.offset_matrix, scale);
+            }
+            if mesh.aabb != AiAABB::default() {
+                mesh.aabb = AiAABB::new(mesh.aabb.min * scale, mesh.aabb.max * scale);
+            }
+        }
+        for skeleton in scene.skeletons.iter_mut() {
+            for bone in skeleton.bones_mut() {
+                let mut offset_matrix = bone.offset_matrix().clone();
+                scale_matrix(&mut offset_matrix, scale);
+                bone.set_offset_matrix(offset_matrix);
+                let mut local_matrix = bone.local_matrix().clone();
+                scale_matrix(&mut local_matrix, scale);
+                bone.set_local_matrix(local_matrix);
+            }
+        }
+        for animation in scene.animations.iter_mut() {
+            for channel in animation.channels.iter_mut() {
+                for key in channel.position_keys.iter_mut() {
+                    key.value = key.value * scale;
+                }
+            }
+        }
+
+        let distance_scale = ai_real_to_f32(scale);
+        for light in scene.lights.iter_mut() {
+            light.position = light.position * scale;
+            light.size = light.size * scale;
+            light.attenuation_linear /= distance_scale;
+            light.attenuation_quadratic /= distance_scale * distance_scale;
+            light.range = light.range.map(|range| range * distance_scale);
+        }
+        for camera in scene.cameras.iter_mut() {
+            camera.position = camera.position * scale;
+            camera.near_plane *= distance_scale;
+            camera.far_plane *= distance_scale;
+            camera.orthographic_width *= distance_scale;
+        }
+    }
+}
 
 impl AiPostProcess for GlobalScale {
     type Error = String;
@@ -14,7 +126,139 @@ impl AiPostProcess for GlobalScale {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement global scaling
+        if !(self.scale > 0.0 && self.scale.is_finite()) {
+            return Err(format!("Invalid global scale factor {}", self.scale));
+        }
+        let mut scale = self.scale;
+        if self.convert_units {
+            if let Some(unit) = Self::unit_in_meters(&scene.metadata) {
+                log::debug!("Converting scene units of {} m to meters", unit);
+                scale *= unit;
+            }
+            scene.metadata.remove(AI_METADATA_UNIT_METER);
+            scene.metadata.remove(AI_METADATA_UNIT_SCALE_FACTOR);
+        }
+        if scale == 1.0 {
+            return Ok(());
+        }
+        Self::scale_scene(scene, scale);
+        log::info!("GlobalScale finished. Scaled the scene by {}", scale);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimation, AiBone, AiCamera, AiLight, AiLightFalloff, AiMesh, AiNode, AiNodeAnim,
+        AiVector3D, AiVectorKey,
+    };
+
+    fn create_scene() -> AiScene {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                vertices: vec![AiVector3D::new(1.0, 2.0, 3.0)],
+                bones: vec![AiBone {
+                    offset_matrix: AiMatrix4x4::from_translation(AiVector3D::new(0.0, -10.0, 0.0)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            animations: vec![AiAnimation {
+                channels: vec![AiNodeAnim {
+                    node_name: "root".to_string(),
+                    position_keys: vec![AiVectorKey {
+                        value: AiVector3D::new(0.0, 0.0, 5.0),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            cameras: vec![AiCamera {
+                near_plane: 1.0,
+                far_plane: 1000.0,
+                ..Default::default()
+            }],
+            lights: vec![AiLight {
+                attenuation_quadratic: 1.0,
+                range: Some(100.0),
+                falloff: AiLightFalloff::Attenuation,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: "root".to_string(),
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(4.0, 0.0, 0.0)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        scene
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        let mut scene = create_scene();
+        let attenuation = scene.lights[0].attenuation_at(10.0);
+        let step = GlobalScale {
+            scale: 2.0,
+            ..Default::default()
+        };
+        assert!(step.process(&mut scene).is_ok());
+
+        assert_eq!(scene.meshes[0].vertices[0], AiVector3D::new(2.0, 4.0, 6.0));
+        assert_eq!(
+            scene.nodes.arena[0].transformation,
+            AiMatrix4x4::from_translation(AiVector3D::new(8.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            scene.meshes[0].bones[0].offset_matrix,
+            AiMatrix4x4::from_translation(AiVector3D::new(0.0, -20.0, 0.0))
+        );
+        assert_eq!(
+            scene.animations[0].channels[0].position_keys[0].value,
+            AiVector3D::new(0.0, 0.0, 10.0)
+        );
+        assert_eq!(scene.cameras[0].near_plane, 2.0);
+        assert_eq!(scene.cameras[0].far_plane, 2000.0);
+        assert_eq!(scene.lights[0].attenuation_quadratic, 0.25);
+        assert_eq!(scene.lights[0].range, Some(200.0));
+        // The light falls off the same at the scaled distance
+        assert!((scene.lights[0].attenuation_at(20.0) - attenuation).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        let mut scene = create_scene();
+        scene.metadata.insert(
+            AI_METADATA_UNIT_SCALE_FACTOR.to_string(),
+            AiMetadataEntry::AiF64(1.0),
+        );
+        assert!(GlobalScale::default().process(&mut scene).is_ok());
+
+        let expected = AiVector3D::new(0.01, 0.02, 0.03);
+        assert!((scene.meshes[0].vertices[0] - expected).len() < 1e-6);
+        assert!(scene.metadata.is_empty());
+
+        // Units were converted already
+        assert!(GlobalScale::default().process(&mut scene).is_ok());
+        assert!((scene.meshes[0].vertices[0] - expected).len() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_scale() {
+        let mut scene = create_scene();
+        let step = GlobalScale {
+            scale: 0.0,
+            ..Default::default()
+        };
+        assert!(step.process(&mut scene).is_err());
+    }
+}
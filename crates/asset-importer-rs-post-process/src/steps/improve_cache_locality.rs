@@ -1,10 +1,187 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_ICL_PTCACHE_SIZE_DEFAULT, AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiFace, AiMesh, AiPrimitiveType, AiScene, AiSkeleton};
 use enumflags2::BitFlags;
 
+use crate::helper::reorder_vertices;
+
 /// Improve cache locality
-#[derive(Default)]
-pub struct ImproveCacheLocality;
+///
+/// Reorders the triangles of a mesh for the post-transform vertex cache using Tipsify
+/// (Sander, Nehab and Barczak, "Fast Triangle Reordering for Vertex Locality and Reduced
+/// Overdraw"), then reorders the vertices in the order the triangles first use them, so they
+/// are fetched sequentially. Only meshes made of triangles are reordered, run `Triangulate`
+/// and `SortByPType` first. Reordering splits up the triangles of encoded polygons, so
+/// `AiPrimitiveType::NgonEncodingFlag` is cleared from reordered meshes.
+///
+/// The average cache miss ratio (ACMR) of a FIFO cache with `cache_size` entries is logged
+/// before and after the reordering, it ranges from 0.5 for an ideal ordering of a large regular
+/// grid to 3 for an ordering without any reuse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImproveCacheLocality {
+    pub cache_size: usize,
+}
+
+impl Default for ImproveCacheLocality {
+    fn default() -> Self {
+        Self {
+            cache_size: AI_CONFIG_PP_ICL_PTCACHE_SIZE_DEFAULT,
+        }
+    }
+}
+
+impl ImproveCacheLocality {
+    /// Computes the average number of vertex cache misses per face of a FIFO cache with
+    /// `cache_size` entries.
+    pub fn acmr(&self, faces: &[AiFace]) -> f32 {
+        if faces.is_empty() {
+            return 0.0;
+        }
+        let mut cache: Vec<usize> = Vec::with_capacity(self.cache_size + 1);
+        let mut misses = 0;
+        for &index in faces.iter().flatten() {
+            if cache.contains(&index) {
+                continue;
+            }
+            misses += 1;
+            cache.push(index);
+            if cache.len() > self.cache_size {
+                cache.remove(0);
+            }
+        }
+        misses as f32 / faces.len() as f32
+    }
+
+    /// Returns the order in which Tipsify emits the faces.
+    fn tipsify(&self, faces: &[AiFace], vertex_count: usize) -> Vec<usize> {
+        // Faces using each vertex, stored as offsets into a single list
+        let mut offsets = vec![0; vertex_count + 1];
+        for &index in faces.iter().flatten() {
+            offsets[index + 1] += 1;
+        }
+        for index in 0..vertex_count {
+            offsets[index + 1] += offsets[index];
+        }
+        let mut live: Vec<usize> = (0..vertex_count)
+            .map(|index| offsets[index + 1] - offsets[index])
+            .collect();
+        let mut adjacency = vec![0; offsets[vertex_count]];
+        let mut fill = offsets.clone();
+        for (face_index, face) in faces.iter().enumerate() {
+            for &index in face {
+                adjacency[fill[index]] = face_index;
+                fill[index] += 1;
+            }
+        }
+
+        let cache_size = self.cache_size;
+        let mut cache_time = vec![0; vertex_count];
+        let mut time_stamp = cache_size + 1;
+        let mut emitted = vec![false; faces.len()];
+        let mut dead_end: Vec<usize> = Vec::new();
+        let mut cursor = 0;
+        let mut order = Vec::with_capacity(faces.len());
+
+        let mut fanning = faces.first().and_then(|face| face.first().copied());
+        while let Some(vertex) = fanning {
+            let mut candidates = Vec::new();
+            for &face_index in &adjacency[offsets[vertex]..offsets[vertex + 1]] {
+                if emitted[face_index] {
+                    continue;
+                }
+                emitted[face_index] = true;
+                order.push(face_index);
+                for &index in &faces[face_index] {
+                    dead_end.push(index);
+                    candidates.push(index);
+                    live[index] -= 1;
+                    if time_stamp - cache_time[index] > cache_size {
+                        cache_time[index] = time_stamp;
+                        time_stamp += 1;
+                    }
+                }
+            }
+
+            // Prefer the candidate that stays in the cache while its faces are emitted, and
+            // among those the one that entered the cache first
+            let mut best: Option<(usize, usize)> = None;
+            for &index in &candidates {
+                if live[index] == 0 {
+                    continue;
+                }
+                let age = time_stamp - cache_time[index];
+                let priority = if age + 2 * live[index] <= cache_size {
+                    age
+                } else {
+                    0
+                };
+                if best.is_none_or(|(_, best_priority)| priority > best_priority) {
+                    best = Some((index, priority));
+                }
+            }
+            fanning = best.map(|(index, _)| index).or_else(|| {
+                while let Some(index) = dead_end.pop() {
+                    if live[index] > 0 {
+                        return Some(index);
+                    }
+                }
+                while cursor < vertex_count {
+                    if live[cursor] > 0 {
+                        return Some(cursor);
+                    }
+                    cursor += 1;
+                }
+                None
+            });
+        }
+        order
+    }
+
+    /// Reorders the faces and vertices of a mesh, returning the ACMR before and after.
+    fn process_mesh(
+        &self,
+        mesh: &mut AiMesh,
+        mesh_index: usize,
+        skeletons: &mut [AiSkeleton],
+    ) -> Option<(f32, f32)> {
+        let vertex_count = mesh.vertices.len();
+        if mesh.faces.is_empty()
+            || !mesh
+                .faces
+                .iter()
+                .all(|face| face.len() == 3 && face.iter().all(|&index| index < vertex_count))
+        {
+            return None;
+        }
+
+        let before = self.acmr(&mesh.faces);
+        let order = self.tipsify(&mesh.faces, vertex_count);
+        let faces: Vec<AiFace> = order
+            .iter()
+            .map(|&index| mesh.faces[index].clone())
+            .collect();
+        let after = self.acmr(&faces);
+        if after < before {
+            mesh.faces = faces;
+            mesh.primitive_types
+                .remove(AiPrimitiveType::NgonEncodingFlag);
+        }
+
+        // Vertices in order of first use, unused vertices are moved to the end
+        let mut used = vec![false; vertex_count];
+        let mut vertex_order = Vec::with_capacity(vertex_count);
+        for &index in mesh.faces.iter().flatten() {
+            if !used[index] {
+                used[index] = true;
+                vertex_order.push(index);
+            }
+        }
+        vertex_order.extend((0..vertex_count).filter(|&index| !used[index]));
+        reorder_vertices(mesh, mesh_index, skeletons, &vertex_order);
+        Some((before, after.min(before)))
+    }
+}
 
 impl AiPostProcess for ImproveCacheLocality {
     type Error = String;
@@ -14,7 +191,194 @@ impl AiPostProcess for ImproveCacheLocality {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement cache locality improvement
+        let mut total_faces = 0;
+        let (mut total_before, mut total_after) = (0.0, 0.0);
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            let mut primitive_types = mesh.primitive_types;
+            primitive_types.remove(AiPrimitiveType::NgonEncodingFlag);
+            if primitive_types != AiPrimitiveType::Triangle {
+                log::debug!(
+                    "Mesh {} is not made of triangles, its cache locality is not improved",
+                    mesh.name
+                );
+                continue;
+            }
+            let Some((before, after)) = self.process_mesh(mesh, mesh_index, &mut scene.skeletons)
+            else {
+                continue;
+            };
+            log::debug!("Mesh {}: ACMR {:.3} -> {:.3}", mesh.name, before, after);
+            let faces = mesh.faces.len() as f32;
+            total_faces += mesh.faces.len();
+            total_before += before * faces;
+            total_after += after * faces;
+        }
+        if total_faces > 0 {
+            log::info!(
+                "ACMR of {} faces: {:.3} -> {:.3}",
+                total_faces,
+                total_before / total_faces as f32,
+                total_after / total_faces as f32
+            );
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiBone, AiReal, AiVector3D, AiVertexWeight};
+
+    /// Creates a grid of `size` by `size` quads whose triangles are in a scattered order.
+    fn create_grid(size: usize) -> AiMesh {
+        let row = size + 1;
+        let mut mesh = AiMesh {
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            ..Default::default()
+        };
+        for y in 0..row {
+            for x in 0..row {
+                mesh.vertices
+                    .push(AiVector3D::new(x as AiReal, y as AiReal, 0.0));
+            }
+        }
+        for y in 0..size {
+            for x in 0..size {
+                let corner = y * row + x;
+                mesh.faces.push(vec![corner, corner + 1, corner + row + 1]);
+                mesh.faces
+                    .push(vec![corner, corner + row + 1, corner + row]);
+            }
+        }
+        // Interleave both halves of the grid to destroy the original locality
+        let half = mesh.faces.len() / 2;
+        let (first, second) = mesh.faces.split_at(half);
+        mesh.faces = first
+            .iter()
+            .zip(second.iter().rev())
+            .flat_map(|(a, b)| [a.clone(), b.clone()])
+            .collect();
+        mesh
+    }
+
+    #[test]
+    fn test_improve_cache_locality() {
+        let mut mesh = create_grid(16);
+        mesh.normals = mesh.vertices.clone();
+        mesh.bones.push(AiBone {
+            weights: vec![AiVertexWeight::new(20, 1.0)],
+            ..Default::default()
+        });
+        let original = mesh.clone();
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        let mut step = ImproveCacheLocality::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::ImproveCacheLocality)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert!(step.acmr(&mesh.faces) < step.acmr(&original.faces));
+        assert!(step.acmr(&mesh.faces) < 1.0);
+        assert_eq!(mesh.faces.len(), original.faces.len());
+        assert_eq!(mesh.normals, mesh.vertices);
+
+        // Every face still covers the same positions
+        let positions = |mesh: &AiMesh, face: &AiFace| {
+            let mut positions: Vec<[u32; 3]> = face
+                .iter()
+                .map(|&index| {
+                    let vertex = mesh.vertices[index];
+                    [vertex.x as u32, vertex.y as u32, vertex.z as u32]
+                })
+                .collect();
+            positions.sort();
+            positions
+        };
+        let mut original_faces: Vec<_> = original
+            .faces
+            .iter()
+            .map(|face| positions(&original, face))
+            .collect();
+        let mut faces: Vec<_> = mesh
+            .faces
+            .iter()
+            .map(|face| positions(mesh, face))
+            .collect();
+        original_faces.sort();
+        faces.sort();
+        assert_eq!(faces, original_faces);
+
+        // Vertices are ordered by first use
+        assert_eq!(mesh.faces[0], vec![0, 1, 2]);
+        let weight = &mesh.bones[0].weights[0];
+        assert_eq!(mesh.vertices[weight.vertex_id], original.vertices[20]);
+    }
+
+    #[test]
+    fn test_improve_cache_locality_skips_polygons() {
+        let mut mesh = create_grid(2);
+        mesh.faces.push(vec![0, 1, 4, 3]);
+        mesh.primitive_types |= AiPrimitiveType::Polygon;
+        let original = mesh.clone();
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        ImproveCacheLocality::default().process(&mut scene).unwrap();
+        assert_eq!(scene.meshes[0], original);
+    }
+
+    #[cfg(feature = "triangulate")]
+    #[test]
+    fn test_improve_cache_locality_after_triangulate() {
+        use crate::steps::Triangulate;
+
+        // Quads of a grid in a scattered order
+        let (size, row) = (8, 9);
+        let mut mesh = AiMesh {
+            primitive_types: BitFlags::from(AiPrimitiveType::Polygon),
+            ..Default::default()
+        };
+        for y in 0..row {
+            for x in 0..row {
+                mesh.vertices
+                    .push(AiVector3D::new(x as AiReal, y as AiReal, 0.0));
+            }
+        }
+        for quad in 0..size * size {
+            let quad = quad * 37 % (size * size);
+            let corner = quad / size * row + quad % size;
+            mesh.faces
+                .push(vec![corner, corner + 1, corner + row + 1, corner + row]);
+        }
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        Triangulate::default().process(&mut scene).unwrap();
+        assert!(
+            scene.meshes[0]
+                .primitive_types
+                .contains(AiPrimitiveType::NgonEncodingFlag)
+        );
+        let triangulated = scene.meshes[0].clone();
+
+        let step = ImproveCacheLocality::default();
+        step.process(&mut scene).unwrap();
+        let mesh = &scene.meshes[0];
+        assert_ne!(mesh.faces, triangulated.faces);
+        assert!(step.acmr(&mesh.faces) < step.acmr(&triangulated.faces));
+        assert_eq!(mesh.primitive_types, AiPrimitiveType::Triangle);
+    }
+
+    #[test]
+    fn test_acmr() {
+        let step = ImproveCacheLocality { cache_size: 3 };
+        assert_eq!(step.acmr(&[vec![0, 1, 2], vec![2, 1, 3]]), 2.0);
+        assert_eq!(step.acmr(&[vec![0, 1, 2], vec![3, 4, 5]]), 3.0);
+        assert_eq!(step.acmr(&[]), 0.0);
+    }
+}
@@ -1,10 +1,260 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps, KdTree, SpatialLookup};
+use asset_importer_rs_scene::{
+    AiAnimMesh, AiColor4D, AiMesh, AiReal, AiScene, AiSceneFlag, AiSkeleton, AiVector3D,
+    AiVertexWeight,
+};
 use enumflags2::BitFlags;
 
+use crate::helper::EpsilonCompute;
+
 /// Join identical vertices in meshes
-#[derive(Default)]
-pub struct JoinIdenticalVertices;
+///
+/// Two vertices are joined only if their positions are within the mesh epsilon and every other
+/// attribute matches within `attribute_epsilon`: normals, tangents, bitangents, all color and
+/// texture coordinate channels, bone weights (including skeleton bones bound to the mesh) and
+/// the attributes of every anim mesh. Faces and vertex weights are remapped to the joined
+/// vertices and the scene is flagged with `AiSceneFlag::NonVerboseFormat`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JoinIdenticalVertices {
+    /// Position tolerance, computed from the extents of each mesh if `None`.
+    pub epsilon: Option<AiReal>,
+    /// Tolerance for all other attributes.
+    pub attribute_epsilon: AiReal,
+}
+
+impl Default for JoinIdenticalVertices {
+    fn default() -> Self {
+        Self {
+            epsilon: None,
+            attribute_epsilon: 1e-5,
+        }
+    }
+}
+
+/// Influences of every vertex as `(bone, weight)` pairs sorted by bone.
+///
+/// Skeleton bones are numbered after the mesh bones so both sources are compared.
+fn vertex_influences(
+    mesh: &AiMesh,
+    mesh_index: usize,
+    skeletons: &[AiSkeleton],
+) -> Vec<Vec<(usize, AiReal)>> {
+    let mut influences: Vec<Vec<(usize, AiReal)>> = vec![Vec::new(); mesh.vertices.len()];
+    let mut push_weights = |bone: usize, weights: &[AiVertexWeight]| {
+        for weight in weights {
+            if let Some(vertex) = influences.get_mut(weight.vertex_id) {
+                vertex.push((bone, weight.weight));
+            }
+        }
+    };
+    for (bone_index, bone) in mesh.bones.iter().enumerate() {
+        push_weights(bone_index, &bone.weights);
+    }
+    let mut bone_index = mesh.bones.len();
+    for skeleton in skeletons {
+        for bone in skeleton.bones() {
+            if bone.mesh_index() == Some(mesh_index) {
+                push_weights(bone_index, bone.weights());
+            }
+            bone_index += 1;
+        }
+    }
+    for vertex in influences.iter_mut() {
+        vertex.sort_by_key(|(bone, _)| *bone);
+    }
+    influences
+}
+
+fn vectors_equal(values: &[AiVector3D], lhs: usize, rhs: usize, epsilon: AiReal) -> bool {
+    match (values.get(lhs), values.get(rhs)) {
+        (Some(a), Some(b)) => (a - b).square_length() <= epsilon * epsilon,
+        (a, b) => a == b,
+    }
+}
+
+fn colors_equal(colors: &[AiColor4D], lhs: usize, rhs: usize, epsilon: AiReal) -> bool {
+    match (colors.get(lhs), colors.get(rhs)) {
+        (Some(a), Some(b)) => [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a]
+            .into_iter()
+            .all(|difference| AiReal::from(difference).abs() <= epsilon),
+        (a, b) => a == b,
+    }
+}
+
+impl JoinIdenticalVertices {
+    fn anim_meshes_equal(
+        &self,
+        anim_meshes: &[AiAnimMesh],
+        lhs: usize,
+        rhs: usize,
+        epsilon: AiReal,
+    ) -> bool {
+        let attribute_epsilon = self.attribute_epsilon;
+        anim_meshes.iter().all(|anim_mesh| {
+            vectors_equal(&anim_mesh.vertices, lhs, rhs, epsilon)
+                && vectors_equal(&anim_mesh.normals, lhs, rhs, attribute_epsilon)
+                && vectors_equal(&anim_mesh.tangents, lhs, rhs, attribute_epsilon)
+                && vectors_equal(&anim_mesh.bi_tangents, lhs, rhs, attribute_epsilon)
+                && anim_mesh
+                    .colors
+                    .iter()
+                    .flatten()
+                    .all(|colors| colors_equal(colors, lhs, rhs, attribute_epsilon))
+                && anim_mesh
+                    .texture_coords
+                    .iter()
+                    .flatten()
+                    .all(|coords| vectors_equal(coords, lhs, rhs, attribute_epsilon))
+        })
+    }
+
+    fn vertices_equal(
+        &self,
+        mesh: &AiMesh,
+        influences: &[Vec<(usize, AiReal)>],
+        lhs: usize,
+        rhs: usize,
+        epsilon: AiReal,
+    ) -> bool {
+        let attribute_epsilon = self.attribute_epsilon;
+        let influences_equal = influences[lhs].len() == influences[rhs].len()
+            && influences[lhs].iter().zip(influences[rhs].iter()).all(
+                |((lhs_bone, lhs_weight), (rhs_bone, rhs_weight))| {
+                    lhs_bone == rhs_bone && (lhs_weight - rhs_weight).abs() <= attribute_epsilon
+                },
+            );
+
+        influences_equal
+            && vectors_equal(&mesh.normals, lhs, rhs, attribute_epsilon)
+            && vectors_equal(&mesh.tangents, lhs, rhs, attribute_epsilon)
+            && vectors_equal(&mesh.bi_tangents, lhs, rhs, attribute_epsilon)
+            && mesh
+                .colors
+                .iter()
+                .flatten()
+                .all(|colors| colors_equal(colors, lhs, rhs, attribute_epsilon))
+            && mesh
+                .texture_coords
+                .iter()
+                .flatten()
+                .all(|coords| vectors_equal(coords, lhs, rhs, attribute_epsilon))
+            && self.anim_meshes_equal(&mesh.anim_meshes, lhs, rhs, epsilon)
+    }
+
+    /// Joins the vertices of a mesh, returning the number of removed vertices.
+    fn process_mesh(
+        &self,
+        mesh: &mut AiMesh,
+        mesh_index: usize,
+        skeletons: &mut [AiSkeleton],
+    ) -> usize {
+        let vertex_count = mesh.vertices.len();
+        if vertex_count == 0 || mesh.faces.is_empty() {
+            return 0;
+        }
+
+        // The radius of the spatial lookup is exclusive, keep it positive so exact duplicates
+        // are found even with a zero epsilon or a mesh without extent
+        let epsilon = self
+            .epsilon
+            .unwrap_or_else(|| mesh.epsilon())
+            .max(AiReal::EPSILON);
+        let influences = vertex_influences(mesh, mesh_index, skeletons);
+        let tree = KdTree::new(&mesh.vertices);
+
+        // For every original vertex, its index in the joined vertex list
+        let mut remap: Vec<usize> = vec![usize::MAX; vertex_count];
+        // For every joined vertex, the original vertex it is copied from
+        let mut kept: Vec<usize> = Vec::with_capacity(vertex_count);
+        for index in 0..vertex_count {
+            let candidates = tree.find_position(mesh.vertices[index], epsilon);
+            let existing = candidates
+                .into_iter()
+                .filter(|&other| other < index)
+                .find(|&other| {
+                    kept[remap[other]] == other
+                        && self.vertices_equal(mesh, &influences, index, other, epsilon)
+                });
+            remap[index] = match existing {
+                Some(other) => remap[other],
+                None => {
+                    kept.push(index);
+                    kept.len() - 1
+                }
+            };
+        }
+
+        let removed = vertex_count - kept.len();
+        if removed == 0 {
+            return 0;
+        }
+
+        fn gather<T: Clone>(values: &mut Vec<T>, kept: &[usize]) {
+            if values.is_empty() {
+                return;
+            }
+            *values = kept
+                .iter()
+                .filter_map(|&index| values.get(index).cloned())
+                .collect();
+        }
+
+        gather(&mut mesh.vertices, &kept);
+        gather(&mut mesh.normals, &kept);
+        gather(&mut mesh.tangents, &kept);
+        gather(&mut mesh.bi_tangents, &kept);
+        for colors in mesh.colors.iter_mut().flatten() {
+            gather(colors, &kept);
+        }
+        for coords in mesh.texture_coords.iter_mut().flatten() {
+            gather(coords, &kept);
+        }
+        for anim_mesh in mesh.anim_meshes.iter_mut() {
+            gather(&mut anim_mesh.vertices, &kept);
+            gather(&mut anim_mesh.normals, &kept);
+            gather(&mut anim_mesh.tangents, &kept);
+            gather(&mut anim_mesh.bi_tangents, &kept);
+            for colors in anim_mesh.colors.iter_mut().flatten() {
+                gather(colors, &kept);
+            }
+            for coords in anim_mesh.texture_coords.iter_mut().flatten() {
+                gather(coords, &kept);
+            }
+        }
+
+        for face in mesh.faces.iter_mut() {
+            for index in face.iter_mut() {
+                if let Some(&new_index) = remap.get(*index) {
+                    *index = new_index;
+                }
+            }
+        }
+
+        // Joined vertices carry identical weights, so only the weights of kept vertices remain
+        let remap_weights = |weights: &mut Vec<AiVertexWeight>| {
+            weights.retain(|weight| {
+                remap
+                    .get(weight.vertex_id)
+                    .is_some_and(|&new_index| kept[new_index] == weight.vertex_id)
+            });
+            for weight in weights.iter_mut() {
+                weight.vertex_id = remap[weight.vertex_id];
+            }
+        };
+        for bone in mesh.bones.iter_mut() {
+            remap_weights(&mut bone.weights);
+        }
+        for skeleton in skeletons.iter_mut() {
+            for bone in skeleton.bones_mut() {
+                if bone.mesh_index() == Some(mesh_index) {
+                    remap_weights(bone.weights_mut());
+                }
+            }
+        }
+
+        removed
+    }
+}
 
 impl AiPostProcess for JoinIdenticalVertices {
     type Error = String;
@@ -13,8 +263,121 @@ impl AiPostProcess for JoinIdenticalVertices {
         steps.contains(AiPostProcessSteps::JoinIdenticalVertices)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement vertex joining
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            self.process_mesh(mesh, mesh_index, &mut scene.skeletons);
+        }
+        scene.flags |= AiSceneFlag::NonVerboseFormat;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiBone, AiPrimitiveType};
+
+    /// Two triangles forming a quad, stored as a triangle soup.
+    fn create_quad_soup() -> AiMesh {
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        AiMesh {
+            vertices: positions.iter().map(|&p| p.into()).collect(),
+            normals: vec![[0.0, 0.0, 1.0].into(); 6],
+            faces: vec![vec![0, 1, 2], vec![3, 4, 5]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_join_identical_vertices() {
+        let mut scene = AiScene {
+            meshes: vec![create_quad_soup()],
+            ..Default::default()
+        };
+        scene.meshes[0].bones.push(AiBone {
+            weights: vec![AiVertexWeight::new(3, 1.0), AiVertexWeight::new(4, 1.0)],
+            ..Default::default()
+        });
+        // Only vertices 0 and 3 agree on their weights, vertex 4 is weighted but vertex 2 is not
+        scene.meshes[0].bones[0]
+            .weights
+            .push(AiVertexWeight::new(0, 1.0));
+
+        let mut step = JoinIdenticalVertices::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::JoinIdenticalVertices)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.normals.len(), 5);
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2], vec![0, 3, 4]]);
+        let mut weights: Vec<usize> = mesh.bones[0]
+            .weights
+            .iter()
+            .map(|weight| weight.vertex_id)
+            .collect();
+        weights.sort_unstable();
+        assert_eq!(weights, vec![0, 3]);
+        assert!(scene.flags.contains(AiSceneFlag::NonVerboseFormat));
+    }
+
+    #[test]
+    fn test_join_identical_vertices_zero_epsilon() {
+        let mut scene = AiScene {
+            meshes: vec![create_quad_soup()],
+            ..Default::default()
+        };
+        let step = JoinIdenticalVertices {
+            epsilon: Some(0.0),
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+        assert_eq!(scene.meshes[0].vertices.len(), 4);
+
+        // Every vertex at the same position, the mesh epsilon is zero
+        let mut mesh = create_quad_soup();
+        mesh.vertices = vec![AiVector3D::new(1.0, 2.0, 3.0); 6];
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        JoinIdenticalVertices::default()
+            .process(&mut scene)
+            .unwrap();
+        assert_eq!(scene.meshes[0].vertices.len(), 1);
+        assert_eq!(scene.meshes[0].faces, vec![vec![0, 0, 0], vec![0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_join_identical_vertices_compares_attributes() {
+        let mut mesh = create_quad_soup();
+        mesh.normals[3] = [0.0, 1.0, 0.0].into();
+        mesh.texture_coords[0] = Some(vec![AiVector3D::zero(); 6]);
+        mesh.texture_coords[0].as_mut().unwrap()[4] = [0.5, 0.5, 0.0].into();
+        let mut anim_mesh = AiAnimMesh {
+            vertices: mesh.vertices.clone(),
+            ..Default::default()
+        };
+        anim_mesh.vertices[2].z = 1.0;
+        mesh.anim_meshes.push(anim_mesh);
+
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        JoinIdenticalVertices::default()
+            .process(&mut scene)
+            .unwrap();
+        // Normal, texture coordinate and anim mesh position differences keep all vertices
+        assert_eq!(scene.meshes[0].vertices.len(), 6);
+        assert_eq!(scene.meshes[0].anim_meshes[0].vertices.len(), 6);
+    }
+}
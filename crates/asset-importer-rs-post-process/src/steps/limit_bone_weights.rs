@@ -1,10 +1,81 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_LBW_MAX_WEIGHTS_DEFAULT, AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiReal, AiScene, AiVertexWeight};
 use enumflags2::BitFlags;
 
 /// Limit bone weights
-#[derive(Default)]
-pub struct LimitBoneWeights;
+///
+/// Keeps the `max_weights` most significant bone influences of every vertex and renormalizes
+/// the kept weights of the vertices that lost influences. Mesh bones left without weights are
+/// removed. Skeleton bones bound to a mesh are limited separately from its mesh bones, and are
+/// never removed since other bones may depend on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitBoneWeights {
+    pub max_weights: usize,
+}
+
+impl Default for LimitBoneWeights {
+    fn default() -> Self {
+        Self {
+            max_weights: AI_CONFIG_PP_LBW_MAX_WEIGHTS_DEFAULT,
+        }
+    }
+}
+
+impl LimitBoneWeights {
+    /// Limits the influences spread over `weights`, returning how many were removed.
+    fn limit(&self, weights: &mut [&mut Vec<AiVertexWeight>], vertex_count: usize) -> usize {
+        // Influences of every vertex as (list, position in list)
+        let mut influences: Vec<Vec<(usize, usize)>> = vec![Vec::new(); vertex_count];
+        for (list, list_weights) in weights.iter().enumerate() {
+            for (position, weight) in list_weights.iter().enumerate() {
+                if let Some(vertex) = influences.get_mut(weight.vertex_id) {
+                    vertex.push((list, position));
+                }
+            }
+        }
+
+        let mut removed: Vec<Vec<bool>> = weights
+            .iter()
+            .map(|list_weights| vec![false; list_weights.len()])
+            .collect();
+        let mut removed_count = 0;
+        for mut vertex in influences {
+            if vertex.len() <= self.max_weights {
+                continue;
+            }
+            vertex.sort_by(|&(lhs_list, lhs), &(rhs_list, rhs)| {
+                weights[rhs_list][rhs]
+                    .weight
+                    .total_cmp(&weights[lhs_list][lhs].weight)
+            });
+            for &(list, position) in &vertex[self.max_weights..] {
+                removed[list][position] = true;
+                removed_count += 1;
+            }
+            let kept = &vertex[..self.max_weights];
+            let sum: AiReal = kept
+                .iter()
+                .map(|&(list, position)| weights[list][position].weight)
+                .sum();
+            if sum > 0.0 {
+                for &(list, position) in kept {
+                    weights[list][position].weight /= sum;
+                }
+            }
+        }
+
+        for (list_weights, removed) in weights.iter_mut().zip(removed) {
+            let mut position = 0;
+            list_weights.retain(|_| {
+                position += 1;
+                !removed[position - 1]
+            });
+        }
+        removed_count
+    }
+}
 
 impl AiPostProcess for LimitBoneWeights {
     type Error = String;
@@ -14,7 +85,99 @@ impl AiPostProcess for LimitBoneWeights {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement bone weight limiting
+        for (mesh_index, mesh) in scene.meshes.iter_mut().enumerate() {
+            let vertex_count = mesh.vertices.len();
+            let mut weights: Vec<&mut Vec<AiVertexWeight>> = mesh
+                .bones
+                .iter_mut()
+                .map(|bone| &mut bone.weights)
+                .collect();
+            let mut removed = self.limit(&mut weights, vertex_count);
+            let bone_count = mesh.bones.len();
+            mesh.bones.retain(|bone| !bone.weights.is_empty());
+            if bone_count != mesh.bones.len() {
+                log::debug!(
+                    "Mesh {} lost {} bones without weights",
+                    mesh.name,
+                    bone_count - mesh.bones.len()
+                );
+            }
+
+            let mut weights: Vec<&mut Vec<AiVertexWeight>> = scene
+                .skeletons
+                .iter_mut()
+                .flat_map(|skeleton| skeleton.bones_mut())
+                .filter(|bone| bone.mesh_index() == Some(mesh_index))
+                .map(|bone| bone.weights_mut())
+                .collect();
+            removed += self.limit(&mut weights, vertex_count);
+            if removed > 0 {
+                log::debug!("Mesh {} lost {} bone weights", mesh.name, removed);
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiBone, AiMesh, AiVector3D};
+
+    fn bone(name: &str, weights: Vec<AiVertexWeight>) -> AiBone {
+        AiBone {
+            name: name.to_string(),
+            weights,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_limit_bone_weights() {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                vertices: vec![AiVector3D::zero(); 2],
+                bones: vec![
+                    bone(
+                        "A",
+                        vec![AiVertexWeight::new(0, 0.4), AiVertexWeight::new(1, 1.0)],
+                    ),
+                    bone("B", vec![AiVertexWeight::new(0, 0.1)]),
+                    bone("C", vec![AiVertexWeight::new(0, 0.3)]),
+                    bone("D", vec![AiVertexWeight::new(0, 0.2)]),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut step = LimitBoneWeights { max_weights: 2 };
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::LimitBoneWeights)));
+        step.process(&mut scene).unwrap();
+
+        let bones = &scene.meshes[0].bones;
+        let names: Vec<&str> = bones.iter().map(|bone| bone.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "C"]);
+        assert_eq!(bones[0].weights.len(), 2);
+        assert!((bones[0].weights[0].weight - 0.4 / 0.7).abs() < 1e-6);
+        assert_eq!(bones[0].weights[1].weight, 1.0);
+        assert!((bones[1].weights[0].weight - 0.3 / 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_limit_bone_weights_within_limit() {
+        let mesh = AiMesh {
+            vertices: vec![AiVector3D::zero()],
+            bones: vec![
+                bone("A", vec![AiVertexWeight::new(0, 0.25)]),
+                bone("B", vec![AiVertexWeight::new(0, 0.25)]),
+            ],
+            ..Default::default()
+        };
+        let mut scene = AiScene {
+            meshes: vec![mesh.clone()],
+            ..Default::default()
+        };
+        LimitBoneWeights::default().process(&mut scene).unwrap();
+        assert_eq!(scene.meshes[0], mesh);
+    }
+}
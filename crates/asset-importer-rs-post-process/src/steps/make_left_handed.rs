@@ -1,11 +1,55 @@
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{AiMatrix4x4, AiMesh, AiScene, AiVector3D};
 use enumflags2::BitFlags;
 
-/// Make meshes left-handed
+/// Make left handed
+///
+/// Converts the scene to a left handed coordinate system by mirroring the Z axis. Vertex data,
+/// node transforms, bone offset matrices, skeletons, animation keys, lights and cameras are all
+/// mirrored, so the scene looks the same in a left handed renderer. The winding order of faces
+/// is kept, combine with `FlipWindingOrder` if the renderer culls clockwise faces.
 #[derive(Default)]
 pub struct MakeLeftHanded;
 
+fn mirror_vector(vector: &mut AiVector3D) {
+    vector.z = -vector.z;
+}
+
+/// Conjugates a matrix with the Z mirror, `S * M * S` with `S = diag(1, 1, -1, 1)`.
+fn mirror_matrix(matrix: &mut AiMatrix4x4) {
+    matrix.a3 = -matrix.a3;
+    matrix.b3 = -matrix.b3;
+    matrix.d3 = -matrix.d3;
+    matrix.c1 = -matrix.c1;
+    matrix.c2 = -matrix.c2;
+    matrix.c4 = -matrix.c4;
+}
+
+fn mirror_mesh(mesh: &mut AiMesh) {
+    let vectors = mesh
+        .vertices
+        .iter_mut()
+        .chain(mesh.normals.iter_mut())
+        .chain(mesh.tangents.iter_mut())
+        .chain(mesh.bi_tangents.iter_mut());
+    vectors.for_each(mirror_vector);
+    for anim_mesh in mesh.anim_meshes.iter_mut() {
+        let vectors = anim_mesh
+            .vertices
+            .iter_mut()
+            .chain(anim_mesh.normals.iter_mut())
+            .chain(anim_mesh.tangents.iter_mut())
+            .chain(anim_mesh.bi_tangents.iter_mut());
+        vectors.for_each(mirror_vector);
+    }
+    for bone in mesh.bones.iter_mut() {
+        mirror_matrix(&mut bone.offset_matrix);
+    }
+    mirror_vector(&mut mesh.aabb.min);
+    mirror_vector(&mut mesh.aabb.max);
+    (mesh.aabb.min.z, mesh.aabb.max.z) = (mesh.aabb.max.z, mesh.aabb.min.z);
+}
+
 impl AiPostProcess for MakeLeftHanded {
     type Error = String;
 
@@ -14,7 +58,140 @@ impl AiPostProcess for MakeLeftHanded {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement left-handed conversion
+        for node in scene.nodes.arena.iter_mut() {
+            mirror_matrix(&mut node.transformation);
+        }
+        for mesh in scene.meshes.iter_mut() {
+            mirror_mesh(mesh);
+        }
+        for skeleton in scene.skeletons.iter_mut() {
+            for bone in skeleton.bones_mut() {
+                let mut offset_matrix = bone.offset_matrix().clone();
+                mirror_matrix(&mut offset_matrix);
+                bone.set_offset_matrix(offset_matrix);
+                let mut local_matrix = bone.local_matrix().clone();
+                mirror_matrix(&mut local_matrix);
+                bone.set_local_matrix(local_matrix);
+            }
+        }
+        for animation in scene.animations.iter_mut() {
+            for channel in animation.channels.iter_mut() {
+                for key in channel.position_keys.iter_mut() {
+                    mirror_vector(&mut key.value);
+                }
+                // Mirroring Z reverses the rotations about X and Y
+                for key in channel.rotation_keys.iter_mut() {
+                    key.value.x = -key.value.x;
+                    key.value.y = -key.value.y;
+                }
+            }
+        }
+        for light in scene.lights.iter_mut() {
+            mirror_vector(&mut light.position);
+            mirror_vector(&mut light.direction);
+            mirror_vector(&mut light.up);
+        }
+        for camera in scene.cameras.iter_mut() {
+            mirror_vector(&mut camera.position);
+            mirror_vector(&mut camera.look_vec);
+            mirror_vector(&mut camera.up_vec);
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimation, AiBone, AiCamera, AiNode, AiNodeAnim, AiQuatKey, AiQuaternion, AiVectorKey,
+    };
+
+    #[test]
+    fn test_mirror_matrix() {
+        let matrix = AiMatrix4x4::from([
+            1.0, 2.0, 3.0, 4.0, //
+            5.0, 6.0, 7.0, 8.0, //
+            9.0, 10.0, 11.0, 12.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+        let point = AiVector3D::new(0.25, -1.0, 2.0);
+        let mut expected = matrix.transform_point(&point);
+        mirror_vector(&mut expected);
+
+        let mut mirrored = matrix;
+        mirror_matrix(&mut mirrored);
+        let mut mirrored_point = point;
+        mirror_vector(&mut mirrored_point);
+        assert_eq!(mirrored.transform_point(&mirrored_point), expected);
+    }
+
+    #[test]
+    fn test_make_left_handed() {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                vertices: vec![AiVector3D::new(1.0, 2.0, 3.0)],
+                normals: vec![AiVector3D::new(0.0, 0.0, 1.0)],
+                bones: vec![AiBone {
+                    offset_matrix: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, 4.0)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            animations: vec![AiAnimation {
+                channels: vec![AiNodeAnim {
+                    position_keys: vec![AiVectorKey {
+                        value: AiVector3D::new(1.0, 1.0, 1.0),
+                        ..Default::default()
+                    }],
+                    rotation_keys: vec![AiQuatKey {
+                        value: AiQuaternion::new(0.5, 0.5, 0.5, 0.5),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            cameras: vec![AiCamera {
+                look_vec: AiVector3D::new(0.0, 0.0, 1.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, 2.0)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        let mut step = MakeLeftHanded;
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::MakeLeftHanded)));
+        step.process(&mut scene).unwrap();
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices[0], AiVector3D::new(1.0, 2.0, -3.0));
+        assert_eq!(mesh.normals[0], AiVector3D::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            mesh.bones[0].offset_matrix,
+            AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, -4.0))
+        );
+        assert_eq!(
+            scene.nodes.arena[0].transformation,
+            AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, -2.0))
+        );
+        let channel = &scene.animations[0].channels[0];
+        assert_eq!(
+            channel.position_keys[0].value,
+            AiVector3D::new(1.0, 1.0, -1.0)
+        );
+        assert_eq!(
+            channel.rotation_keys[0].value,
+            AiQuaternion::new(0.5, -0.5, -0.5, 0.5)
+        );
+        assert_eq!(scene.cameras[0].look_vec, AiVector3D::new(0.0, 0.0, -1.0));
+    }
+}
@@ -1,10 +1,119 @@
+use std::{collections::VecDeque, mem};
+
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
 use asset_importer_rs_scene::AiScene;
 use enumflags2::BitFlags;
 
+use crate::helper::{parse_name_list, transform_mesh};
+
 /// Optimize scene graph
-#[derive(Default)]
-pub struct OptimizeGraph;
+///
+/// Collapses nodes that have no meshes and are not referenced by animations, bones, lights,
+/// cameras or the `keep_list`, and that carry no metadata. Their transform is folded into their
+/// children. Sibling leaf nodes that are not referenced either are merged into one node, if they
+/// share their transform or if their meshes can be transformed into the space of that node.
+/// A mesh can be transformed if only one node references it and no bone deforms it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptimizeGraph {
+    /// Names of nodes that are neither collapsed nor merged, see
+    /// [`OptimizeGraph::parse_keep_list`].
+    pub keep_list: Vec<String>,
+}
+
+impl OptimizeGraph {
+    /// Parses a whitespace separated list of node names, names containing spaces are enclosed in
+    /// single quotes, e.g. `"Root 'Left Arm' Head"`.
+    pub fn parse_keep_list(list: &str) -> Vec<String> {
+        parse_name_list(list)
+    }
+
+    /// Flags the nodes that must survive the optimization.
+    fn locked_nodes(&self, scene: &AiScene) -> Vec<bool> {
+        let arena = &scene.nodes.arena;
+        let mut locked: Vec<bool> = arena
+            .iter()
+            .map(|node| node.metadata.is_some() || self.keep_list.contains(&node.name))
+            .collect();
+        let mut lock_name = |name: &str| {
+            if name.is_empty() {
+                return;
+            }
+            for (index, node) in arena.iter().enumerate() {
+                if node.name == name {
+                    locked[index] = true;
+                }
+            }
+        };
+        for animation in &scene.animations {
+            for channel in &animation.channels {
+                lock_name(&channel.node_name);
+            }
+        }
+        for light in &scene.lights {
+            lock_name(&light.name);
+        }
+        for camera in &scene.cameras {
+            lock_name(&camera.name);
+        }
+        for mesh in &scene.meshes {
+            for bone in &mesh.bones {
+                lock_name(&bone.name);
+            }
+        }
+
+        let mut lock_index = |index: usize| {
+            if let Some(locked) = locked.get_mut(index) {
+                *locked = true;
+            }
+        };
+        for mesh in &scene.meshes {
+            for bone in &mesh.bones {
+                lock_index(bone.node_index);
+                lock_index(bone.armature_index);
+            }
+        }
+        for skeleton in &scene.skeletons {
+            for bone in skeleton.bones() {
+                lock_index(bone.node_index());
+                if let Some(armature_index) = bone.armature_index() {
+                    lock_index(armature_index);
+                }
+            }
+        }
+        if let Some(root) = scene.nodes.root {
+            lock_index(root);
+        }
+        locked
+    }
+
+    /// Flags the meshes that can be transformed into the space of another node.
+    fn transformable_meshes(scene: &AiScene) -> Vec<bool> {
+        let mut references = vec![0; scene.meshes.len()];
+        for node in &scene.nodes.arena {
+            for &mesh_index in &node.mesh_indexes {
+                if let Some(count) = references.get_mut(mesh_index) {
+                    *count += 1;
+                }
+            }
+        }
+        let mut transformable: Vec<bool> = scene
+            .meshes
+            .iter()
+            .zip(references)
+            .map(|(mesh, references)| references == 1 && mesh.bones.is_empty())
+            .collect();
+        for skeleton in &scene.skeletons {
+            for bone in skeleton.bones() {
+                if let Some(mesh_index) = bone.mesh_index()
+                    && let Some(transformable) = transformable.get_mut(mesh_index)
+                {
+                    *transformable = false;
+                }
+            }
+        }
+        transformable
+    }
+}
 
 impl AiPostProcess for OptimizeGraph {
     type Error = String;
@@ -13,8 +122,303 @@ impl AiPostProcess for OptimizeGraph {
         steps.contains(AiPostProcessSteps::OptimizeGraph)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement graph optimization
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let Some(root) = scene.nodes.root else {
+            return Ok(());
+        };
+        let locked = self.locked_nodes(scene);
+        let transformable = Self::transformable_meshes(scene);
+        let node_count = scene.nodes.arena.len();
+        let arena = &mut scene.nodes.arena;
+        let meshes = &mut scene.meshes;
+
+        let mut removed = vec![false; node_count];
+        let mut visited = vec![false; node_count];
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            if index >= node_count || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+
+            // Collapse children, their children take their place
+            let mut pending: VecDeque<usize> = mem::take(&mut arena[index].children).into();
+            let mut children = Vec::with_capacity(pending.len());
+            while let Some(child) = pending.pop_front() {
+                if child >= node_count || visited[child] {
+                    continue;
+                }
+                if locked[child] || !arena[child].mesh_indexes.is_empty() {
+                    children.push(child);
+                    continue;
+                }
+                visited[child] = true;
+                removed[child] = true;
+                let transform = arena[child].transformation.clone();
+                let grandchildren = mem::take(&mut arena[child].children);
+                for &grandchild in grandchildren.iter().rev() {
+                    if let Some(node) = arena.get_mut(grandchild) {
+                        node.transformation = &transform * &node.transformation;
+                        node.parent = Some(index);
+                        pending.push_front(grandchild);
+                    }
+                }
+            }
+
+            // Merge sibling leaves into the first of them
+            let mut kept = Vec::with_capacity(children.len());
+            let mut target: Option<usize> = None;
+            for child in children {
+                if locked[child] || !arena[child].children.is_empty() {
+                    kept.push(child);
+                    continue;
+                }
+                let Some(target) = target else {
+                    target = Some(child);
+                    kept.push(child);
+                    continue;
+                };
+                if arena[child].transformation != arena[target].transformation {
+                    let relative = arena[target]
+                        .transformation
+                        .inverse()
+                        .map(|inverse| &inverse * &arena[child].transformation);
+                    let Some(relative) = relative.filter(|_| {
+                        arena[child]
+                            .mesh_indexes
+                            .iter()
+                            .all(|&mesh_index| transformable.get(mesh_index) == Some(&true))
+                    }) else {
+                        kept.push(child);
+                        continue;
+                    };
+                    for &mesh_index in &arena[child].mesh_indexes {
+                        transform_mesh(&mut meshes[mesh_index], &relative);
+                    }
+                }
+                let mesh_indexes = mem::take(&mut arena[child].mesh_indexes);
+                arena[target].mesh_indexes.extend(mesh_indexes);
+                removed[child] = true;
+            }
+            arena[index].children = kept.clone();
+            stack.extend(kept);
+        }
+
+        let removed_count = removed.iter().filter(|&&removed| removed).count();
+        if removed_count == 0 {
+            return Ok(());
+        }
+        log::debug!(
+            "OptimizeGraph removed {} of {} nodes",
+            removed_count,
+            node_count
+        );
+
+        let mut new_indices: Vec<Option<usize>> = Vec::with_capacity(node_count);
+        let mut next = 0;
+        for &removed in &removed {
+            new_indices.push((!removed).then_some(next));
+            next += usize::from(!removed);
+        }
+        let remap = |index: usize| new_indices.get(index).copied().flatten();
+
+        let old_arena = mem::take(arena);
+        for (mut node, &new_index) in old_arena.into_iter().zip(&new_indices) {
+            if new_index.is_none() {
+                continue;
+            }
+            node.parent = node.parent.and_then(remap);
+            node.children = node.children.into_iter().filter_map(remap).collect();
+            arena.push(node);
+        }
+        scene.nodes.root = remap(root);
+
+        for mesh in meshes.iter_mut() {
+            for bone in mesh.bones.iter_mut() {
+                bone.node_index = remap(bone.node_index).unwrap_or(bone.node_index);
+                bone.armature_index = remap(bone.armature_index).unwrap_or(bone.armature_index);
+            }
+        }
+        for skeleton in scene.skeletons.iter_mut() {
+            for bone in skeleton.bones_mut() {
+                if let Some(node_index) = remap(bone.node_index()) {
+                    bone.set_node_index(node_index);
+                }
+                bone.set_armature_index(bone.armature_index().and_then(remap));
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimation, AiBone, AiMatrix4x4, AiMesh, AiNode, AiNodeAnim, AiReal, AiVector3D,
+    };
+
+    fn translation(x: AiReal) -> AiMatrix4x4 {
+        AiMatrix4x4::from_translation(AiVector3D::new(x, 0.0, 0.0))
+    }
+
+    fn insert(
+        scene: &mut AiScene,
+        name: &str,
+        transformation: AiMatrix4x4,
+        mesh_indexes: Vec<usize>,
+        parent: Option<usize>,
+    ) -> usize {
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: name.to_string(),
+                    transformation,
+                    mesh_indexes,
+                    ..Default::default()
+                },
+                parent,
+            )
+            .unwrap()
+    }
+
+    fn create_mesh() -> AiMesh {
+        AiMesh {
+            vertices: vec![AiVector3D::new(0.0, 0.0, 0.0)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_optimize_graph_collapse() {
+        let mut scene = AiScene {
+            meshes: vec![create_mesh(), create_mesh()],
+            ..Default::default()
+        };
+        let root = insert(&mut scene, "Root", AiMatrix4x4::identity(), vec![], None);
+        let pivot = insert(&mut scene, "Pivot", translation(1.0), vec![], Some(root));
+        let inner = insert(&mut scene, "Inner", translation(2.0), vec![], Some(pivot));
+        insert(&mut scene, "Mesh", translation(4.0), vec![0], Some(inner));
+        insert(&mut scene, "Empty", translation(8.0), vec![], Some(root));
+        let animated = insert(
+            &mut scene,
+            "Animated",
+            translation(16.0),
+            vec![],
+            Some(root),
+        );
+        insert(
+            &mut scene,
+            "Child",
+            AiMatrix4x4::identity(),
+            vec![1],
+            Some(animated),
+        );
+        scene.animations.push(AiAnimation {
+            channels: vec![AiNodeAnim {
+                node_name: "Animated".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let mut step = OptimizeGraph::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::OptimizeGraph)));
+        step.process(&mut scene).unwrap();
+
+        let names: Vec<&str> = scene
+            .nodes
+            .arena
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Root", "Mesh", "Animated", "Child"]);
+        assert_eq!(scene.nodes.root, Some(0));
+        assert_eq!(scene.nodes.arena[0].children, vec![1, 2]);
+        assert_eq!(scene.nodes.arena[1].parent, Some(0));
+        assert_eq!(scene.nodes.arena[1].transformation, translation(7.0));
+        assert_eq!(scene.nodes.arena[2].children, vec![3]);
+        assert_eq!(scene.nodes.arena[3].parent, Some(2));
+    }
+
+    #[test]
+    fn test_optimize_graph_merge_siblings() {
+        let mut scene = AiScene {
+            meshes: vec![create_mesh(), create_mesh(), create_mesh(), create_mesh()],
+            ..Default::default()
+        };
+        let root = insert(&mut scene, "Root", AiMatrix4x4::identity(), vec![], None);
+        insert(&mut scene, "A", translation(1.0), vec![0], Some(root));
+        insert(&mut scene, "B", translation(1.0), vec![1], Some(root));
+        insert(&mut scene, "C", translation(3.0), vec![2], Some(root));
+        insert(&mut scene, "D", translation(5.0), vec![3], Some(root));
+        insert(&mut scene, "E", translation(7.0), vec![3], Some(root));
+        insert(&mut scene, "Kept", translation(9.0), vec![0], Some(root));
+
+        let step = OptimizeGraph {
+            keep_list: OptimizeGraph::parse_keep_list("Kept"),
+        };
+        step.process(&mut scene).unwrap();
+
+        let names: Vec<&str> = scene
+            .nodes
+            .arena
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Root", "A", "D", "E", "Kept"]);
+        assert_eq!(scene.nodes.arena[1].mesh_indexes, vec![0, 1, 2]);
+        assert_eq!(scene.meshes[1].vertices[0], AiVector3D::new(0.0, 0.0, 0.0));
+        assert_eq!(scene.meshes[2].vertices[0], AiVector3D::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_optimize_graph_remaps_bones() {
+        let mut scene = AiScene::default();
+        let root = insert(&mut scene, "Root", AiMatrix4x4::identity(), vec![], None);
+        let pivot = insert(
+            &mut scene,
+            "Pivot",
+            AiMatrix4x4::identity(),
+            vec![],
+            Some(root),
+        );
+        let armature = insert(
+            &mut scene,
+            "Armature",
+            AiMatrix4x4::identity(),
+            vec![],
+            Some(pivot),
+        );
+        let joint = insert(
+            &mut scene,
+            "Joint",
+            AiMatrix4x4::identity(),
+            vec![],
+            Some(armature),
+        );
+        insert(
+            &mut scene,
+            "Skin",
+            AiMatrix4x4::identity(),
+            vec![0],
+            Some(root),
+        );
+        scene.meshes.push(AiMesh {
+            bones: vec![AiBone {
+                name: "Joint".to_string(),
+                node_index: joint,
+                armature_index: armature,
+                ..Default::default()
+            }],
+            ..create_mesh()
+        });
+        OptimizeGraph::default().process(&mut scene).unwrap();
+
+        assert_eq!(scene.nodes.arena.len(), 4);
+        assert_eq!(scene.meshes[0].bones[0].node_index, 2);
+        assert_eq!(scene.meshes[0].bones[0].armature_index, 1);
+        assert_eq!(scene.nodes.arena[1].name, "Armature");
+    }
+}
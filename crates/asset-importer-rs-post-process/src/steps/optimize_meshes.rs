@@ -1,10 +1,109 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::mem;
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_SLM_TRIANGLE_LIMIT_DEFAULT, AI_CONFIG_PP_SLM_VERTEX_LIMIT_DEFAULT, AiPostProcess,
+    AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiMesh, AiScene};
 use enumflags2::BitFlags;
 
+use crate::helper::{MeshFormat, append_mesh};
+
 /// Optimize meshes
-#[derive(Default)]
-pub struct OptimizeMeshes;
+///
+/// Merges the meshes of a node that share their material, primitive types and vertex format,
+/// reducing the number of draw calls. Merged meshes hold at most `max_vertices` vertices and
+/// `max_faces` faces. Meshes referenced by several nodes, by no node, or with morph targets or
+/// mesh animations are kept as they are.
+///
+/// Meshes of different nodes are never merged since they are placed by different transforms,
+/// run `PreTransformVertices` or `OptimizeGraph` first to collapse the hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizeMeshes {
+    pub max_vertices: usize,
+    pub max_faces: usize,
+}
+
+impl Default for OptimizeMeshes {
+    fn default() -> Self {
+        Self {
+            max_vertices: AI_CONFIG_PP_SLM_VERTEX_LIMIT_DEFAULT,
+            max_faces: AI_CONFIG_PP_SLM_TRIANGLE_LIMIT_DEFAULT,
+        }
+    }
+}
+
+/// Meshes of a node that are merged together.
+struct MergeGroup {
+    format: Option<MeshFormat>,
+    meshes: Vec<usize>,
+    vertices: usize,
+    faces: usize,
+}
+
+impl OptimizeMeshes {
+    /// Returns whether the mesh can be merged with other meshes.
+    fn can_merge(scene: &AiScene, mesh: &AiMesh, references: usize) -> bool {
+        references == 1
+            && mesh.anim_meshes.is_empty()
+            && !scene.animations.iter().any(|animation| {
+                animation
+                    .mesh_channels
+                    .iter()
+                    .any(|channel| channel.name() == mesh.name)
+                    || animation
+                        .morph_channels
+                        .iter()
+                        .any(|channel| channel.name == mesh.name)
+            })
+    }
+
+    /// Groups the meshes of a node.
+    fn group_meshes(
+        &self,
+        scene: &AiScene,
+        mesh_indexes: &[usize],
+        references: &[usize],
+    ) -> Vec<MergeGroup> {
+        let mut groups: Vec<MergeGroup> = Vec::new();
+        for &mesh_index in mesh_indexes {
+            let Some(mesh) = scene.meshes.get(mesh_index) else {
+                continue;
+            };
+            let vertices = mesh.vertices.len();
+            let faces = mesh.faces.len();
+            if !Self::can_merge(scene, mesh, references[mesh_index]) {
+                groups.push(MergeGroup {
+                    format: None,
+                    meshes: vec![mesh_index],
+                    vertices,
+                    faces,
+                });
+                continue;
+            }
+
+            let format = MeshFormat::new(mesh);
+            match groups.iter_mut().find(|group| {
+                group.format.as_ref() == Some(&format)
+                    && group.vertices + vertices <= self.max_vertices
+                    && group.faces + faces <= self.max_faces
+            }) {
+                Some(group) => {
+                    group.meshes.push(mesh_index);
+                    group.vertices += vertices;
+                    group.faces += faces;
+                }
+                None => groups.push(MergeGroup {
+                    format: Some(format),
+                    meshes: vec![mesh_index],
+                    vertices,
+                    faces,
+                }),
+            }
+        }
+        groups
+    }
+}
 
 impl AiPostProcess for OptimizeMeshes {
     type Error = String;
@@ -13,8 +112,220 @@ impl AiPostProcess for OptimizeMeshes {
         steps.contains(AiPostProcessSteps::OptimizeMeshes)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement mesh optimization
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let mut references = vec![0; scene.meshes.len()];
+        for node in &scene.nodes.arena {
+            for &mesh_index in &node.mesh_indexes {
+                if let Some(count) = references.get_mut(mesh_index) {
+                    *count += 1;
+                }
+            }
+        }
+
+        // The first mesh of every group receives the other meshes of the group
+        let mut merged_into: Vec<usize> = (0..scene.meshes.len()).collect();
+        for node in &scene.nodes.arena {
+            for group in self.group_meshes(scene, &node.mesh_indexes, &references) {
+                for &mesh_index in &group.meshes[1..] {
+                    merged_into[mesh_index] = group.meshes[0];
+                }
+            }
+        }
+        if merged_into
+            .iter()
+            .enumerate()
+            .all(|(index, &target)| index == target)
+        {
+            return Ok(());
+        }
+
+        let mut old_meshes: Vec<Option<AiMesh>> =
+            mem::take(&mut scene.meshes).into_iter().map(Some).collect();
+        let mut new_indices = vec![0; old_meshes.len()];
+        let mut vertex_offsets = vec![0; old_meshes.len()];
+        for first in 0..old_meshes.len() {
+            if merged_into[first] != first {
+                continue;
+            }
+            let Some(mut mesh) = old_meshes[first].take() else {
+                continue;
+            };
+            new_indices[first] = scene.meshes.len();
+            for (index, &target) in merged_into.iter().enumerate().skip(first + 1) {
+                if target != first {
+                    continue;
+                }
+                if let Some(source) = old_meshes[index].take() {
+                    new_indices[index] = scene.meshes.len();
+                    vertex_offsets[index] = mesh.vertices.len();
+                    append_mesh(&mut mesh, source);
+                }
+            }
+            scene.meshes.push(mesh);
+        }
+        log::debug!(
+            "OptimizeMeshes merged {} meshes into {}",
+            old_meshes.len(),
+            scene.meshes.len()
+        );
+
+        for node in scene.nodes.arena.iter_mut() {
+            let mut mesh_indexes: Vec<usize> = Vec::with_capacity(node.mesh_indexes.len());
+            for &mesh_index in &node.mesh_indexes {
+                let Some(&new_index) = new_indices.get(mesh_index) else {
+                    continue;
+                };
+                if !mesh_indexes.contains(&new_index) {
+                    mesh_indexes.push(new_index);
+                }
+            }
+            node.mesh_indexes = mesh_indexes;
+        }
+        for skeleton in scene.skeletons.iter_mut() {
+            for bone in skeleton.bones_mut() {
+                let Some(mesh_index) = bone.mesh_index() else {
+                    continue;
+                };
+                let Some(&new_index) = new_indices.get(mesh_index) else {
+                    continue;
+                };
+                let offset = vertex_offsets[mesh_index];
+                bone.set_mesh_index(Some(new_index));
+                for weight in bone.weights_mut().iter_mut() {
+                    weight.vertex_id += offset;
+                }
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiBone, AiNode, AiPrimitiveType, AiSkeleton, AiSkeletonBone, AiVector3D, AiVertexWeight,
+    };
+
+    fn create_triangle(material_index: u32) -> AiMesh {
+        AiMesh {
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![AiVector3D::new(0.0, 0.0, 1.0); 3],
+            faces: vec![vec![0, 1, 2]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            material_index,
+            ..Default::default()
+        }
+    }
+
+    fn create_scene(meshes: Vec<AiMesh>, nodes: Vec<Vec<usize>>) -> AiScene {
+        let mut scene = AiScene {
+            meshes,
+            ..Default::default()
+        };
+        let root = scene.nodes.insert(AiNode::default(), None).unwrap();
+        for mesh_indexes in nodes {
+            scene
+                .nodes
+                .insert(
+                    AiNode {
+                        mesh_indexes,
+                        ..Default::default()
+                    },
+                    Some(root),
+                )
+                .unwrap();
+        }
+        scene
+    }
+
+    #[test]
+    fn test_optimize_meshes_merge() {
+        let mut scene = create_scene(
+            vec![create_triangle(0), create_triangle(1), create_triangle(0)],
+            vec![vec![0, 1, 2]],
+        );
+        let mut step = OptimizeMeshes::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::OptimizeMeshes)));
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.nodes.arena[1].mesh_indexes, vec![0, 1]);
+        assert_eq!(scene.meshes[0].vertices.len(), 6);
+        assert_eq!(scene.meshes[0].normals.len(), 6);
+        assert_eq!(scene.meshes[0].faces, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(scene.meshes[1].material_index, 1);
+    }
+
+    #[test]
+    fn test_optimize_meshes_keeps_shared_meshes() {
+        let mut without_normals = create_triangle(0);
+        without_normals.normals.clear();
+        let mut scene = create_scene(
+            vec![
+                create_triangle(0),
+                create_triangle(0),
+                without_normals,
+                create_triangle(0),
+            ],
+            vec![vec![0, 1, 2], vec![3], vec![1]],
+        );
+        OptimizeMeshes::default().process(&mut scene).unwrap();
+        assert_eq!(scene.meshes.len(), 4);
+
+        let mut scene = create_scene(
+            vec![create_triangle(0), create_triangle(0), create_triangle(0)],
+            vec![vec![0, 1, 2]],
+        );
+        let step = OptimizeMeshes {
+            max_vertices: 6,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.meshes[0].vertices.len(), 6);
+        assert_eq!(scene.meshes[1].vertices.len(), 3);
+    }
+
+    #[test]
+    fn test_optimize_meshes_bones() {
+        let mut first = create_triangle(0);
+        first.bones.push(AiBone {
+            name: "Bone".to_string(),
+            weights: vec![AiVertexWeight::new(0, 1.0)],
+            ..Default::default()
+        });
+        let mut second = create_triangle(0);
+        second.bones.push(AiBone {
+            name: "Bone".to_string(),
+            weights: vec![AiVertexWeight::new(2, 1.0)],
+            ..Default::default()
+        });
+        let mut scene = create_scene(vec![first, second], vec![vec![0, 1]]);
+        scene.skeletons.push(
+            AiSkeleton::new(
+                "Skeleton".to_string(),
+                vec![
+                    AiSkeletonBone::new(None, 0, Default::default(), Default::default())
+                        .with_mesh(1, vec![AiVertexWeight::new(1, 0.5)]),
+                ],
+            )
+            .unwrap(),
+        );
+        OptimizeMeshes::default().process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].bones.len(), 1);
+        assert_eq!(
+            scene.meshes[0].bones[0].weights,
+            vec![AiVertexWeight::new(0, 1.0), AiVertexWeight::new(5, 1.0)]
+        );
+        let bone = &scene.skeletons[0].bones()[0];
+        assert_eq!(bone.mesh_index(), Some(0));
+        assert_eq!(bone.weights(), &[AiVertexWeight::new(4, 0.5)]);
+    }
+}
@@ -1,10 +1,170 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::mem;
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_PTV_KEEP_HIERARCHY_DEFAULT, AI_CONFIG_PP_PTV_NORMALIZE_DEFAULT, AiPostProcess,
+    AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{
+    AiAABB, AiMatrix4x4, AiMesh, AiNode, AiNodeTree, AiScene, AiVector3D,
+};
 use enumflags2::BitFlags;
 
+use crate::helper::{MeshFormat, append_mesh, transform_mesh};
+
 /// Pre-transform vertices
-#[derive(Default)]
-pub struct PreTransformVertices;
+///
+/// Bakes the world transform of every node into the meshes it references, meshes referenced
+/// by several nodes are copied. Faces of mirrored meshes are flipped to keep their winding.
+/// Lights and cameras are moved to world space as well.
+///
+/// By default, meshes sharing their material and vertex format are merged, and the node tree is
+/// flattened to the root with a child node per mesh, light and camera, named after them. With
+/// `mesh_nodes` disabled, the root references all meshes itself. With `keep_hierarchy`, meshes
+/// stay attached to their nodes, whose transforms are reset to identity, and no mesh is merged.
+/// With `normalize`, the result is scaled and centered into the `[-1, 1]` cube.
+///
+/// Animations, mesh bones and skeletons are removed since the transforms they refer to are gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreTransformVertices {
+    pub keep_hierarchy: bool,
+    pub normalize: bool,
+    pub mesh_nodes: bool,
+}
+
+impl Default for PreTransformVertices {
+    fn default() -> Self {
+        Self {
+            keep_hierarchy: AI_CONFIG_PP_PTV_KEEP_HIERARCHY_DEFAULT,
+            normalize: AI_CONFIG_PP_PTV_NORMALIZE_DEFAULT,
+            mesh_nodes: true,
+        }
+    }
+}
+
+/// Computes the world transform of every node reachable from the root.
+fn world_transforms(nodes: &AiNodeTree) -> Vec<Option<AiMatrix4x4>> {
+    let mut transforms: Vec<Option<AiMatrix4x4>> = vec![None; nodes.arena.len()];
+    let Some(root) = nodes.root.filter(|&root| root < nodes.arena.len()) else {
+        return transforms;
+    };
+    transforms[root] = Some(nodes.arena[root].transformation.clone());
+    let mut stack = vec![root];
+    while let Some(index) = stack.pop() {
+        let Some(parent) = transforms[index].clone() else {
+            continue;
+        };
+        for &child in &nodes.arena[index].children {
+            if child < transforms.len() && transforms[child].is_none() {
+                transforms[child] = Some(&parent * &nodes.arena[child].transformation);
+                stack.push(child);
+            }
+        }
+    }
+    transforms
+}
+
+impl PreTransformVertices {
+    /// Moves lights and cameras to world space.
+    fn transform_lights_and_cameras(scene: &mut AiScene) {
+        for light in scene.lights.iter_mut() {
+            if let (Some(position), Some(direction), Some(up)) = (
+                light.get_world_position(&scene.nodes),
+                light.get_world_direction(&scene.nodes),
+                light.get_world_up(&scene.nodes),
+            ) {
+                light.position = position;
+                light.direction = direction;
+                light.up = up;
+            }
+        }
+        for camera in scene.cameras.iter_mut() {
+            if let (Some(position), Some(look_vec), Some(up_vec)) = (
+                camera.get_world_position(&scene.nodes),
+                camera.get_world_look_vec(&scene.nodes),
+                camera.get_world_up_vec(&scene.nodes),
+            ) {
+                camera.position = position;
+                camera.look_vec = look_vec;
+                camera.up_vec = up_vec;
+            }
+        }
+    }
+
+    /// Replaces the node tree by a root with a child per mesh, light and camera.
+    fn flatten_nodes(&self, scene: &mut AiScene) {
+        let root = scene
+            .nodes
+            .root
+            .and_then(|root| scene.nodes.arena.get(root))
+            .cloned()
+            .unwrap_or_default();
+        let mut nodes = AiNodeTree::default();
+        let Ok(root) = nodes.insert(
+            AiNode {
+                name: root.name,
+                metadata: root.metadata,
+                ..Default::default()
+            },
+            None,
+        ) else {
+            return;
+        };
+        if self.mesh_nodes {
+            for (mesh_index, mesh) in scene.meshes.iter().enumerate() {
+                let _ = nodes.insert(
+                    AiNode {
+                        name: mesh.name.clone(),
+                        mesh_indexes: vec![mesh_index],
+                        ..Default::default()
+                    },
+                    Some(root),
+                );
+            }
+        } else {
+            nodes.arena[root].mesh_indexes = (0..scene.meshes.len()).collect();
+        }
+        let names = scene
+            .lights
+            .iter()
+            .map(|light| &light.name)
+            .chain(scene.cameras.iter().map(|camera| &camera.name));
+        for name in names {
+            let _ = nodes.insert(
+                AiNode {
+                    name: name.clone(),
+                    ..Default::default()
+                },
+                Some(root),
+            );
+        }
+        scene.nodes = nodes;
+    }
+
+    /// Scales and centers the meshes, lights and cameras into the `[-1, 1]` cube.
+    fn normalize_scene(scene: &mut AiScene) {
+        let bounds = AiAABB::from_points(scene.meshes.iter().flat_map(|mesh| &mesh.vertices));
+        if bounds.is_empty() {
+            return;
+        }
+        let size = bounds.size();
+        let extent = size.x.max(size.y).max(size.z) / 2.0;
+        if extent <= 0.0 {
+            return;
+        }
+        let scale = 1.0 / extent;
+        let transform = &AiMatrix4x4::from_scaling(AiVector3D::new(scale, scale, scale))
+            * &AiMatrix4x4::from_translation(-bounds.center());
+        for mesh in scene.meshes.iter_mut() {
+            transform_mesh(mesh, &transform);
+        }
+        for light in scene.lights.iter_mut() {
+            light.position = transform.transform_point(&light.position);
+        }
+        for camera in scene.cameras.iter_mut() {
+            camera.position = transform.transform_point(&camera.position);
+        }
+    }
+}
 
 impl AiPostProcess for PreTransformVertices {
     type Error = String;
@@ -14,7 +174,269 @@ impl AiPostProcess for PreTransformVertices {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement vertex pre-transformation
+        if scene.nodes.root.is_none() {
+            return Ok(());
+        }
+        let transforms = world_transforms(&scene.nodes);
+        Self::transform_lights_and_cameras(scene);
+
+        if !scene.animations.is_empty()
+            || !scene.skeletons.is_empty()
+            || scene.meshes.iter().any(|mesh| !mesh.bones.is_empty())
+        {
+            log::warn!("PreTransformVertices removes animations, bones and skeletons");
+            scene.animations.clear();
+            scene.skeletons.clear();
+            for mesh in scene.meshes.iter_mut() {
+                mesh.bones.clear();
+            }
+        }
+
+        // Every reference of a mesh by a reachable node becomes an instance in world space
+        let mut references = vec![0; scene.meshes.len()];
+        let mut instances: Vec<(usize, usize)> = Vec::new();
+        for (node_index, node) in scene.nodes.arena.iter().enumerate() {
+            if transforms[node_index].is_none() {
+                continue;
+            }
+            for &mesh_index in &node.mesh_indexes {
+                if let Some(count) = references.get_mut(mesh_index) {
+                    *count += 1;
+                    instances.push((node_index, mesh_index));
+                }
+            }
+        }
+        let identity = AiMatrix4x4::identity();
+        let mut originals: Vec<Option<AiMesh>> =
+            mem::take(&mut scene.meshes).into_iter().map(Some).collect();
+        let mut meshes: Vec<AiMesh> = Vec::with_capacity(instances.len());
+        for &(node_index, mesh_index) in &instances {
+            references[mesh_index] -= 1;
+            let mut mesh = if references[mesh_index] == 0 {
+                originals[mesh_index].take()
+            } else {
+                originals[mesh_index].clone()
+            }
+            .unwrap_or_default();
+            if let Some(transform) = &transforms[node_index]
+                && *transform != identity
+            {
+                transform_mesh(&mut mesh, transform);
+            }
+            meshes.push(mesh);
+        }
+
+        if self.keep_hierarchy {
+            let mut instance = 0;
+            for (node_index, node) in scene.nodes.arena.iter_mut().enumerate() {
+                node.transformation = AiMatrix4x4::identity();
+                if transforms[node_index].is_none() {
+                    node.mesh_indexes.clear();
+                    continue;
+                }
+                for mesh_index in node.mesh_indexes.iter_mut() {
+                    *mesh_index = instance;
+                    instance += 1;
+                }
+            }
+            scene.meshes = meshes;
+        } else {
+            // Merge instances sharing their material and vertex format
+            let mut formats: Vec<Option<MeshFormat>> = Vec::new();
+            for mesh in meshes {
+                let format = mesh.anim_meshes.is_empty().then(|| MeshFormat::new(&mesh));
+                let target = format.as_ref().and_then(|format| {
+                    formats
+                        .iter()
+                        .position(|other| other.as_ref() == Some(format))
+                });
+                match target {
+                    Some(target) => append_mesh(&mut scene.meshes[target], mesh),
+                    None => {
+                        formats.push(format);
+                        scene.meshes.push(mesh);
+                    }
+                }
+            }
+            log::debug!(
+                "PreTransformVertices baked {} mesh instances into {} meshes",
+                instances.len(),
+                scene.meshes.len()
+            );
+            self.flatten_nodes(scene);
+        }
+
+        if self.normalize {
+            Self::normalize_scene(scene);
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiAnimation, AiLight, AiPrimitiveType, AiReal};
+
+    fn create_triangle(material_index: u32) -> AiMesh {
+        AiMesh {
+            name: format!("Triangle{}", material_index),
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+            ],
+            normals: vec![AiVector3D::new(0.0, 0.0, 1.0); 3],
+            faces: vec![vec![0, 1, 2]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            material_index,
+            ..Default::default()
+        }
+    }
+
+    fn insert(
+        scene: &mut AiScene,
+        name: &str,
+        transformation: AiMatrix4x4,
+        mesh_indexes: Vec<usize>,
+        parent: Option<usize>,
+    ) -> usize {
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: name.to_string(),
+                    transformation,
+                    mesh_indexes,
+                    ..Default::default()
+                },
+                parent,
+            )
+            .unwrap()
+    }
+
+    /// A root translated along x, with a child translated along y and a mirrored child.
+    fn create_scene() -> AiScene {
+        let mut scene = AiScene {
+            meshes: vec![create_triangle(0), create_triangle(1)],
+            lights: vec![AiLight {
+                name: "Light".to_string(),
+                ..Default::default()
+            }],
+            animations: vec![AiAnimation::default()],
+            ..Default::default()
+        };
+        let root = insert(
+            &mut scene,
+            "Root",
+            AiMatrix4x4::from_translation(AiVector3D::new(1.0, 0.0, 0.0)),
+            vec![0],
+            None,
+        );
+        let child = insert(
+            &mut scene,
+            "Child",
+            AiMatrix4x4::from_translation(AiVector3D::new(0.0, 2.0, 0.0)),
+            vec![0, 1],
+            Some(root),
+        );
+        insert(
+            &mut scene,
+            "Light",
+            AiMatrix4x4::identity(),
+            vec![],
+            Some(child),
+        );
+        insert(
+            &mut scene,
+            "Mirror",
+            AiMatrix4x4::from_scaling(AiVector3D::new(-1.0, 1.0, 1.0)),
+            vec![1],
+            Some(root),
+        );
+        scene
+    }
+
+    #[test]
+    fn test_pre_transform_vertices() {
+        let mut scene = create_scene();
+        let mut step = PreTransformVertices::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::PreTransformVertices)));
+        step.process(&mut scene).unwrap();
+
+        assert!(scene.animations.is_empty());
+        assert_eq!(scene.meshes.len(), 2);
+        let first = &scene.meshes[0];
+        assert_eq!(first.material_index, 0);
+        assert_eq!(first.faces, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(first.vertices[0], AiVector3D::new(1.0, 0.0, 0.0));
+        assert_eq!(first.vertices[3], AiVector3D::new(1.0, 2.0, 0.0));
+
+        let second = &scene.meshes[1];
+        assert_eq!(second.material_index, 1);
+        assert_eq!(second.vertices.len(), 6);
+        // The mirrored instance keeps its winding, so its normal still faces +z
+        assert_eq!(second.vertices[4], AiVector3D::new(0.0, 0.0, 0.0));
+        assert_eq!(second.faces[1], vec![5, 4, 3]);
+        assert_eq!(second.normals[3], AiVector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(scene.lights[0].position, AiVector3D::new(1.0, 2.0, 0.0));
+        let names: Vec<&str> = scene
+            .nodes
+            .arena
+            .iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Root", "Triangle0", "Triangle1", "Light"]);
+        assert!(
+            scene
+                .nodes
+                .arena
+                .iter()
+                .all(|node| node.transformation == AiMatrix4x4::identity())
+        );
+        assert_eq!(scene.nodes.arena[2].mesh_indexes, vec![1]);
+    }
+
+    #[test]
+    fn test_pre_transform_vertices_keep_hierarchy() {
+        let mut scene = create_scene();
+        let step = PreTransformVertices {
+            keep_hierarchy: true,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 4);
+        assert_eq!(scene.nodes.arena.len(), 4);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0]);
+        assert_eq!(scene.nodes.arena[1].mesh_indexes, vec![1, 2]);
+        assert_eq!(scene.nodes.arena[3].mesh_indexes, vec![3]);
+        assert_eq!(scene.meshes[1].vertices[0], AiVector3D::new(1.0, 2.0, 0.0));
+        assert!(
+            scene
+                .nodes
+                .arena
+                .iter()
+                .all(|node| node.transformation == AiMatrix4x4::identity())
+        );
+    }
+
+    #[test]
+    fn test_pre_transform_vertices_normalize() {
+        let mut scene = create_scene();
+        let step = PreTransformVertices {
+            normalize: true,
+            mesh_nodes: false,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        let bounds = AiAABB::from_points(scene.meshes.iter().flat_map(|mesh| &mesh.vertices));
+        let epsilon: AiReal = 1e-5;
+        assert!((bounds.min.x.min(bounds.min.y) + 1.0).abs() < epsilon);
+        assert!((bounds.max.x.max(bounds.max.y) - 1.0).abs() < epsilon);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0, 1]);
+        assert_eq!(scene.nodes.arena.len(), 2);
+    }
+}
@@ -1,10 +1,48 @@
+use std::collections::HashMap;
+
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{AiMaterial, AiScene, matkey};
 use enumflags2::BitFlags;
 
+use crate::helper::parse_name_list;
+
 /// Remove redundant materials
-#[derive(Default)]
-pub struct RemoveRedundantMaterials;
+///
+/// Materials with the same set of properties are merged into the first of them, and materials
+/// no mesh uses are removed. Mesh material indices are remapped accordingly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoveRedundantMaterials {
+    /// Compare materials without their name, as Assimp does.
+    pub ignore_name: bool,
+    /// Names of materials that are neither merged nor removed, see
+    /// [`RemoveRedundantMaterials::parse_exclude_list`].
+    pub exclude_list: Vec<String>,
+}
+
+impl Default for RemoveRedundantMaterials {
+    fn default() -> Self {
+        Self {
+            ignore_name: true,
+            exclude_list: Vec::new(),
+        }
+    }
+}
+
+impl RemoveRedundantMaterials {
+    /// Parses a whitespace separated list of material names, names containing spaces are
+    /// enclosed in single quotes, e.g. `"Skin 'Car Paint' Glass"`.
+    pub fn parse_exclude_list(list: &str) -> Vec<String> {
+        parse_name_list(list)
+    }
+
+    fn is_excluded(&self, material: &AiMaterial) -> bool {
+        !self.exclude_list.is_empty()
+            && material
+                .get_property_ai_str(matkey::AI_MATKEY_NAME, None, 0)
+                .and_then(Result::ok)
+                .is_some_and(|name| self.exclude_list.contains(&name))
+    }
+}
 
 impl AiPostProcess for RemoveRedundantMaterials {
     type Error = String;
@@ -13,8 +51,182 @@ impl AiPostProcess for RemoveRedundantMaterials {
         steps.contains(AiPostProcessSteps::RemoveRedundantMaterials)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement redundant material removal
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let skip_keys: &[&str] = if self.ignore_name {
+            &[matkey::AI_MATKEY_NAME]
+        } else {
+            &[]
+        };
+
+        let mut used = vec![false; scene.materials.len()];
+        for mesh in &scene.meshes {
+            if let Some(used) = used.get_mut(mesh.material_index as usize) {
+                *used = true;
+            }
+        }
+
+        // For every material, the material it is merged into, or `None` if it is removed
+        let mut targets: Vec<Option<usize>> = vec![None; scene.materials.len()];
+        let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, material) in scene.materials.iter().enumerate() {
+            if self.is_excluded(material) {
+                targets[index] = Some(index);
+                continue;
+            }
+            if !used[index] {
+                continue;
+            }
+            let candidates = by_hash
+                .entry(material.properties_hash(skip_keys))
+                .or_default();
+            let existing = candidates
+                .iter()
+                .copied()
+                .find(|&candidate| scene.materials[candidate].properties_eq(material, skip_keys));
+            targets[index] = Some(existing.unwrap_or_else(|| {
+                candidates.push(index);
+                index
+            }));
+        }
+
+        if targets
+            .iter()
+            .enumerate()
+            .all(|(index, target)| *target == Some(index))
+        {
+            return Ok(());
+        }
+
+        let mut new_indices: Vec<Option<u32>> = vec![None; scene.materials.len()];
+        let mut materials = Vec::new();
+        for (index, material) in std::mem::take(&mut scene.materials).into_iter().enumerate() {
+            if targets[index] == Some(index) {
+                new_indices[index] = Some(materials.len() as u32);
+                materials.push(material);
+            }
+        }
+        let removed = targets.len() - materials.len();
+        scene.materials = materials;
+
+        for mesh in scene.meshes.iter_mut() {
+            let index = mesh.material_index as usize;
+            if let Some(new_index) = targets
+                .get(index)
+                .copied()
+                .flatten()
+                .and_then(|target| new_indices[target])
+            {
+                mesh.material_index = new_index;
+            }
+        }
+        log::debug!("RemoveRedundantMaterials removed {} materials", removed);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AiMesh, AiPropertyTypeInfo, AiTextureType};
+
+    fn create_material(name: &str, texture: &str) -> AiMaterial {
+        let mut material = AiMaterial::new();
+        material.add_property(
+            matkey::AI_MATKEY_NAME,
+            Some(AiTextureType::None),
+            AiPropertyTypeInfo::Binary,
+            0,
+            name.bytes().collect(),
+        );
+        material.add_property(
+            matkey::_AI_MATKEY_TEXTURE_BASE,
+            Some(AiTextureType::Diffuse),
+            AiPropertyTypeInfo::Binary,
+            0,
+            texture.bytes().collect(),
+        );
+        material
+    }
+
+    fn create_scene() -> AiScene {
+        AiScene {
+            materials: vec![
+                create_material("A", "wood.png"),
+                create_material("B", "wood.png"),
+                create_material("Unused", "stone.png"),
+                create_material("C", "stone.png"),
+            ],
+            meshes: (0..4)
+                .filter(|&index| index != 2)
+                .map(|index| AiMesh {
+                    material_index: index,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn material_name(material: &AiMaterial) -> String {
+        material
+            .get_property_ai_str(matkey::AI_MATKEY_NAME, None, 0)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_remove_redundant_materials() {
+        let mut scene = create_scene();
+        let mut step = RemoveRedundantMaterials::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::RemoveRedundantMaterials)));
+        step.process(&mut scene).unwrap();
+
+        let names: Vec<String> = scene.materials.iter().map(material_name).collect();
+        assert_eq!(names, vec!["A", "C"]);
+        let indices: Vec<u32> = scene
+            .meshes
+            .iter()
+            .map(|mesh| mesh.material_index)
+            .collect();
+        assert_eq!(indices, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_remove_redundant_materials_compare_names() {
+        let mut scene = create_scene();
+        let step = RemoveRedundantMaterials {
+            ignore_name: false,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+        let names: Vec<String> = scene.materials.iter().map(material_name).collect();
+        assert_eq!(names, vec!["A", "B", "C"]);
+        let indices: Vec<u32> = scene
+            .meshes
+            .iter()
+            .map(|mesh| mesh.material_index)
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_remove_redundant_materials_exclude_list() {
+        let exclude_list = RemoveRedundantMaterials::parse_exclude_list("  B 'Un used'\tUnused ");
+        assert_eq!(exclude_list, vec!["B", "Un used", "Unused"]);
+
+        let mut scene = create_scene();
+        let step = RemoveRedundantMaterials {
+            exclude_list,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+        let names: Vec<String> = scene.materials.iter().map(material_name).collect();
+        assert_eq!(names, vec!["A", "B", "Unused", "C"]);
+        let indices: Vec<u32> = scene
+            .meshes
+            .iter()
+            .map(|mesh| mesh.material_index)
+            .collect();
+        assert_eq!(indices, vec![0, 1, 3]);
+    }
+}
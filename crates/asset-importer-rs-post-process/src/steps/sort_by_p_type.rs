@@ -1,10 +1,59 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{AI_CONFIG_PP_SBP_REMOVE_DEFAULT, AiPostProcess, AiPostProcessSteps};
+use asset_importer_rs_scene::{AiMesh, AiPrimitiveType, AiScene};
 use enumflags2::BitFlags;
 
+use crate::helper::{extract_sub_mesh, replace_meshes, skeleton_bound_meshes};
+
+/// Primitive types in the order their meshes are created.
+const PRIMITIVE_TYPES: [AiPrimitiveType; 4] = [
+    AiPrimitiveType::Point,
+    AiPrimitiveType::Line,
+    AiPrimitiveType::Triangle,
+    AiPrimitiveType::Polygon,
+];
+
 /// Sort by primitive type
-#[derive(Default)]
-pub struct SortByPType;
+///
+/// Splits meshes mixing several primitive types into one mesh per type, in the order points,
+/// lines, triangles and polygons. Faces of the types in `remove` are removed, and so are meshes
+/// left without faces. Nodes reference all meshes a mesh is split into.
+///
+/// Meshes bound to skeleton bones are not split, their faces of removed types are dropped in
+/// place so their vertices and bone weights stay valid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortByPType {
+    pub remove: BitFlags<AiPrimitiveType>,
+}
+
+impl Default for SortByPType {
+    fn default() -> Self {
+        Self {
+            remove: AI_CONFIG_PP_SBP_REMOVE_DEFAULT,
+        }
+    }
+}
+
+impl SortByPType {
+    /// Groups the faces of a mesh by primitive type, skipping removed types.
+    fn group_faces(&self, mesh: &AiMesh) -> Vec<(AiPrimitiveType, Vec<usize>)> {
+        let mut groups: Vec<(AiPrimitiveType, Vec<usize>)> = PRIMITIVE_TYPES
+            .iter()
+            .filter(|&&primitive_type| !self.remove.contains(primitive_type))
+            .map(|&primitive_type| (primitive_type, Vec::new()))
+            .collect();
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            let primitive_type = AiPrimitiveType::primitive_type_for_n_indices(face.len());
+            if let Some((_, faces)) = groups
+                .iter_mut()
+                .find(|(group_type, _)| *group_type == primitive_type)
+            {
+                faces.push(face_index);
+            }
+        }
+        groups.retain(|(_, faces)| !faces.is_empty());
+        groups
+    }
+}
 
 impl AiPostProcess for SortByPType {
     type Error = String;
@@ -14,7 +63,215 @@ impl AiPostProcess for SortByPType {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement primitive type sorting
+        let bound = skeleton_bound_meshes(scene);
+        let mut parts = Vec::with_capacity(scene.meshes.len());
+        let mut changed = false;
+        for (mut mesh, bound) in std::mem::take(&mut scene.meshes).into_iter().zip(bound) {
+            let groups = self.group_faces(&mesh);
+            if mesh.faces.is_empty() || (groups.len() == 1 && groups[0].1.len() == mesh.faces.len())
+            {
+                let vertex_count = mesh.vertices.len();
+                parts.push(vec![(mesh, (0..vertex_count).collect())]);
+                continue;
+            }
+            if bound {
+                if groups.len() > 1 {
+                    log::warn!("Mesh {} is bound to a skeleton and is not split", mesh.name);
+                }
+                let kept_faces: usize = groups.iter().map(|(_, faces)| faces.len()).sum();
+                if kept_faces < mesh.faces.len() {
+                    changed = true;
+                    if groups.is_empty() {
+                        log::debug!("Mesh {} only holds removed primitive types", mesh.name);
+                        parts.push(Vec::new());
+                        continue;
+                    }
+                    mesh.faces.retain(|face| {
+                        !self
+                            .remove
+                            .contains(AiPrimitiveType::primitive_type_for_n_indices(face.len()))
+                    });
+                    let mut primitive_types: BitFlags<AiPrimitiveType> = groups
+                        .iter()
+                        .map(|&(primitive_type, _)| primitive_type)
+                        .collect();
+                    if primitive_types.contains(AiPrimitiveType::Triangle) {
+                        primitive_types |= mesh.primitive_types & AiPrimitiveType::NgonEncodingFlag;
+                    }
+                    mesh.primitive_types = primitive_types;
+                }
+                let vertex_count = mesh.vertices.len();
+                parts.push(vec![(mesh, (0..vertex_count).collect())]);
+                continue;
+            }
+            changed = true;
+            if groups.is_empty() {
+                log::debug!("Mesh {} only holds removed primitive types", mesh.name);
+            }
+            parts.push(
+                groups
+                    .iter()
+                    .map(|(primitive_type, faces)| {
+                        let (mut sub_mesh, vertices) = extract_sub_mesh(&mesh, faces);
+                        if *primitive_type != AiPrimitiveType::Triangle {
+                            sub_mesh
+                                .primitive_types
+                                .remove(AiPrimitiveType::NgonEncodingFlag);
+                        }
+                        (sub_mesh, vertices)
+                    })
+                    .collect(),
+            );
+        }
+        if changed {
+            replace_meshes(scene, parts);
+        } else {
+            scene.meshes = parts.into_iter().flatten().map(|(mesh, _)| mesh).collect();
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiMatrix4x4, AiNode, AiSkeleton, AiSkeletonBone, AiVector3D, AiVertexWeight,
+    };
+
+    fn create_mesh() -> AiMesh {
+        AiMesh {
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+                AiVector3D::new(1.0, 1.0, 0.0),
+            ],
+            faces: vec![vec![0, 1, 2], vec![3], vec![1, 3], vec![1, 3, 2]],
+            primitive_types: AiPrimitiveType::Point
+                | AiPrimitiveType::Line
+                | AiPrimitiveType::Triangle,
+            ..Default::default()
+        }
+    }
+
+    fn create_scene() -> AiScene {
+        let triangles = AiMesh {
+            vertices: vec![AiVector3D::zero(); 3],
+            faces: vec![vec![0, 1, 2]],
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            ..Default::default()
+        };
+        let mut scene = AiScene {
+            meshes: vec![create_mesh(), triangles],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0, 1],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        scene
+    }
+
+    #[test]
+    fn test_sort_by_p_type() {
+        let mut scene = create_scene();
+        let mut step = SortByPType::default();
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::SortByPType)));
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 4);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0, 1, 2, 3]);
+        let primitive_types: Vec<BitFlags<AiPrimitiveType>> = scene
+            .meshes
+            .iter()
+            .map(|mesh| mesh.primitive_types)
+            .collect();
+        assert_eq!(
+            primitive_types,
+            vec![
+                BitFlags::from(AiPrimitiveType::Point),
+                BitFlags::from(AiPrimitiveType::Line),
+                BitFlags::from(AiPrimitiveType::Triangle),
+                BitFlags::from(AiPrimitiveType::Triangle),
+            ]
+        );
+        assert_eq!(
+            scene.meshes[0].vertices,
+            vec![AiVector3D::new(1.0, 1.0, 0.0)]
+        );
+        assert_eq!(scene.meshes[2].faces, vec![vec![0, 1, 2], vec![1, 3, 2]]);
+    }
+
+    #[test]
+    fn test_sort_by_p_type_remove() {
+        let mut scene = create_scene();
+        let step = SortByPType {
+            remove: AiPrimitiveType::Point | AiPrimitiveType::Line,
+        };
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0, 1]);
+        assert!(
+            scene
+                .meshes
+                .iter()
+                .all(|mesh| mesh.primitive_types == AiPrimitiveType::Triangle)
+        );
+
+        let step = SortByPType {
+            remove: BitFlags::from(AiPrimitiveType::Triangle),
+        };
+        step.process(&mut scene).unwrap();
+        assert!(scene.meshes.is_empty());
+        assert!(scene.nodes.arena[0].mesh_indexes.is_empty());
+    }
+
+    #[test]
+    fn test_sort_by_p_type_skeleton_bound() {
+        let weights = vec![AiVertexWeight::new(1, 0.5), AiVertexWeight::new(3, 1.0)];
+        let bone = AiSkeletonBone::new(None, 0, AiMatrix4x4::identity(), AiMatrix4x4::identity())
+            .with_mesh(0, weights.clone());
+        let mut scene = create_scene();
+        scene.skeletons = vec![AiSkeleton::new("Skeleton".to_string(), vec![bone]).unwrap()];
+        let step = SortByPType {
+            remove: AiPrimitiveType::Point | AiPrimitiveType::Line,
+        };
+        step.process(&mut scene).unwrap();
+
+        // The skinned mesh keeps its vertices, only its points and lines are dropped
+        assert_eq!(scene.meshes.len(), 2);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0, 1]);
+        assert_eq!(scene.meshes[0].vertices, create_mesh().vertices);
+        assert_eq!(scene.meshes[0].faces, vec![vec![0, 1, 2], vec![1, 3, 2]]);
+        assert_eq!(
+            scene.meshes[0].primitive_types,
+            BitFlags::from(AiPrimitiveType::Triangle)
+        );
+        let bone = &scene.skeletons[0].bones()[0];
+        assert_eq!(bone.mesh_index(), Some(0));
+        assert_eq!(bone.weights(), weights.as_slice());
+
+        // Mixed types that are kept are not split
+        let mut scene = create_scene();
+        scene.skeletons = vec![
+            AiSkeleton::new(
+                "Skeleton".to_string(),
+                vec![
+                    AiSkeletonBone::new(None, 0, AiMatrix4x4::identity(), AiMatrix4x4::identity())
+                        .with_mesh(0, weights),
+                ],
+            )
+            .unwrap(),
+        ];
+        SortByPType::default().process(&mut scene).unwrap();
+        assert_eq!(scene.meshes[0], create_mesh());
+    }
+}
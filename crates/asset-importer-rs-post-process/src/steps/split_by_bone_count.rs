@@ -1,10 +1,95 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::collections::HashSet;
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_SBBC_MAX_BONES_DEFAULT, AiPostProcess, AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiMesh, AiScene};
 use enumflags2::BitFlags;
 
+use crate::helper::{extract_sub_mesh, replace_meshes, skeleton_bound_meshes};
+
 /// Split by bone count
-#[derive(Default)]
-pub struct SplitByBoneCount;
+///
+/// Splits meshes with more than `max_bones` bones into submeshes that each reference at most
+/// `max_bones` bones. Vertices shared by faces of different submeshes are duplicated, and
+/// every submesh keeps the name, material, offset matrices and bone nodes of the original
+/// mesh. Nodes reference all submeshes of a split mesh.
+///
+/// A face whose vertices alone are influenced by more than `max_bones` bones cannot be split
+/// and is kept in a submesh of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitByBoneCount {
+    pub max_bones: usize,
+}
+
+impl Default for SplitByBoneCount {
+    fn default() -> Self {
+        Self {
+            max_bones: AI_CONFIG_PP_SBBC_MAX_BONES_DEFAULT,
+        }
+    }
+}
+
+impl SplitByBoneCount {
+    /// Groups the faces of a mesh so that no group references more than `max_bones` bones.
+    fn group_faces(&self, mesh: &AiMesh) -> Vec<Vec<usize>> {
+        let mut vertex_bones: Vec<Vec<usize>> = vec![Vec::new(); mesh.vertices.len()];
+        for (bone_index, bone) in mesh.bones.iter().enumerate() {
+            for weight in &bone.weights {
+                if weight.weight > 0.0
+                    && let Some(bones) = vertex_bones.get_mut(weight.vertex_id)
+                {
+                    bones.push(bone_index);
+                }
+            }
+        }
+        let face_bones: Vec<HashSet<usize>> = mesh
+            .faces
+            .iter()
+            .map(|face| {
+                face.iter()
+                    .filter_map(|&index| vertex_bones.get(index))
+                    .flatten()
+                    .copied()
+                    .collect()
+            })
+            .collect();
+
+        // Every pass fills one group with as many of the remaining faces as fit
+        let mut assigned = vec![false; mesh.faces.len()];
+        let mut remaining = mesh.faces.len();
+        let mut groups = Vec::new();
+        while remaining > 0 {
+            let mut bones: HashSet<usize> = HashSet::new();
+            let mut faces = Vec::new();
+            for (face_index, face_bones) in face_bones.iter().enumerate() {
+                if assigned[face_index] {
+                    continue;
+                }
+                let new_bones = face_bones.difference(&bones).count();
+                if bones.len() + new_bones > self.max_bones && !faces.is_empty() {
+                    continue;
+                }
+                bones.extend(face_bones);
+                faces.push(face_index);
+                assigned[face_index] = true;
+                remaining -= 1;
+                if bones.len() > self.max_bones {
+                    log::warn!(
+                        "Face {} of mesh {} is influenced by {} bones, more than the limit of {}",
+                        face_index,
+                        mesh.name,
+                        bones.len(),
+                        self.max_bones
+                    );
+                    break;
+                }
+            }
+            groups.push(faces);
+        }
+        groups
+    }
+}
 
 impl AiPostProcess for SplitByBoneCount {
     type Error = String;
@@ -14,7 +99,170 @@ impl AiPostProcess for SplitByBoneCount {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement bone count splitting
+        if scene
+            .meshes
+            .iter()
+            .all(|mesh| mesh.bones.len() <= self.max_bones)
+        {
+            return Ok(());
+        }
+
+        let bound = skeleton_bound_meshes(scene);
+        let mut parts = Vec::with_capacity(scene.meshes.len());
+        for (mesh, bound) in std::mem::take(&mut scene.meshes).into_iter().zip(bound) {
+            if mesh.bones.len() <= self.max_bones || bound {
+                if bound && mesh.bones.len() > self.max_bones {
+                    log::warn!("Mesh {} is bound to a skeleton and is not split", mesh.name);
+                }
+                let vertex_count = mesh.vertices.len();
+                parts.push(vec![(mesh, (0..vertex_count).collect())]);
+                continue;
+            }
+            let groups = self.group_faces(&mesh);
+            log::debug!(
+                "Mesh {} with {} bones is split into {} meshes",
+                mesh.name,
+                mesh.bones.len(),
+                groups.len()
+            );
+            parts.push(
+                groups
+                    .iter()
+                    .map(|faces| extract_sub_mesh(&mesh, faces))
+                    .collect(),
+            );
+        }
+        replace_meshes(scene, parts);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiBone, AiMatrix4x4, AiNode, AiPrimitiveType, AiReal, AiVector3D, AiVertexWeight,
+    };
+
+    /// Creates a strip of quads, every quad is deformed by a bone of its own.
+    fn create_mesh(quads: usize) -> AiMesh {
+        let mut mesh = AiMesh {
+            name: "Strip".to_string(),
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            ..Default::default()
+        };
+        for x in 0..=quads {
+            mesh.vertices.push(AiVector3D::new(x as AiReal, 0.0, 0.0));
+            mesh.vertices.push(AiVector3D::new(x as AiReal, 1.0, 0.0));
+        }
+        mesh.normals = vec![AiVector3D::new(0.0, 0.0, 1.0); mesh.vertices.len()];
+        for quad in 0..quads {
+            let corner = quad * 2;
+            mesh.faces.push(vec![corner, corner + 2, corner + 3]);
+            mesh.faces.push(vec![corner, corner + 3, corner + 1]);
+            mesh.bones.push(AiBone {
+                name: format!("Bone{}", quad),
+                node_index: quad + 1,
+                offset_matrix: AiMatrix4x4::from_translation(AiVector3D::new(
+                    quad as AiReal,
+                    0.0,
+                    0.0,
+                )),
+                weights: (corner..corner + 2)
+                    .map(|index| AiVertexWeight::new(index, 1.0))
+                    .collect(),
+                ..Default::default()
+            });
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_split_by_bone_count() {
+        let mut scene = AiScene {
+            meshes: vec![create_mesh(4)],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        let mut step = SplitByBoneCount { max_bones: 3 };
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::SplitByBoneCount)));
+        step.process(&mut scene).unwrap();
+
+        assert!(scene.meshes.len() > 1);
+        assert_eq!(
+            scene.nodes.arena[0].mesh_indexes,
+            (0..scene.meshes.len()).collect::<Vec<_>>()
+        );
+        let original = create_mesh(4);
+        let mut faces = 0;
+        for mesh in &scene.meshes {
+            assert!(mesh.bones.len() <= 3);
+            assert_eq!(mesh.name, "Strip");
+            assert_eq!(mesh.normals.len(), mesh.vertices.len());
+            faces += mesh.faces.len();
+            for bone in &mesh.bones {
+                let source = original
+                    .bones
+                    .iter()
+                    .find(|source| source.name == bone.name)
+                    .unwrap();
+                assert_eq!(bone.node_index, source.node_index);
+                assert_eq!(bone.offset_matrix, source.offset_matrix);
+                let positions: Vec<AiVector3D> = source
+                    .weights
+                    .iter()
+                    .map(|weight| original.vertices[weight.vertex_id])
+                    .collect();
+                for weight in &bone.weights {
+                    assert!(positions.contains(&mesh.vertices[weight.vertex_id]));
+                }
+            }
+        }
+        assert_eq!(faces, original.faces.len());
+    }
+
+    #[test]
+    fn test_split_by_bone_count_within_limit() {
+        let mut scene = AiScene {
+            meshes: vec![create_mesh(2)],
+            ..Default::default()
+        };
+        SplitByBoneCount::default().process(&mut scene).unwrap();
+        assert_eq!(scene.meshes, vec![create_mesh(2)]);
+    }
+
+    #[test]
+    fn test_split_by_bone_count_ignores_zero_weights() {
+        let mut mesh = create_mesh(4);
+        // Zero weights of every bone on the first quad
+        for bone in mesh.bones.iter_mut().skip(1) {
+            bone.weights.push(AiVertexWeight::new(0, 0.0));
+        }
+        let mut scene = AiScene {
+            meshes: vec![mesh],
+            ..Default::default()
+        };
+        SplitByBoneCount { max_bones: 2 }
+            .process(&mut scene)
+            .unwrap();
+
+        for mesh in &scene.meshes {
+            assert!(mesh.bones.len() <= 2);
+            assert!(
+                mesh.bones
+                    .iter()
+                    .flat_map(|bone| bone.weights.iter())
+                    .all(|weight| weight.weight > 0.0)
+            );
+        }
+    }
+}
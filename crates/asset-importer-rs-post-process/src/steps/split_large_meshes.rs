@@ -1,10 +1,70 @@
-use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use std::collections::HashSet;
+
+use asset_importer_rs_core::{
+    AI_CONFIG_PP_SLM_TRIANGLE_LIMIT_DEFAULT, AI_CONFIG_PP_SLM_VERTEX_LIMIT_DEFAULT, AiPostProcess,
+    AiPostProcessSteps,
+};
+use asset_importer_rs_scene::{AiMesh, AiScene};
 use enumflags2::BitFlags;
 
+use crate::helper::{extract_sub_mesh, replace_meshes, skeleton_bound_meshes};
+
 /// Split large meshes
-#[derive(Default)]
-pub struct SplitLargeMeshes;
+///
+/// Splits meshes with more than `max_vertices` vertices or `max_faces` faces into parts
+/// within both limits. Faces keep their order, every part holds the vertices its faces use
+/// along with all attribute channels, bone weights and anim meshes. Nodes reference all parts
+/// of a split mesh.
+///
+/// The default vertex limit keeps every index within 16 bits, as required by glTF 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitLargeMeshes {
+    pub max_vertices: usize,
+    pub max_faces: usize,
+}
+
+impl Default for SplitLargeMeshes {
+    fn default() -> Self {
+        Self {
+            max_vertices: AI_CONFIG_PP_SLM_VERTEX_LIMIT_DEFAULT,
+            max_faces: AI_CONFIG_PP_SLM_TRIANGLE_LIMIT_DEFAULT,
+        }
+    }
+}
+
+impl SplitLargeMeshes {
+    fn is_large(&self, mesh: &AiMesh) -> bool {
+        mesh.vertices.len() > self.max_vertices || mesh.faces.len() > self.max_faces
+    }
+
+    /// Groups consecutive faces of a mesh within the limits.
+    fn group_faces(&self, mesh: &AiMesh) -> Vec<Vec<usize>> {
+        let mut groups = Vec::new();
+        let mut faces: Vec<usize> = Vec::new();
+        let mut vertices: HashSet<usize> = HashSet::new();
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            let new_vertices = face
+                .iter()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter(|index| !vertices.contains(index))
+                .count();
+            if !faces.is_empty()
+                && (faces.len() + 1 > self.max_faces
+                    || vertices.len() + new_vertices > self.max_vertices)
+            {
+                groups.push(std::mem::take(&mut faces));
+                vertices.clear();
+            }
+            faces.push(face_index);
+            vertices.extend(face);
+        }
+        if !faces.is_empty() {
+            groups.push(faces);
+        }
+        groups
+    }
+}
 
 impl AiPostProcess for SplitLargeMeshes {
     type Error = String;
@@ -14,7 +74,163 @@ impl AiPostProcess for SplitLargeMeshes {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement large mesh splitting
+        if self.max_vertices == 0 || self.max_faces == 0 {
+            return Err("SplitLargeMeshes limits must be greater than zero".to_string());
+        }
+        if !scene.meshes.iter().any(|mesh| self.is_large(mesh)) {
+            return Ok(());
+        }
+
+        let bound = skeleton_bound_meshes(scene);
+        let mut parts = Vec::with_capacity(scene.meshes.len());
+        for (mesh, bound) in std::mem::take(&mut scene.meshes).into_iter().zip(bound) {
+            if !self.is_large(&mesh) || bound {
+                if bound && self.is_large(&mesh) {
+                    log::warn!("Mesh {} is bound to a skeleton and is not split", mesh.name);
+                }
+                let vertex_count = mesh.vertices.len();
+                parts.push(vec![(mesh, (0..vertex_count).collect())]);
+                continue;
+            }
+            let groups = self.group_faces(&mesh);
+            log::debug!(
+                "Mesh {} with {} vertices and {} faces is split into {} meshes",
+                mesh.name,
+                mesh.vertices.len(),
+                mesh.faces.len(),
+                groups.len()
+            );
+            parts.push(
+                groups
+                    .iter()
+                    .map(|faces| extract_sub_mesh(&mesh, faces))
+                    .collect(),
+            );
+        }
+        replace_meshes(scene, parts);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimMesh, AiBone, AiMatrix4x4, AiNode, AiPrimitiveType, AiReal, AiSkeleton,
+        AiSkeletonBone, AiVector3D, AiVertexWeight,
+    };
+
+    /// Creates a fan of `triangles` triangles around vertex 0.
+    fn create_fan(triangles: usize) -> AiMesh {
+        let vertices: Vec<AiVector3D> = (0..triangles + 2)
+            .map(|index| AiVector3D::new(index as AiReal, 0.0, 0.0))
+            .collect();
+        let mut mesh = AiMesh {
+            name: "Fan".to_string(),
+            primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+            normals: vertices.clone(),
+            faces: (0..triangles)
+                .map(|index| vec![0, index + 1, index + 2])
+                .collect(),
+            bones: vec![AiBone {
+                name: "Bone".to_string(),
+                weights: vec![AiVertexWeight::new(0, 1.0), AiVertexWeight::new(1, 0.5)],
+                ..Default::default()
+            }],
+            anim_meshes: vec![AiAnimMesh {
+                vertices: vertices.clone(),
+                ..Default::default()
+            }],
+            vertices,
+            ..Default::default()
+        };
+        mesh.texture_coords[0] = Some(mesh.vertices.clone());
+        mesh
+    }
+
+    #[test]
+    fn test_split_large_meshes_by_vertices() {
+        let mut scene = AiScene {
+            meshes: vec![create_fan(6), create_fan(1)],
+            ..Default::default()
+        };
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    mesh_indexes: vec![0, 1],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        let mut step = SplitLargeMeshes {
+            max_vertices: 4,
+            ..Default::default()
+        };
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::SplitLargeMeshes)));
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes.len(), 4);
+        assert_eq!(scene.nodes.arena[0].mesh_indexes, vec![0, 1, 2, 3]);
+        for mesh in &scene.meshes[..3] {
+            assert!(mesh.vertices.len() <= 4);
+            assert_eq!(mesh.faces.len(), 2);
+            assert_eq!(mesh.normals, mesh.vertices);
+            assert_eq!(mesh.texture_coords[0].as_ref(), Some(&mesh.vertices));
+            assert_eq!(mesh.anim_meshes[0].vertices, mesh.vertices);
+            // Every part contains the hub of the fan
+            assert_eq!(mesh.bones[0].weights[0], AiVertexWeight::new(0, 1.0));
+        }
+        assert_eq!(scene.meshes[0].bones[0].weights.len(), 2);
+        assert_eq!(scene.meshes[1].faces, vec![vec![0, 1, 2], vec![0, 2, 3]]);
+        assert_eq!(scene.meshes[1].vertices[1], AiVector3D::new(3.0, 0.0, 0.0));
+        assert_eq!(scene.meshes[3], create_fan(1));
+    }
+
+    #[test]
+    fn test_split_large_meshes_by_faces() {
+        let mut scene = AiScene {
+            meshes: vec![create_fan(5)],
+            ..Default::default()
+        };
+        let step = SplitLargeMeshes {
+            max_faces: 2,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        let faces: Vec<usize> = scene.meshes.iter().map(|mesh| mesh.faces.len()).collect();
+        assert_eq!(faces, vec![2, 2, 1]);
+        assert!(
+            SplitLargeMeshes {
+                max_faces: 0,
+                ..Default::default()
+            }
+            .process(&mut scene)
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_skeleton_bound_mesh_kept() {
+        let bone = AiSkeletonBone::new(None, 0, AiMatrix4x4::identity(), AiMatrix4x4::identity())
+            .with_mesh(
+                0,
+                vec![AiVertexWeight::new(0, 1.0), AiVertexWeight::new(7, 1.0)],
+            );
+        let mut scene = AiScene {
+            meshes: vec![create_fan(6)],
+            skeletons: vec![AiSkeleton::new("Skeleton".to_string(), vec![bone.clone()]).unwrap()],
+            ..Default::default()
+        };
+        let step = SplitLargeMeshes {
+            max_vertices: 4,
+            ..Default::default()
+        };
+        step.process(&mut scene).unwrap();
+
+        assert_eq!(scene.meshes, vec![create_fan(6)]);
+        assert_eq!(scene.skeletons[0].bones(), &[bone]);
+    }
+}
@@ -1,8 +1,26 @@
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{AiScene, AiTextureType, AiUvTransform, AiVector3D, matkey};
 use enumflags2::BitFlags;
 
+use crate::helper::{free_uv_channel, set_texture_property};
+
+/// Applies a UV transform to a texture coordinate.
+///
+/// The coordinate is translated, then rotated and scaled around the texture center.
+fn apply_transform(transform: &AiUvTransform, uv: &mut AiVector3D) {
+    let (sin, cos) = transform.rotation.sin_cos();
+    let x = uv.x + transform.translation.x - 0.5;
+    let y = uv.y + transform.translation.y - 0.5;
+    uv.x = (x * cos - y * sin) * transform.scaling.x + 0.5;
+    uv.y = (x * sin + y * cos) * transform.scaling.y + 0.5;
+}
+
 /// Transform UV coordinates
+///
+/// Bakes the `$tex.uvtrafo` of every texture into the texture coordinates of the meshes using the
+/// material and resets the transform. When textures reading the same channel use different
+/// transforms, the channel is duplicated into a free channel for each further transform and the
+/// `$tex.uvwsrc` of those textures is updated.
 #[derive(Default)]
 pub struct TransformUVCoords;
 
@@ -14,7 +32,258 @@ impl AiPostProcess for TransformUVCoords {
     }
 
     fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement UV coordinate transformation
+        let identity = AiUvTransform::default();
+        for (material_index, material) in scene.materials.iter_mut().enumerate() {
+            let textures: Vec<(AiTextureType, u32, usize, AiUvTransform)> = material
+                .iter()
+                .filter(|property| property.key == matkey::_AI_MATKEY_TEXTURE_BASE)
+                .map(|property| {
+                    let channel = material
+                        .get_property_as::<u32>(
+                            matkey::_AI_MATKEY_UVWSRC_BASE,
+                            Some(property.semantic),
+                            property.index,
+                        )
+                        .unwrap_or(0) as usize;
+                    let transform = material
+                        .get_property_as::<AiUvTransform>(
+                            matkey::_AI_MATKEY_UVTRANSFORM_BASE,
+                            Some(property.semantic),
+                            property.index,
+                        )
+                        .unwrap_or_default();
+                    (property.semantic, property.index, channel, transform)
+                })
+                .collect();
+            if textures
+                .iter()
+                .all(|(_, _, _, transform)| *transform == identity)
+            {
+                continue;
+            }
+            let mesh_indices: Vec<usize> = scene
+                .meshes
+                .iter()
+                .enumerate()
+                .filter(|(_, mesh)| mesh.material_index as usize == material_index)
+                .map(|(mesh_index, _)| mesh_index)
+                .collect();
+
+            // The first transform of a channel is baked in place, the others into copies
+            let mut baked: Vec<(usize, AiUvTransform, usize)> = Vec::new();
+            for (semantic, index, channel, transform) in textures {
+                let target = match baked
+                    .iter()
+                    .find(|(source, other, _)| *source == channel && *other == transform)
+                {
+                    Some((_, _, target)) => *target,
+                    None => {
+                        let target = if baked.iter().any(|(source, _, _)| *source == channel) {
+                            let Some(target) = free_uv_channel(&scene.meshes, &mesh_indices) else {
+                                log::warn!(
+                                    "No free UV channel to transform UVs of {} texture {}",
+                                    semantic,
+                                    index
+                                );
+                                continue;
+                            };
+                            for &mesh_index in &mesh_indices {
+                                let mesh = &mut scene.meshes[mesh_index];
+                                mesh.texture_coords[target] = mesh.texture_coords[channel].clone();
+                                mesh.texture_coordinate_names[target] =
+                                    mesh.texture_coordinate_names[channel].clone();
+                                for anim_mesh in mesh.anim_meshes.iter_mut() {
+                                    anim_mesh.texture_coords[target] =
+                                        anim_mesh.texture_coords[channel].clone();
+                                }
+                            }
+                            target
+                        } else {
+                            channel
+                        };
+                        if transform != identity {
+                            for &mesh_index in &mesh_indices {
+                                let mesh = &mut scene.meshes[mesh_index];
+                                let channels = std::iter::once(&mut mesh.texture_coords[target])
+                                    .chain(
+                                        mesh.anim_meshes
+                                            .iter_mut()
+                                            .map(|anim_mesh| &mut anim_mesh.texture_coords[target]),
+                                    );
+                                for uv in channels.flatten().flatten() {
+                                    apply_transform(&transform, uv);
+                                }
+                            }
+                        }
+                        baked.push((channel, transform, target));
+                        target
+                    }
+                };
+                if target != channel {
+                    log::debug!(
+                        "Moved UVs of {} texture {} to channel {}",
+                        semantic,
+                        index,
+                        target
+                    );
+                    set_texture_property(
+                        material,
+                        matkey::_AI_MATKEY_UVWSRC_BASE,
+                        semantic,
+                        index,
+                        (target as u32).to_le_bytes().to_vec(),
+                    );
+                }
+                if transform != identity {
+                    set_texture_property(
+                        material,
+                        matkey::_AI_MATKEY_UVTRANSFORM_BASE,
+                        semantic,
+                        index,
+                        bytemuck::bytes_of(&identity).to_vec(),
+                    );
+                }
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{AI_MATH_PI, AiMaterial, AiMesh, AiPropertyTypeInfo, AiVector2D};
+
+    fn add_texture(material: &mut AiMaterial, semantic: AiTextureType, transform: AiUvTransform) {
+        material.add_property(
+            matkey::_AI_MATKEY_TEXTURE_BASE,
+            Some(semantic),
+            AiPropertyTypeInfo::Binary,
+            0,
+            b"texture.png".to_vec(),
+        );
+        material.add_property(
+            matkey::_AI_MATKEY_UVTRANSFORM_BASE,
+            Some(semantic),
+            AiPropertyTypeInfo::Binary,
+            0,
+            bytemuck::bytes_of(&transform).to_vec(),
+        );
+    }
+
+    fn create_scene(material: AiMaterial) -> AiScene {
+        let mesh = AiMesh {
+            vertices: vec![AiVector3D::zero(); 2],
+            faces: vec![vec![0, 1]],
+            texture_coords: [
+                Some(vec![
+                    AiVector3D::new(0.0, 0.0, 0.0),
+                    AiVector3D::new(1.0, 1.0, 0.0),
+                ]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ],
+            ..Default::default()
+        };
+        AiScene {
+            meshes: vec![mesh],
+            materials: vec![material],
+            ..Default::default()
+        }
+    }
+
+    fn get_transform(scene: &AiScene, semantic: AiTextureType) -> Option<AiUvTransform> {
+        scene.materials[0].get_property_as::<AiUvTransform>(
+            matkey::_AI_MATKEY_UVTRANSFORM_BASE,
+            Some(semantic),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_bake_transform() {
+        let mut material = AiMaterial::new();
+        let transform = AiUvTransform {
+            translation: AiVector2D::new(0.5, 0.0),
+            scaling: AiVector2D::new(2.0, 2.0),
+            rotation: 0.0,
+        };
+        add_texture(&mut material, AiTextureType::Diffuse, transform);
+        let mut scene = create_scene(material);
+        assert!(TransformUVCoords.process(&mut scene).is_ok());
+
+        assert_eq!(
+            scene.meshes[0].texture_coords[0],
+            Some(vec![
+                AiVector3D::new(0.5, -0.5, 0.0),
+                AiVector3D::new(2.5, 1.5, 0.0),
+            ])
+        );
+        assert_eq!(
+            get_transform(&scene, AiTextureType::Diffuse),
+            Some(AiUvTransform::default())
+        );
+    }
+
+    #[test]
+    fn test_rotation_around_center() {
+        let mut uv = AiVector3D::new(1.0, 0.5, 0.0);
+        let transform = AiUvTransform {
+            rotation: AI_MATH_PI / 2.0,
+            ..Default::default()
+        };
+        apply_transform(&transform, &mut uv);
+        assert!((uv.x - 0.5).abs() < 1e-5);
+        assert!((uv.y - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_duplicate_channel() {
+        let mut material = AiMaterial::new();
+        add_texture(
+            &mut material,
+            AiTextureType::Diffuse,
+            AiUvTransform::default(),
+        );
+        let transform = AiUvTransform {
+            translation: AiVector2D::new(1.0, 1.0),
+            ..Default::default()
+        };
+        add_texture(&mut material, AiTextureType::Normals, transform);
+        let mut scene = create_scene(material);
+        assert!(TransformUVCoords.process(&mut scene).is_ok());
+
+        let mesh = &scene.meshes[0];
+        assert_eq!(
+            mesh.texture_coords[0],
+            Some(vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 1.0, 0.0),
+            ])
+        );
+        assert_eq!(
+            mesh.texture_coords[1],
+            Some(vec![
+                AiVector3D::new(1.0, 1.0, 0.0),
+                AiVector3D::new(2.0, 2.0, 0.0),
+            ])
+        );
+        assert_eq!(
+            scene.materials[0].get_property_as::<u32>(
+                matkey::_AI_MATKEY_UVWSRC_BASE,
+                Some(AiTextureType::Normals),
+                0
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            get_transform(&scene, AiTextureType::Normals),
+            Some(AiUvTransform::default())
+        );
+    }
+}
@@ -1,10 +1,86 @@
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{AiFace, AiMesh, AiPrimitiveType, AiReal, AiScene, AiVector3D};
 use enumflags2::BitFlags;
 
 /// Triangulate all meshes
-#[derive(Default)]
-pub struct Triangulate;
+///
+/// Polygons are projected onto their best-fit plane and split by ear clipping, which handles
+/// concave polygons and collinear points. Self-intersecting polygons have no proper
+/// triangulation; they are still split into `n - 2` triangles covering all their vertices.
+/// Lines and points are kept as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Triangulate {
+    /// Encode the triangles of every polygon with `AiPrimitiveType::NgonEncodingFlag`.
+    ///
+    /// Consecutive triangles sharing their first index belong to the same polygon. Polygons
+    /// whose triangles have no vertex in common are encoded as independent triangles.
+    pub ngon_encoding: bool,
+}
+
+impl Default for Triangulate {
+    fn default() -> Self {
+        Self {
+            ngon_encoding: true,
+        }
+    }
+}
+
+impl Triangulate {
+    fn process_mesh(&self, mesh: &mut AiMesh) -> Result<bool, String> {
+        if !mesh.faces.iter().any(|face| face.len() > 3) {
+            return Ok(false);
+        }
+
+        let mut faces: Vec<AiFace> = Vec::with_capacity(mesh.faces.len() * 2);
+        let mut last_ngon_first: Option<usize> = None;
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            if let Some(&index) = face.iter().find(|&&index| index >= mesh.vertices.len()) {
+                return Err(format!(
+                    "Face {} of mesh {} references vertex {} but the mesh has {} vertices",
+                    face_index,
+                    mesh.name,
+                    index,
+                    mesh.vertices.len()
+                ));
+            }
+
+            match face.len() {
+                0..=2 => faces.push(face.clone()),
+                3 => {
+                    let mut triangle = face.clone();
+                    if self.ngon_encoding {
+                        encode_triangle(&mut triangle, &mut last_ngon_first);
+                    }
+                    faces.push(triangle);
+                }
+                _ => {
+                    let positions: Vec<AiVector3D> =
+                        face.iter().map(|&index| mesh.vertices[index]).collect();
+                    let mut triangles: Vec<AiFace> = triangulate_polygon(&positions)
+                        .into_iter()
+                        .map(|[a, b, c]| vec![face[a], face[b], face[c]])
+                        .collect();
+                    if self.ngon_encoding {
+                        encode_polygon(&mut triangles, &mut last_ngon_first);
+                    }
+                    faces.extend(triangles);
+                }
+            }
+        }
+        mesh.faces = faces;
+
+        let mut primitive_types = BitFlags::empty();
+        for face in &mesh.faces {
+            primitive_types |= AiPrimitiveType::primitive_type_for_n_indices(face.len());
+        }
+        primitive_types.remove(AiPrimitiveType::None);
+        if self.ngon_encoding {
+            primitive_types |= AiPrimitiveType::NgonEncodingFlag;
+        }
+        mesh.primitive_types = primitive_types;
+        Ok(true)
+    }
+}
 
 impl AiPostProcess for Triangulate {
     type Error = String;
@@ -13,8 +89,293 @@ impl AiPostProcess for Triangulate {
         steps.contains(AiPostProcessSteps::Triangulate)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement triangulation
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        for mesh in scene.meshes.iter_mut() {
+            self.process_mesh(mesh)?;
+        }
         Ok(())
     }
 }
+
+/// Rotates a standalone triangle so it does not continue the previous polygon.
+fn encode_triangle(triangle: &mut AiFace, last_ngon_first: &mut Option<usize>) {
+    for _ in 0..2 {
+        if Some(triangle[0]) != *last_ngon_first {
+            break;
+        }
+        triangle.rotate_left(1);
+    }
+    *last_ngon_first = Some(triangle[0]);
+}
+
+/// Rotates the triangles of a polygon so they all start with a shared vertex.
+fn encode_polygon(triangles: &mut [AiFace], last_ngon_first: &mut Option<usize>) {
+    let Some(first) = triangles.first() else {
+        return;
+    };
+    let shared = first.iter().copied().find(|&index| {
+        Some(index) != *last_ngon_first && triangles.iter().all(|tri| tri.contains(&index))
+    });
+    match shared {
+        Some(shared) => {
+            for triangle in triangles.iter_mut() {
+                while triangle[0] != shared {
+                    triangle.rotate_left(1);
+                }
+            }
+            *last_ngon_first = Some(shared);
+        }
+        None => {
+            for triangle in triangles.iter_mut() {
+                encode_triangle(triangle, last_ngon_first);
+            }
+        }
+    }
+}
+
+/// Triangulates a polygon, returning triangles as indices into `positions`.
+///
+/// The triangles keep the winding order of the polygon.
+fn triangulate_polygon(positions: &[AiVector3D]) -> Vec<[usize; 3]> {
+    let count = positions.len();
+
+    // Newell's method gives the normal of the best-fit plane, oriented by the winding order
+    let mut normal = AiVector3D::zero();
+    for (i, current) in positions.iter().enumerate() {
+        let next = &positions[(i + 1) % count];
+        normal.x += (current.y - next.y) * (current.z + next.z);
+        normal.y += (current.z - next.z) * (current.x + next.x);
+        normal.z += (current.x - next.x) * (current.y + next.y);
+    }
+    if normal.square_length() == 0.0 {
+        // All points are collinear or coincident, any split is as good as another
+        return (1..count - 1).map(|i| [0, i, i + 1]).collect();
+    }
+    let normal = normal.norm();
+
+    // Build a basis of the plane such that u x v = normal, keeping the polygon counter-clockwise
+    let axis = if normal.x.abs() <= normal.y.abs() && normal.x.abs() <= normal.z.abs() {
+        AiVector3D::new(1.0, 0.0, 0.0)
+    } else if normal.y.abs() <= normal.z.abs() {
+        AiVector3D::new(0.0, 1.0, 0.0)
+    } else {
+        AiVector3D::new(0.0, 0.0, 1.0)
+    };
+    let u = normal.cross(&axis).norm();
+    let v = normal.cross(&u);
+    let points: Vec<[AiReal; 2]> = positions.iter().map(|p| [p * u, p * v]).collect();
+
+    let mut min = [AiReal::MAX; 2];
+    let mut max = [AiReal::MIN; 2];
+    for point in &points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    let extent = (max[0] - min[0]).max(max[1] - min[1]);
+    let epsilon = extent * extent * 1e-6;
+
+    let mut remaining: Vec<usize> = (0..count).collect();
+    let mut triangles = Vec::with_capacity(count - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corner = |i: usize| {
+            (
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            )
+        };
+
+        let ear = (0..len).find(|&i| {
+            let (prev, current, next) = corner(i);
+            let (a, b, c) = (points[prev], points[current], points[next]);
+            if cross(a, b, c) <= epsilon {
+                return false;
+            }
+            !remaining.iter().any(|&other| {
+                let p = points[other];
+                other != prev
+                    && other != current
+                    && other != next
+                    && p != a
+                    && p != b
+                    && p != c
+                    && cross(a, b, p) >= -epsilon
+                    && cross(b, c, p) >= -epsilon
+                    && cross(c, a, p) >= -epsilon
+            })
+        });
+
+        // Self-intersecting polygons or numerical issues may leave no proper ear, fall back to
+        // the most convex corner so that the polygon is always fully split
+        let ear = ear.unwrap_or_else(|| {
+            (0..len)
+                .max_by(|&lhs, &rhs| {
+                    let (a, b, c) = corner(lhs);
+                    let (d, e, f) = corner(rhs);
+                    cross(points[a], points[b], points[c])
+                        .total_cmp(&cross(points[d], points[e], points[f]))
+                })
+                .unwrap_or(0)
+        });
+
+        let (prev, current, next) = corner(ear);
+        triangles.push([prev, current, next]);
+        remaining.remove(ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`, positive if counter-clockwise.
+fn cross(a: [AiReal; 2], b: [AiReal; 2], c: [AiReal; 2]) -> AiReal {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle_area(mesh: &AiMesh) -> AiReal {
+        mesh.faces
+            .iter()
+            .map(|face| {
+                let a = mesh.vertices[face[0]];
+                let b = mesh.vertices[face[1]];
+                let c = mesh.vertices[face[2]];
+                (b - a).cross(&(c - a)).z * 0.5
+            })
+            .sum()
+    }
+
+    fn create_mesh(vertices: Vec<AiVector3D>) -> AiMesh {
+        let face: AiFace = (0..vertices.len()).collect();
+        AiMesh {
+            vertices,
+            faces: vec![face],
+            primitive_types: BitFlags::from(AiPrimitiveType::Polygon),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_triangulate_concave_polygon() {
+        // An L shape with an area of 3, its corner at (1, 1) is reflex
+        let mut mesh = create_mesh(vec![
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 1.0, 0.0),
+            AiVector3D::new(1.0, 1.0, 0.0),
+            AiVector3D::new(1.0, 2.0, 0.0),
+            AiVector3D::new(0.0, 2.0, 0.0),
+        ]);
+        let triangulate = Triangulate {
+            ngon_encoding: false,
+        };
+        assert!(triangulate.process_mesh(&mut mesh).unwrap());
+
+        assert_eq!(mesh.faces.len(), 4);
+        assert!(mesh.faces.iter().all(|face| face.len() == 3));
+        assert_eq!(
+            mesh.primitive_types,
+            BitFlags::from(AiPrimitiveType::Triangle)
+        );
+        // Every triangle keeps the counter-clockwise winding, so no area is covered twice
+        for face in &mesh.faces {
+            let a = mesh.vertices[face[0]];
+            let b = mesh.vertices[face[1]];
+            let c = mesh.vertices[face[2]];
+            assert!((b - a).cross(&(c - a)).z > 0.0);
+        }
+        assert!((triangle_area(&mesh) - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_triangulate_collinear_points() {
+        let mut mesh = create_mesh(vec![
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(1.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 2.0, 0.0),
+            AiVector3D::new(0.0, 2.0, 0.0),
+        ]);
+        Triangulate::default().process_mesh(&mut mesh).unwrap();
+        assert_eq!(mesh.faces.len(), 3);
+        assert!((triangle_area(&mesh) - 4.0).abs() < 1e-5);
+
+        // All points on a line still produce n - 2 triangles
+        let mut mesh = create_mesh(vec![
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(1.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 0.0, 0.0),
+            AiVector3D::new(3.0, 0.0, 0.0),
+        ]);
+        Triangulate::default().process_mesh(&mut mesh).unwrap();
+        assert_eq!(mesh.faces.len(), 2);
+    }
+
+    #[test]
+    fn test_triangulate_self_intersecting_polygon() {
+        // A bow tie
+        let mut mesh = create_mesh(vec![
+            AiVector3D::new(0.0, 0.0, 0.0),
+            AiVector3D::new(2.0, 2.0, 0.0),
+            AiVector3D::new(2.0, 0.0, 0.0),
+            AiVector3D::new(0.0, 2.0, 0.0),
+            AiVector3D::new(-1.0, 1.0, 0.0),
+        ]);
+        Triangulate::default().process_mesh(&mut mesh).unwrap();
+        assert_eq!(mesh.faces.len(), 3);
+        let mut used: Vec<usize> = mesh.faces.iter().flatten().copied().collect();
+        used.sort_unstable();
+        used.dedup();
+        assert_eq!(used, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_triangulate_ngon_encoding() {
+        let mut mesh = AiMesh {
+            vertices: vec![
+                AiVector3D::new(0.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 0.0, 0.0),
+                AiVector3D::new(1.0, 1.0, 0.0),
+                AiVector3D::new(0.0, 1.0, 0.0),
+                AiVector3D::new(0.0, 0.0, 1.0),
+                AiVector3D::new(0.0, 0.0, 2.0),
+            ],
+            faces: vec![vec![0, 1, 2, 3], vec![0, 4, 5], vec![0, 1]],
+            primitive_types: AiPrimitiveType::Polygon
+                | AiPrimitiveType::Triangle
+                | AiPrimitiveType::Line,
+            ..Default::default()
+        };
+        Triangulate::default().process_mesh(&mut mesh).unwrap();
+
+        assert_eq!(mesh.faces.len(), 4);
+        assert_eq!(mesh.faces[0][0], mesh.faces[1][0]);
+        // The standalone triangle must not read as part of the quad
+        assert_ne!(mesh.faces[2][0], mesh.faces[1][0]);
+        assert_eq!(mesh.faces[3], vec![0, 1]);
+        assert_eq!(
+            mesh.primitive_types,
+            AiPrimitiveType::Triangle | AiPrimitiveType::Line | AiPrimitiveType::NgonEncodingFlag
+        );
+    }
+
+    #[test]
+    fn test_triangulate_invalid_index() {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                vertices: vec![AiVector3D::zero(); 3],
+                faces: vec![vec![0, 1, 2, 3]],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut triangulate = Triangulate::default();
+        assert!(triangulate.prepare(BitFlags::from(AiPostProcessSteps::Triangulate)));
+        assert!(triangulate.process(&mut scene).is_err());
+    }
+}
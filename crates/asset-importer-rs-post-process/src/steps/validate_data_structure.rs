@@ -1,20 +1,750 @@
+use std::collections::HashSet;
+
 use asset_importer_rs_core::{AiPostProcess, AiPostProcessSteps};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_scene::{
+    AiMeshAnimError, AiPrimitiveType, AiReal, AiScene, AiSceneFlag, AiSkeletonError, matkey,
+};
 use enumflags2::BitFlags;
 
+/// A problem found in a scene by [`ValidateDataStructure`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// A mesh without vertices
+    MeshWithoutVertices { mesh: usize },
+    /// A face without indices
+    EmptyFace { mesh: usize, face: usize },
+    /// A face index past the end of the vertex array
+    FaceIndexOutOfRange {
+        mesh: usize,
+        face: usize,
+        index: usize,
+        vertex_count: usize,
+    },
+    /// A face whose primitive type is missing from `primitive_types`
+    PrimitiveTypeMismatch {
+        mesh: usize,
+        face: usize,
+        primitive_type: AiPrimitiveType,
+    },
+    /// A per-vertex attribute whose length differs from the vertex count
+    AttributeLength {
+        mesh: usize,
+        attribute: String,
+        len: usize,
+        vertex_count: usize,
+    },
+    /// A bone weight referencing a vertex past the end of the vertex array
+    BoneWeightOutOfRange {
+        mesh: usize,
+        bone: usize,
+        vertex: usize,
+    },
+    /// A bone referencing a node that does not exist
+    BoneNodeOutOfRange {
+        mesh: usize,
+        bone: usize,
+        node: usize,
+    },
+    /// The weights of a vertex do not sum up to one (warning)
+    BoneWeightSum {
+        mesh: usize,
+        vertex: usize,
+        sum: AiReal,
+    },
+    /// A material index past the end of the material array
+    MaterialIndexOutOfRange {
+        mesh: usize,
+        material_index: u32,
+        material_count: usize,
+    },
+    /// The root node is out of range or has a parent
+    InvalidRoot { root: usize },
+    /// A parent index past the end of the node arena, or a parent that does not list the node
+    InvalidParent { node: usize, parent: usize },
+    /// A node without a parent that is not the root
+    OrphanNode { node: usize },
+    /// A child index past the end of the node arena, or a child whose parent is another node
+    InvalidChild { node: usize, child: usize },
+    /// A node reachable through more than one path, or part of a cycle
+    NodeVisitedTwice { node: usize },
+    /// A node mesh index past the end of the mesh array
+    NodeMeshOutOfRange { node: usize, mesh: usize },
+    /// An animation channel naming a node that does not exist
+    UnknownChannelNode { animation: usize, node_name: String },
+    /// Animation keys that are not sorted by time (warning)
+    UnorderedChannelKeys { animation: usize, node_name: String },
+    /// An invalid mesh animation channel
+    MeshChannel {
+        animation: usize,
+        channel: usize,
+        error: AiMeshAnimError,
+    },
+    /// An invalid skeleton
+    Skeleton {
+        skeleton: usize,
+        error: AiSkeletonError,
+    },
+    /// An embedded texture reference (`*N`) that does not resolve
+    TextureReference { material: usize, path: String },
+}
+
+impl ValidationIssue {
+    /// Warnings flag the scene with `AiSceneFlag::ValidationWarning`, every other issue fails
+    /// the validation.
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            ValidationIssue::BoneWeightSum { .. } | ValidationIssue::UnorderedChannelKeys { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::MeshWithoutVertices { mesh } => {
+                write!(f, "Mesh {} has no vertices", mesh)
+            }
+            ValidationIssue::EmptyFace { mesh, face } => {
+                write!(f, "Face {} of mesh {} has no indices", face, mesh)
+            }
+            ValidationIssue::FaceIndexOutOfRange {
+                mesh,
+                face,
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "Face {} of mesh {} references vertex {} but the mesh has {} vertices",
+                face, mesh, index, vertex_count
+            ),
+            ValidationIssue::PrimitiveTypeMismatch {
+                mesh,
+                face,
+                primitive_type,
+            } => write!(
+                f,
+                "Face {} of mesh {} is a {:?} but the mesh primitive types do not contain it",
+                face, mesh, primitive_type
+            ),
+            ValidationIssue::AttributeLength {
+                mesh,
+                attribute,
+                len,
+                vertex_count,
+            } => write!(
+                f,
+                "Mesh {} has {} {} for {} vertices",
+                mesh, len, attribute, vertex_count
+            ),
+            ValidationIssue::BoneWeightOutOfRange { mesh, bone, vertex } => write!(
+                f,
+                "Bone {} of mesh {} weights vertex {} which does not exist",
+                bone, mesh, vertex
+            ),
+            ValidationIssue::BoneNodeOutOfRange { mesh, bone, node } => write!(
+                f,
+                "Bone {} of mesh {} references node {} which does not exist",
+                bone, mesh, node
+            ),
+            ValidationIssue::BoneWeightSum { mesh, vertex, sum } => write!(
+                f,
+                "The weights of vertex {} of mesh {} sum up to {}",
+                vertex, mesh, sum
+            ),
+            ValidationIssue::MaterialIndexOutOfRange {
+                mesh,
+                material_index,
+                material_count,
+            } => write!(
+                f,
+                "Mesh {} uses material {} but the scene has {} materials",
+                mesh, material_index, material_count
+            ),
+            ValidationIssue::InvalidRoot { root } => {
+                write!(f, "Root node {} does not exist or has a parent", root)
+            }
+            ValidationIssue::InvalidParent { node, parent } => write!(
+                f,
+                "Node {} has parent {} which does not list it as a child",
+                node, parent
+            ),
+            ValidationIssue::OrphanNode { node } => {
+                write!(f, "Node {} has no parent but is not the root", node)
+            }
+            ValidationIssue::InvalidChild { node, child } => write!(
+                f,
+                "Node {} has child {} which does not list it as its parent",
+                node, child
+            ),
+            ValidationIssue::NodeVisitedTwice { node } => {
+                write!(f, "Node {} is reachable more than once from the root", node)
+            }
+            ValidationIssue::NodeMeshOutOfRange { node, mesh } => {
+                write!(
+                    f,
+                    "Node {} references mesh {} which does not exist",
+                    node, mesh
+                )
+            }
+            ValidationIssue::UnknownChannelNode {
+                animation,
+                node_name,
+            } => write!(
+                f,
+                "Animation {} has a channel for node {} which does not exist",
+                animation, node_name
+            ),
+            ValidationIssue::UnorderedChannelKeys {
+                animation,
+                node_name,
+            } => write!(
+                f,
+                "Animation {} has keys for node {} that are not sorted by time",
+                animation, node_name
+            ),
+            ValidationIssue::MeshChannel {
+                animation,
+                channel,
+                error,
+            } => write!(
+                f,
+                "Mesh channel {} of animation {} is invalid: {}",
+                channel, animation, error
+            ),
+            ValidationIssue::Skeleton { skeleton, error } => {
+                write!(f, "Skeleton {} is invalid: {}", skeleton, error)
+            }
+            ValidationIssue::TextureReference { material, path } => write!(
+                f,
+                "Material {} references embedded texture {} which does not exist",
+                material, path
+            ),
+        }
+    }
+}
+
+/// Errors found by [`ValidateDataStructure`], warnings are only logged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidateDataStructureError {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl std::fmt::Display for ValidateDataStructureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The scene is invalid ({} errors)", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidateDataStructureError {}
+
 /// Validate data structure
+///
+/// Checks meshes, bones, materials, the node hierarchy, animations, skeletons and embedded
+/// texture references. A valid scene is flagged with `AiSceneFlag::Validated`, and also with
+/// `AiSceneFlag::ValidationWarning` if there were warnings.
 #[derive(Default)]
 pub struct ValidateDataStructure;
 
+impl ValidateDataStructure {
+    /// Returns every issue of the scene, errors and warnings alike.
+    pub fn validate(&self, scene: &AiScene) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        validate_meshes(scene, &mut issues);
+        validate_nodes(scene, &mut issues);
+        validate_animations(scene, &mut issues);
+        for (skeleton_index, skeleton) in scene.skeletons.iter().enumerate() {
+            if let Err(error) = skeleton.validate(scene.nodes.arena.len(), &scene.meshes) {
+                issues.push(ValidationIssue::Skeleton {
+                    skeleton: skeleton_index,
+                    error,
+                });
+            }
+        }
+        validate_textures(scene, &mut issues);
+        issues
+    }
+}
+
+fn validate_meshes(scene: &AiScene, issues: &mut Vec<ValidationIssue>) {
+    for (mesh_index, mesh) in scene.meshes.iter().enumerate() {
+        let vertex_count = mesh.vertices.len();
+        if vertex_count == 0 {
+            issues.push(ValidationIssue::MeshWithoutVertices { mesh: mesh_index });
+        }
+
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            if face.is_empty() {
+                issues.push(ValidationIssue::EmptyFace {
+                    mesh: mesh_index,
+                    face: face_index,
+                });
+                continue;
+            }
+            let primitive_type = AiPrimitiveType::primitive_type_for_n_indices(face.len());
+            if !mesh.primitive_types.contains(primitive_type) {
+                issues.push(ValidationIssue::PrimitiveTypeMismatch {
+                    mesh: mesh_index,
+                    face: face_index,
+                    primitive_type,
+                });
+            }
+            if let Some(&index) = face.iter().find(|&&index| index >= vertex_count) {
+                issues.push(ValidationIssue::FaceIndexOutOfRange {
+                    mesh: mesh_index,
+                    face: face_index,
+                    index,
+                    vertex_count,
+                });
+            }
+        }
+
+        let mut check_length = |attribute: String, len: usize, allow_empty: bool| {
+            if len != vertex_count && !(allow_empty && len == 0) {
+                issues.push(ValidationIssue::AttributeLength {
+                    mesh: mesh_index,
+                    attribute,
+                    len,
+                    vertex_count,
+                });
+            }
+        };
+        check_length("normals".to_string(), mesh.normals.len(), true);
+        check_length("tangents".to_string(), mesh.tangents.len(), true);
+        check_length("bitangents".to_string(), mesh.bi_tangents.len(), true);
+        for (channel, colors) in mesh.colors.iter().enumerate() {
+            if let Some(colors) = colors {
+                check_length(
+                    format!("colors in channel {}", channel),
+                    colors.len(),
+                    false,
+                );
+            }
+        }
+        for (channel, coords) in mesh.texture_coords.iter().enumerate() {
+            if let Some(coords) = coords {
+                check_length(
+                    format!("texture coordinates in channel {}", channel),
+                    coords.len(),
+                    false,
+                );
+            }
+        }
+        for (anim_mesh_index, anim_mesh) in mesh.anim_meshes.iter().enumerate() {
+            let prefix = format!("anim mesh {}", anim_mesh_index);
+            check_length(
+                format!("{} vertices", prefix),
+                anim_mesh.vertices.len(),
+                true,
+            );
+            check_length(format!("{} normals", prefix), anim_mesh.normals.len(), true);
+            check_length(
+                format!("{} tangents", prefix),
+                anim_mesh.tangents.len(),
+                true,
+            );
+            check_length(
+                format!("{} bitangents", prefix),
+                anim_mesh.bi_tangents.len(),
+                true,
+            );
+            for (channel, colors) in anim_mesh.colors.iter().enumerate() {
+                if let Some(colors) = colors {
+                    check_length(
+                        format!("{} colors in channel {}", prefix, channel),
+                        colors.len(),
+                        false,
+                    );
+                }
+            }
+            for (channel, coords) in anim_mesh.texture_coords.iter().enumerate() {
+                if let Some(coords) = coords {
+                    check_length(
+                        format!("{} texture coordinates in channel {}", prefix, channel),
+                        coords.len(),
+                        false,
+                    );
+                }
+            }
+        }
+
+        let mut weight_sums: Vec<Option<AiReal>> = vec![None; vertex_count];
+        for (bone_index, bone) in mesh.bones.iter().enumerate() {
+            if bone.node_index >= scene.nodes.arena.len() {
+                issues.push(ValidationIssue::BoneNodeOutOfRange {
+                    mesh: mesh_index,
+                    bone: bone_index,
+                    node: bone.node_index,
+                });
+            }
+            for weight in &bone.weights {
+                match weight_sums.get_mut(weight.vertex_id) {
+                    Some(sum) => *sum = Some(sum.unwrap_or(0.0) + weight.weight),
+                    None => issues.push(ValidationIssue::BoneWeightOutOfRange {
+                        mesh: mesh_index,
+                        bone: bone_index,
+                        vertex: weight.vertex_id,
+                    }),
+                }
+            }
+        }
+        for (vertex, sum) in weight_sums.into_iter().enumerate() {
+            if let Some(sum) = sum
+                && (sum - 1.0).abs() > 0.01
+            {
+                issues.push(ValidationIssue::BoneWeightSum {
+                    mesh: mesh_index,
+                    vertex,
+                    sum,
+                });
+            }
+        }
+
+        if mesh.material_index as usize >= scene.materials.len() {
+            issues.push(ValidationIssue::MaterialIndexOutOfRange {
+                mesh: mesh_index,
+                material_index: mesh.material_index,
+                material_count: scene.materials.len(),
+            });
+        }
+    }
+}
+
+fn validate_nodes(scene: &AiScene, issues: &mut Vec<ValidationIssue>) {
+    let arena = &scene.nodes.arena;
+    let root = scene.nodes.root;
+    if let Some(root) = root
+        && arena.get(root).is_none_or(|node| node.parent.is_some())
+    {
+        issues.push(ValidationIssue::InvalidRoot { root });
+    }
+
+    for (node_index, node) in arena.iter().enumerate() {
+        match node.parent {
+            Some(parent) => {
+                if arena
+                    .get(parent)
+                    .is_none_or(|parent| !parent.children.contains(&node_index))
+                {
+                    issues.push(ValidationIssue::InvalidParent {
+                        node: node_index,
+                        parent,
+                    });
+                }
+            }
+            None => {
+                if root != Some(node_index) {
+                    issues.push(ValidationIssue::OrphanNode { node: node_index });
+                }
+            }
+        }
+        for &child in &node.children {
+            if arena
+                .get(child)
+                .is_none_or(|child| child.parent != Some(node_index))
+            {
+                issues.push(ValidationIssue::InvalidChild {
+                    node: node_index,
+                    child,
+                });
+            }
+        }
+        for &mesh in &node.mesh_indexes {
+            if mesh >= scene.meshes.len() {
+                issues.push(ValidationIssue::NodeMeshOutOfRange {
+                    node: node_index,
+                    mesh,
+                });
+            }
+        }
+    }
+
+    // Walk the hierarchy to find nodes shared by several parents and cycles
+    let Some(root) = root.filter(|&root| root < arena.len()) else {
+        return;
+    };
+    let mut visited = vec![false; arena.len()];
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if visited[node] {
+            issues.push(ValidationIssue::NodeVisitedTwice { node });
+            continue;
+        }
+        visited[node] = true;
+        stack.extend(
+            arena[node]
+                .children
+                .iter()
+                .copied()
+                .filter(|&child| child < arena.len()),
+        );
+    }
+}
+
+fn validate_animations(scene: &AiScene, issues: &mut Vec<ValidationIssue>) {
+    let node_names: HashSet<&str> = scene
+        .nodes
+        .arena
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect();
+    for (animation_index, animation) in scene.animations.iter().enumerate() {
+        for channel in &animation.channels {
+            if !node_names.contains(channel.node_name.as_str()) {
+                issues.push(ValidationIssue::UnknownChannelNode {
+                    animation: animation_index,
+                    node_name: channel.node_name.clone(),
+                });
+            }
+            let sorted = channel
+                .position_keys
+                .windows(2)
+                .all(|keys| keys[0].time <= keys[1].time)
+                && channel
+                    .rotation_keys
+                    .windows(2)
+                    .all(|keys| keys[0].time <= keys[1].time)
+                && channel
+                    .scaling_keys
+                    .windows(2)
+                    .all(|keys| keys[0].time <= keys[1].time);
+            if !sorted {
+                issues.push(ValidationIssue::UnorderedChannelKeys {
+                    animation: animation_index,
+                    node_name: channel.node_name.clone(),
+                });
+            }
+        }
+        for (channel_index, channel) in animation.mesh_channels.iter().enumerate() {
+            if let Err(error) = channel.validate(&scene.meshes) {
+                issues.push(ValidationIssue::MeshChannel {
+                    animation: animation_index,
+                    channel: channel_index,
+                    error,
+                });
+            }
+        }
+    }
+}
+
+fn validate_textures(scene: &AiScene, issues: &mut Vec<ValidationIssue>) {
+    for (material_index, material) in scene.materials.iter().enumerate() {
+        for property in material.iter() {
+            if property.key != matkey::_AI_MATKEY_TEXTURE_BASE {
+                continue;
+            }
+            let Ok(path) = std::str::from_utf8(&property.data) else {
+                continue;
+            };
+            let Some(index) = path.strip_prefix('*') else {
+                continue;
+            };
+            if index
+                .parse::<usize>()
+                .ok()
+                .is_none_or(|index| index >= scene.textures.len())
+            {
+                issues.push(ValidationIssue::TextureReference {
+                    material: material_index,
+                    path: path.to_string(),
+                });
+            }
+        }
+    }
+}
+
 impl AiPostProcess for ValidateDataStructure {
-    type Error = String;
+    type Error = ValidateDataStructureError;
 
     fn prepare(&mut self, steps: BitFlags<AiPostProcessSteps>) -> bool {
         steps.contains(AiPostProcessSteps::ValidateDataStructure)
     }
 
-    fn process(&self, _scene: &mut AiScene) -> Result<(), Self::Error> {
-        // TODO: Implement data structure validation
+    fn process(&self, scene: &mut AiScene) -> Result<(), Self::Error> {
+        let (warnings, errors): (Vec<ValidationIssue>, Vec<ValidationIssue>) = self
+            .validate(scene)
+            .into_iter()
+            .partition(ValidationIssue::is_warning);
+        for warning in &warnings {
+            log::warn!("{}", warning);
+        }
+        if !errors.is_empty() {
+            return Err(ValidateDataStructureError { issues: errors });
+        }
+
+        scene.flags |= AiSceneFlag::Validated;
+        if !warnings.is_empty() {
+            scene.flags |= AiSceneFlag::ValidationWarning;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asset_importer_rs_scene::{
+        AiAnimation, AiBone, AiMaterial, AiMesh, AiNode, AiNodeAnim, AiPropertyTypeInfo,
+        AiTextureType, AiVertexWeight,
+    };
+
+    fn create_valid_scene() -> AiScene {
+        let mut scene = AiScene {
+            meshes: vec![AiMesh {
+                vertices: vec![[0.0, 0.0, 0.0].into(); 3],
+                normals: vec![[0.0, 0.0, 1.0].into(); 3],
+                faces: vec![vec![0, 1, 2]],
+                primitive_types: BitFlags::from(AiPrimitiveType::Triangle),
+                bones: vec![AiBone {
+                    weights: vec![AiVertexWeight::new(0, 1.0)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            materials: vec![AiMaterial::new()],
+            ..Default::default()
+        };
+        let root = scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: "Root".to_string(),
+                    mesh_indexes: vec![0],
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        scene
+            .nodes
+            .insert(
+                AiNode {
+                    name: "Child".to_string(),
+                    ..Default::default()
+                },
+                Some(root),
+            )
+            .unwrap();
+        scene
+    }
+
+    #[test]
+    fn test_validate_valid_scene() {
+        let mut scene = create_valid_scene();
+        let mut step = ValidateDataStructure;
+        assert!(step.prepare(BitFlags::from(AiPostProcessSteps::ValidateDataStructure)));
+        step.process(&mut scene).unwrap();
+        assert!(scene.flags.contains(AiSceneFlag::Validated));
+        assert!(!scene.flags.contains(AiSceneFlag::ValidationWarning));
+    }
+
+    #[test]
+    fn test_validate_warnings() {
+        let mut scene = create_valid_scene();
+        scene.meshes[0].bones[0].weights[0].weight = 0.5;
+        ValidateDataStructure.process(&mut scene).unwrap();
+        assert!(scene.flags.contains(AiSceneFlag::Validated));
+        assert!(scene.flags.contains(AiSceneFlag::ValidationWarning));
+    }
+
+    #[test]
+    fn test_validate_mesh_errors() {
+        let mut scene = create_valid_scene();
+        let mesh = &mut scene.meshes[0];
+        mesh.faces.push(vec![0, 1, 2, 3]);
+        mesh.normals.pop();
+        mesh.bones[0].weights.push(AiVertexWeight::new(7, 0.0));
+        mesh.material_index = 2;
+
+        let issues = ValidateDataStructure.validate(&scene);
+        assert!(issues.contains(&ValidationIssue::PrimitiveTypeMismatch {
+            mesh: 0,
+            face: 1,
+            primitive_type: AiPrimitiveType::Polygon,
+        }));
+        assert!(issues.contains(&ValidationIssue::FaceIndexOutOfRange {
+            mesh: 0,
+            face: 1,
+            index: 3,
+            vertex_count: 3,
+        }));
+        assert!(issues.contains(&ValidationIssue::AttributeLength {
+            mesh: 0,
+            attribute: "normals".to_string(),
+            len: 2,
+            vertex_count: 3,
+        }));
+        assert!(issues.contains(&ValidationIssue::BoneWeightOutOfRange {
+            mesh: 0,
+            bone: 0,
+            vertex: 7,
+        }));
+        assert!(issues.contains(&ValidationIssue::MaterialIndexOutOfRange {
+            mesh: 0,
+            material_index: 2,
+            material_count: 1,
+        }));
+
+        let error = ValidateDataStructure.process(&mut scene).unwrap_err();
+        assert_eq!(error.issues.len(), 5);
+        assert!(!scene.flags.contains(AiSceneFlag::Validated));
+    }
+
+    #[test]
+    fn test_validate_node_errors() {
+        let mut scene = create_valid_scene();
+        scene.nodes.arena[1].children.push(0);
+        scene.nodes.arena.push(AiNode {
+            mesh_indexes: vec![4],
+            ..Default::default()
+        });
+
+        let issues = ValidateDataStructure.validate(&scene);
+        assert!(issues.contains(&ValidationIssue::InvalidChild { node: 1, child: 0 }));
+        assert!(issues.contains(&ValidationIssue::NodeVisitedTwice { node: 0 }));
+        assert!(issues.contains(&ValidationIssue::OrphanNode { node: 2 }));
+        assert!(issues.contains(&ValidationIssue::NodeMeshOutOfRange { node: 2, mesh: 4 }));
+    }
+
+    #[test]
+    fn test_validate_animation_and_texture_errors() {
+        let mut scene = create_valid_scene();
+        scene.animations.push(AiAnimation {
+            name: String::new(),
+            duration: 1.0,
+            ticks_per_second: 1.0,
+            channels: vec![AiNodeAnim {
+                node_name: "Missing".to_string(),
+                ..Default::default()
+            }],
+            mesh_channels: Vec::new(),
+            morph_channels: Vec::new(),
+        });
+        scene.materials[0].add_property(
+            matkey::_AI_MATKEY_TEXTURE_BASE,
+            Some(AiTextureType::Diffuse),
+            AiPropertyTypeInfo::Binary,
+            0,
+            "*0".bytes().collect(),
+        );
+
+        let issues = ValidateDataStructure.validate(&scene);
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::UnknownChannelNode {
+                    animation: 0,
+                    node_name: "Missing".to_string(),
+                },
+                ValidationIssue::TextureReference {
+                    material: 0,
+                    path: "*0".to_string(),
+                },
+            ]
+        );
+    }
+}
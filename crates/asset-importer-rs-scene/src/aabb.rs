@@ -1,7 +1,82 @@
-use super::vector::AiVector3D;
+use super::{type_def::base_types::AiReal, vector::AiVector3D};
 
+/// An axis-aligned bounding box.
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiAABB {
-    min: AiVector3D,
-    max: AiVector3D,
+    pub min: AiVector3D,
+    pub max: AiVector3D,
+}
+
+impl AiAABB {
+    pub fn new(min: AiVector3D, max: AiVector3D) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates an inverted box that contains nothing and grows to fit the first point extended into it.
+    pub fn empty() -> Self {
+        Self {
+            min: AiVector3D::new(AiReal::MAX, AiReal::MAX, AiReal::MAX),
+            max: AiVector3D::new(AiReal::MIN, AiReal::MIN, AiReal::MIN),
+        }
+    }
+
+    /// Creates the smallest box containing all points, or an empty box if there are none.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a AiVector3D>) -> Self {
+        let mut aabb = Self::empty();
+        for point in points {
+            aabb.extend(point);
+        }
+        aabb
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Grows the box to contain `point`.
+    pub fn extend(&mut self, point: &AiVector3D) {
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn union(&self, other: &AiAABB) -> AiAABB {
+        AiAABB {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max),
+        }
+    }
+
+    pub fn center(&self) -> AiVector3D {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> AiVector3D {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, point: &AiVector3D) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+
+    /// Checks whether the boxes touch or overlap.
+    pub fn overlaps(&self, other: &AiAABB) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    /// Squared distance from `point` to the closest point of the box, zero if inside.
+    pub fn square_distance(&self, point: &AiVector3D) -> AiReal {
+        let clamped = point.max(&self.min).min(&self.max);
+        (*point - clamped).square_length()
+    }
 }
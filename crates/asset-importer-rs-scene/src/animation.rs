@@ -1,4 +1,4 @@
-use super::{quaternion::AiQuaternion, vector::AiVector3D};
+use super::{error::AiMeshAnimError, mesh::AiMesh, quaternion::AiQuaternion, vector::AiVector3D};
 
 /// Interpolation Method Animation Keys from previous
 #[repr(u8)]
@@ -116,6 +116,20 @@ impl PartialOrd for AiMeshKey {
 }
 
 impl AiMeshKey {
+    /// Creates a key that switches to the animation mesh `value` at `time`.
+    pub fn new(time: f64, value: usize) -> Self {
+        Self { time, value }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Index into [`AiMesh::anim_meshes`](crate::AiMesh) of the animated mesh.
+    pub fn value(&self) -> usize {
+        self.value
+    }
+
     pub fn val_eq(&self, other: &Self) -> bool {
         self.value == other.value
     }
@@ -157,7 +171,7 @@ pub enum AiAnimBehavior {
     Repeat,
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiNodeAnim {
     pub node_name: String,
     pub position_keys: Vec<AiVectorKey>,
@@ -167,19 +181,69 @@ pub struct AiNodeAnim {
     pub post_state: AiAnimBehavior,
 }
 
-#[derive(Debug, PartialEq, Default)]
+/// Vertex animation of a single mesh, switching between its animation meshes.
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiMeshAnim {
     name: String,
     keys: Vec<AiMeshKey>,
 }
 
-#[derive(Debug, PartialEq, Default)]
+impl AiMeshAnim {
+    /// Creates a new channel animating the mesh named `name`.
+    pub fn new(name: String, keys: Vec<AiMeshKey>) -> Self {
+        Self { name, keys }
+    }
+
+    /// Name of the mesh affected by this channel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn keys(&self) -> &[AiMeshKey] {
+        &self.keys
+    }
+
+    pub fn keys_mut(&mut self) -> &mut Vec<AiMeshKey> {
+        &mut self.keys
+    }
+
+    /// Validates the channel against the meshes of the scene.
+    ///
+    /// Checks that a mesh with the channel's name exists, that key times are finite and
+    /// ascending, and that every key references an animation mesh of that mesh.
+    pub fn validate(&self, meshes: &[AiMesh]) -> Result<(), AiMeshAnimError> {
+        let mesh = meshes
+            .iter()
+            .find(|mesh| mesh.name == self.name)
+            .ok_or_else(|| AiMeshAnimError::UnknownMesh(self.name.clone()))?;
+        let mut previous_time = f64::NEG_INFINITY;
+        for (index, key) in self.keys.iter().enumerate() {
+            if !key.time.is_finite() || key.time < previous_time {
+                return Err(AiMeshAnimError::UnorderedKey { key: index });
+            }
+            if key.value >= mesh.anim_meshes.len() {
+                return Err(AiMeshAnimError::InvalidAnimMesh {
+                    key: index,
+                    anim_mesh: key.value,
+                });
+            }
+            previous_time = key.time;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiMeshMorphAnim {
     pub name: String,
     pub keys: Vec<AiMeshMorphKey>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct AiAnimation {
     pub name: String,
     pub duration: f64,
@@ -1,6 +1,11 @@
-use super::{matrix::AiMatrix4x4, type_def::AI_MATH_TWO_PI_F, vector::AiVector3D};
+use super::{
+    matrix::AiMatrix4x4,
+    scene::AiNodeTree,
+    type_def::{AI_MATH_TWO_PI_F, base_types::AiReal},
+    vector::AiVector3D,
+};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct AiCamera {
     pub name: String,
     pub position: AiVector3D,
@@ -97,6 +102,78 @@ impl AiCamera {
         camera_mat.d4 = 1.0;
         camera_mat
     }
+
+    /// Finds the node sharing the camera's name.
+    pub fn find_node(&self, nodes: &AiNodeTree) -> Option<usize> {
+        nodes.find_node_by_name(&self.name)
+    }
+
+    /// Computes the view matrix transforming world coordinates into camera space.
+    ///
+    /// The camera is placed by the global transformation of the node sharing its name,
+    /// then [`AiCamera::get_camera_matrix`] is applied. Returns `None` if there is no such
+    /// node or its transformation can not be inverted.
+    pub fn get_world_camera_matrix(&self, nodes: &AiNodeTree) -> Option<AiMatrix4x4> {
+        let node_index = self.find_node(nodes)?;
+        let world_to_node = nodes.global_transform(node_index)?.inverse()?;
+        Some(&self.get_camera_matrix() * &world_to_node)
+    }
+
+    /// Returns the camera position in world space.
+    pub fn get_world_position(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_point(&self.position))
+    }
+
+    /// Returns the normalized viewing direction in world space.
+    pub fn get_world_look_vec(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_vector(&self.look_vec).norm())
+    }
+
+    /// Returns the normalized up vector in world space.
+    pub fn get_world_up_vec(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_vector(&self.up_vec).norm())
+    }
+
+    /// Computes the projection matrix of the camera.
+    ///
+    /// The matrix maps the camera space produced by [`AiCamera::get_camera_matrix`], in which the
+    /// camera looks along +Z, to clip space with depth ranging from -1 at `near_plane` to 1 at
+    /// `far_plane`.
+    ///
+    /// If `orthographic_width` is non-zero an orthographic projection with a half width of
+    /// `orthographic_width` is returned, otherwise a perspective projection using `horizontal_fov`
+    /// as half the horizontal opening angle. An `aspect_ratio` of zero is treated as 1.
+    pub fn get_projection_matrix(&self) -> AiMatrix4x4 {
+        let aspect_ratio = if self.aspect_ratio == 0.0 {
+            1.0
+        } else {
+            self.aspect_ratio as AiReal
+        };
+        let near = self.near_plane as AiReal;
+        let far = self.far_plane as AiReal;
+        let depth = far - near;
+
+        let mut projection = AiMatrix4x4::new();
+        if self.orthographic_width != 0.0 {
+            let half_width = self.orthographic_width as AiReal;
+            projection.a1 = 1.0 / half_width;
+            projection.b2 = aspect_ratio / half_width;
+            projection.c3 = 2.0 / depth;
+            projection.c4 = -(far + near) / depth;
+            projection.d4 = 1.0;
+        } else {
+            let focal = 1.0 / (self.horizontal_fov as AiReal).tan();
+            projection.a1 = focal;
+            projection.b2 = focal * aspect_ratio;
+            projection.c3 = (far + near) / depth;
+            projection.c4 = -2.0 * far * near / depth;
+            projection.d3 = 1.0;
+        }
+        projection
+    }
 }
 
 #[cfg(test)]
@@ -134,4 +211,76 @@ mod tests {
         };
         assert_eq!(base_matrix, view_matrix);
     }
+
+    fn project(matrix: &AiMatrix4x4, point: &AiVector3D) -> AiVector3D {
+        let w = matrix.d1 * point.x + matrix.d2 * point.y + matrix.d3 * point.z + matrix.d4;
+        matrix.transform_point(point) / w
+    }
+
+    #[test]
+    fn test_get_projection_matrix() {
+        let camera = AiCamera {
+            horizontal_fov: std::f32::consts::FRAC_PI_4,
+            near_plane: 1.0,
+            far_plane: 10.0,
+            aspect_ratio: 2.0,
+            ..Default::default()
+        };
+        let projection = camera.get_projection_matrix();
+        let near = project(&projection, &AiVector3D::new(1.0, 0.5, 1.0));
+        assert!((near - AiVector3D::new(1.0, 1.0, -1.0)).len() < 1e-5);
+        let far = project(&projection, &AiVector3D::new(0.0, 0.0, 10.0));
+        assert!((far.z - 1.0).abs() < 1e-5);
+
+        let camera = AiCamera {
+            orthographic_width: 4.0,
+            ..camera
+        };
+        let projection = camera.get_projection_matrix();
+        let corner = project(&projection, &AiVector3D::new(4.0, 2.0, 1.0));
+        assert!((corner - AiVector3D::new(1.0, 1.0, -1.0)).len() < 1e-5);
+    }
+
+    #[test]
+    fn test_get_world_camera_matrix() {
+        let mut nodes = AiNodeTree::default();
+        let root = nodes
+            .insert(
+                crate::AiNode {
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 0.0, 5.0)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        nodes
+            .insert(
+                crate::AiNode {
+                    name: "Camera".to_string(),
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(1.0, 0.0, 0.0)),
+                    ..Default::default()
+                },
+                Some(root),
+            )
+            .unwrap();
+
+        let camera = AiCamera {
+            name: "Camera".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(camera.find_node(&nodes), Some(1));
+        assert_eq!(
+            camera.get_world_position(&nodes),
+            Some(AiVector3D::new(1.0, 0.0, 5.0))
+        );
+        let view = camera.get_world_camera_matrix(&nodes).unwrap();
+        let origin = view.transform_point(&AiVector3D::new(1.0, 0.0, 5.0));
+        assert!(origin.len() < 1e-5);
+
+        let camera = AiCamera {
+            name: "Missing".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(camera.get_world_camera_matrix(&nodes), None);
+    }
 }
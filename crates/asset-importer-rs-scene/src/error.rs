@@ -39,3 +39,72 @@ impl Display for AiReturnError {
 }
 
 impl error::Error for AiReturnError {}
+
+/// Errors raised when building or validating an [`AiSkeleton`](crate::AiSkeleton).
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiSkeletonError {
+    /// The parent index of a bone is outside of the skeleton.
+    InvalidParent { bone: usize, parent: usize },
+    /// Following the parents of a bone leads back to itself.
+    Cycle { bone: usize },
+    /// A bone references a node that does not exist.
+    InvalidNode { bone: usize, node: usize },
+    /// A bone references a mesh that does not exist.
+    InvalidMesh { bone: usize, mesh: usize },
+    /// A bone weight references a vertex outside of its mesh.
+    InvalidVertex { bone: usize, vertex: usize },
+    /// A bone has vertex weights but no mesh they apply to.
+    WeightsWithoutMesh { bone: usize },
+}
+
+impl Display for AiSkeletonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiSkeletonError::InvalidParent { bone, parent } => {
+                write!(f, "Bone {} has invalid parent {}", bone, parent)
+            }
+            AiSkeletonError::Cycle { bone } => write!(f, "Bone {} is part of a cycle", bone),
+            AiSkeletonError::InvalidNode { bone, node } => {
+                write!(f, "Bone {} references invalid node {}", bone, node)
+            }
+            AiSkeletonError::InvalidMesh { bone, mesh } => {
+                write!(f, "Bone {} references invalid mesh {}", bone, mesh)
+            }
+            AiSkeletonError::InvalidVertex { bone, vertex } => {
+                write!(f, "Bone {} references invalid vertex {}", bone, vertex)
+            }
+            AiSkeletonError::WeightsWithoutMesh { bone } => {
+                write!(f, "Bone {} has vertex weights but no mesh", bone)
+            }
+        }
+    }
+}
+
+impl error::Error for AiSkeletonError {}
+
+/// Errors raised when validating an [`AiMeshAnim`](crate::AiMeshAnim).
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiMeshAnimError {
+    /// No mesh with the channel's name exists.
+    UnknownMesh(String),
+    /// A key has a time that is not finite or smaller than the previous key.
+    UnorderedKey { key: usize },
+    /// A key references an animation mesh the target mesh does not have.
+    InvalidAnimMesh { key: usize, anim_mesh: usize },
+}
+
+impl Display for AiMeshAnimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiMeshAnimError::UnknownMesh(name) => write!(f, "No mesh named {}", name),
+            AiMeshAnimError::UnorderedKey { key } => {
+                write!(f, "Key {} is not ordered by time", key)
+            }
+            AiMeshAnimError::InvalidAnimMesh { key, anim_mesh } => {
+                write!(f, "Key {} references invalid anim mesh {}", key, anim_mesh)
+            }
+        }
+    }
+}
+
+impl error::Error for AiMeshAnimError {}
@@ -14,8 +14,13 @@ mod texture;
 mod type_def;
 mod vector;
 
+pub use aabb::AiAABB;
+
+pub use animation::AiAnimBehavior;
 pub use animation::AiAnimInterpolation;
 pub use animation::AiAnimation;
+pub use animation::AiMeshAnim;
+pub use animation::AiMeshKey;
 pub use animation::AiMeshMorphAnim;
 pub use animation::AiMeshMorphKey;
 pub use animation::AiNodeAnim;
@@ -27,7 +32,12 @@ pub use camera::AiCamera;
 pub use color::AiColor3D;
 pub use color::AiColor4D;
 
+pub use error::AiMeshAnimError;
+pub use error::AiSkeletonError;
+
 pub use light::AiLight;
+pub use light::AiLightFalloff;
+pub use light::AiLightIntensityUnit;
 pub use light::AiLightSourceType;
 
 pub use material::AiMaterial;
@@ -35,11 +45,13 @@ pub use material::AiMaterialProperty;
 pub use material::AiPropertyTypeInfo;
 pub use material::AiShadingMode;
 pub use material::AiTextureMapMode;
+pub use material::AiTextureMapping;
 pub use material::AiTextureType;
 pub use material::AiUvTransform;
 pub use material::matkey;
 
 pub use matrix::AiMatrix4x4;
+pub use matrix::DecomposedMatrix;
 
 pub use mesh::AI_MAX_NUMBER_OF_COLORS_SETS;
 pub use mesh::AI_MAX_NUMBER_OF_TEXTURECOORDS;
@@ -48,6 +60,8 @@ pub use mesh::AiBone;
 pub use mesh::AiFace;
 pub use mesh::AiMesh;
 pub use mesh::AiPrimitiveType;
+pub use mesh::AiSkeleton;
+pub use mesh::AiSkeletonBone;
 pub use mesh::AiVertexWeight;
 
 pub use metadata::AiMetadata;
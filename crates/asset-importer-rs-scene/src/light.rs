@@ -1,5 +1,6 @@
 use super::{
     color::AiColor3D,
+    scene::AiNodeTree,
     type_def::AI_MATH_PI_F,
     vector::{AiVector2D, AiVector3D},
 };
@@ -16,7 +17,77 @@ pub enum AiLightSourceType {
     Area,
 }
 
-#[derive(Debug, PartialEq)]
+/// Unit of [`AiLight::intensity`].
+#[repr(u8)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum AiLightIntensityUnit {
+    /// No physical unit, the intensity is a plain multiplier of the light color.
+    #[default]
+    Unitless,
+    /// Luminous intensity (lm/sr), used by glTF point and spot lights.
+    Candela,
+    /// Illuminance (lm/m²), used by glTF directional lights.
+    Lux,
+    /// Radiant power, used by FBX and most DCC tools.
+    Watt,
+}
+
+/// Distance falloff of a light as described by its source format.
+///
+/// The legacy model attenuates a light by `1 / (attenuation + attenuation_linear * d +
+/// attenuation_quadratic * d²)`. The exact falloffs map onto it as follows:
+///
+/// | Falloff         | `attenuation` | `attenuation_linear` | `attenuation_quadratic` |
+/// |-----------------|---------------|----------------------|-------------------------|
+/// | `None`          | 1             | 0                    | 0                       |
+/// | `InverseLinear` | 0             | 1                    | 0                       |
+/// | `InverseSquare` | 0             | 0                    | 1                       |
+///
+/// `InverseCube` has no legacy equivalent, and any other set of coefficients is kept as
+/// `Attenuation`.
+#[repr(u8)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum AiLightFalloff {
+    /// The falloff is only described by the legacy attenuation coefficients.
+    #[default]
+    Attenuation,
+    /// The light does not fall off with distance.
+    None,
+    /// `1 / d`
+    InverseLinear,
+    /// `1 / d²`, the physically based falloff used by glTF.
+    InverseSquare,
+    /// `1 / d³`
+    InverseCube,
+}
+
+impl AiLightFalloff {
+    /// Returns the legacy `(constant, linear, quadratic)` coefficients equivalent to the falloff.
+    ///
+    /// Returns `None` for `Attenuation`, whose coefficients live on the light, and for
+    /// `InverseCube`, which the legacy model cannot express.
+    pub fn to_attenuation(self) -> Option<(f32, f32, f32)> {
+        match self {
+            AiLightFalloff::None => Some((1.0, 0.0, 0.0)),
+            AiLightFalloff::InverseLinear => Some((0.0, 1.0, 0.0)),
+            AiLightFalloff::InverseSquare => Some((0.0, 0.0, 1.0)),
+            AiLightFalloff::Attenuation | AiLightFalloff::InverseCube => None,
+        }
+    }
+
+    /// Classifies legacy attenuation coefficients, falling back to `Attenuation` if they do
+    /// not describe an exact falloff.
+    pub fn from_attenuation(constant: f32, linear: f32, quadratic: f32) -> Self {
+        match (constant, linear, quadratic) {
+            (1.0, 0.0, 0.0) => AiLightFalloff::None,
+            (0.0, 1.0, 0.0) => AiLightFalloff::InverseLinear,
+            (0.0, 0.0, 1.0) => AiLightFalloff::InverseSquare,
+            _ => AiLightFalloff::Attenuation,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct AiLight {
     pub name: String,
     pub source_type: AiLightSourceType,
@@ -32,6 +103,17 @@ pub struct AiLight {
     pub inner_cone_angle: f32,
     pub outer_cone_angle: f32,
     pub size: AiVector2D,
+    /// Intensity of the light in `intensity_unit`.
+    ///
+    /// The color fields already include the intensity, so consumers of the legacy model can
+    /// ignore it. Use [`AiLight::base_color`] to recover the source color.
+    pub intensity: f32,
+    pub intensity_unit: AiLightIntensityUnit,
+    /// Distance at which the light reaches zero, `None` if the light has no cutoff.
+    pub range: Option<f32>,
+    /// Falloff as described by the source format, see [`AiLightFalloff`] for the conversion
+    /// to the legacy attenuation coefficients.
+    pub falloff: AiLightFalloff,
 }
 
 impl Default for AiLight {
@@ -51,6 +133,170 @@ impl Default for AiLight {
             inner_cone_angle: AI_MATH_PI_F,
             outer_cone_angle: AI_MATH_PI_F,
             size: Default::default(),
+            intensity: 1.0,
+            intensity_unit: Default::default(),
+            range: None,
+            falloff: Default::default(),
+        }
+    }
+}
+
+impl AiLight {
+    /// Returns the diffuse color without the intensity folded in.
+    pub fn base_color(&self) -> AiColor3D {
+        if self.intensity == 0.0 {
+            return self.diffuse_color;
+        }
+        AiColor3D::new(
+            self.diffuse_color.r / self.intensity,
+            self.diffuse_color.g / self.intensity,
+            self.diffuse_color.b / self.intensity,
+        )
+    }
+
+    /// Returns the factor the light is scaled by at `distance`.
+    ///
+    /// `Attenuation` evaluates the legacy coefficients, the other falloffs are evaluated
+    /// exactly. If `range` is set, the result is windowed by `clamp(1 - (d / range)⁴, 0, 1)²`
+    /// as specified by `KHR_lights_punctual`. A light is not attenuated where the falloff
+    /// would divide by zero.
+    pub fn attenuation_at(&self, distance: f32) -> f32 {
+        let denominator = match self.falloff {
+            AiLightFalloff::Attenuation => {
+                self.attenuation
+                    + self.attenuation_linear * distance
+                    + self.attenuation_quadratic * distance * distance
+            }
+            AiLightFalloff::None => 1.0,
+            AiLightFalloff::InverseLinear => distance,
+            AiLightFalloff::InverseSquare => distance * distance,
+            AiLightFalloff::InverseCube => distance * distance * distance,
+        };
+        let factor = if denominator > 0.0 {
+            1.0 / denominator
+        } else {
+            1.0
+        };
+        match self.range {
+            Some(range) if range > 0.0 => {
+                let ratio = distance / range;
+                let window = (1.0 - ratio.powi(4)).clamp(0.0, 1.0);
+                factor * window * window
+            }
+            _ => factor,
+        }
+    }
+
+    /// Finds the node sharing the light's name.
+    pub fn find_node(&self, nodes: &AiNodeTree) -> Option<usize> {
+        nodes.find_node_by_name(&self.name)
+    }
+
+    /// Returns the light position in world space.
+    ///
+    /// `position` is relative to the node sharing the light's name, returns `None` if there is no such node.
+    pub fn get_world_position(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_point(&self.position))
+    }
+
+    /// Returns the normalized light direction in world space.
+    ///
+    /// `direction` is relative to the node sharing the light's name, returns `None` if there is no such node.
+    pub fn get_world_direction(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_vector(&self.direction).norm())
+    }
+
+    /// Returns the normalized up vector of an area light in world space.
+    pub fn get_world_up(&self, nodes: &AiNodeTree) -> Option<AiVector3D> {
+        let transform = nodes.global_transform(self.find_node(nodes)?)?;
+        Some(transform.transform_vector(&self.up).norm())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AiMatrix4x4, AiNode};
+
+    #[test]
+    fn test_light_world_space() {
+        let mut nodes = AiNodeTree::default();
+        let root = nodes
+            .insert(
+                AiNode {
+                    transformation: AiMatrix4x4::from_scaling(AiVector3D::new(2.0, 2.0, 2.0)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        nodes
+            .insert(
+                AiNode {
+                    name: "Spot".to_string(),
+                    transformation: AiMatrix4x4::from_translation(AiVector3D::new(0.0, 1.0, 0.0)),
+                    ..Default::default()
+                },
+                Some(root),
+            )
+            .unwrap();
+
+        let light = AiLight {
+            name: "Spot".to_string(),
+            source_type: AiLightSourceType::Spot,
+            position: AiVector3D::new(1.0, 0.0, 0.0),
+            direction: AiVector3D::new(0.0, 0.0, -1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            light.get_world_position(&nodes),
+            Some(AiVector3D::new(2.0, 2.0, 0.0))
+        );
+        assert_eq!(
+            light.get_world_direction(&nodes),
+            Some(AiVector3D::new(0.0, 0.0, -1.0))
+        );
+
+        let light = AiLight {
+            name: "Missing".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(light.get_world_position(&nodes), None);
+    }
+
+    #[test]
+    fn test_light_falloff() {
+        for falloff in [
+            AiLightFalloff::None,
+            AiLightFalloff::InverseLinear,
+            AiLightFalloff::InverseSquare,
+        ] {
+            let (constant, linear, quadratic) = falloff.to_attenuation().unwrap();
+            assert_eq!(
+                AiLightFalloff::from_attenuation(constant, linear, quadratic),
+                falloff
+            );
         }
+        assert_eq!(AiLightFalloff::InverseCube.to_attenuation(), None);
+        assert_eq!(
+            AiLightFalloff::from_attenuation(0.5, 0.0, 2.0),
+            AiLightFalloff::Attenuation
+        );
+
+        let mut light = AiLight {
+            attenuation: 0.0,
+            attenuation_linear: 0.0,
+            attenuation_quadratic: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(light.attenuation_at(2.0), 0.25);
+        light.falloff = AiLightFalloff::InverseSquare;
+        assert_eq!(light.attenuation_at(2.0), 0.25);
+        light.range = Some(4.0);
+        assert!(light.attenuation_at(2.0) < 0.25);
+        assert_eq!(light.attenuation_at(4.0), 0.0);
+        assert_eq!(light.attenuation_at(8.0), 0.0);
     }
 }
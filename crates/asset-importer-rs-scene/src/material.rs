@@ -1,4 +1,9 @@
-use std::{fmt::Display, string::FromUtf8Error};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    string::FromUtf8Error,
+};
 
 use bytemuck::{AnyBitPattern, Pod, Zeroable};
 use enumflags2::bitflags;
@@ -176,8 +181,9 @@ impl TryFrom<u8> for AiTextureMapMode {
 }
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Default, TryFromPrimitive, IntoPrimitive)]
 pub enum AiTextureMapping {
+    #[default]
     UV,
     Sphere,
     Cylinder,
@@ -311,7 +317,7 @@ pub enum AiPropertyTypeInfo {
     Buffer,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct AiMaterialProperty {
     //Property Key
     pub key: String,
@@ -325,7 +331,7 @@ pub struct AiMaterialProperty {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiMaterial {
     properties: Vec<AiMaterialProperty>,
 }
@@ -347,6 +353,46 @@ impl AiMaterial {
     }
 }
 
+impl AiMaterial {
+    /// Computes a hash over the material's properties that does not depend on their order.
+    ///
+    /// Properties whose key is listed in `skip_keys` are left out, which allows
+    /// e.g. ignoring [`matkey::AI_MATKEY_NAME`] when looking for duplicate materials.
+    /// Two materials that are [`AiMaterial::properties_eq`] always produce the same hash.
+    pub fn properties_hash(&self, skip_keys: &[&str]) -> u64 {
+        self.properties
+            .iter()
+            .filter(|property| !skip_keys.contains(&property.key.as_str()))
+            .map(|property| {
+                let mut hasher = DefaultHasher::new();
+                property.key.hash(&mut hasher);
+                property.index.hash(&mut hasher);
+                (property.semantic as u8).hash(&mut hasher);
+                (property.property_type.clone() as u8).hash(&mut hasher);
+                property.data.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0u64, |acc, hash| acc.wrapping_add(hash))
+    }
+
+    /// Checks whether both materials carry the same set of properties, regardless of order.
+    ///
+    /// Properties whose key is listed in `skip_keys` are ignored on both sides.
+    pub fn properties_eq(&self, other: &AiMaterial, skip_keys: &[&str]) -> bool {
+        let lhs: Vec<&AiMaterialProperty> = self
+            .properties
+            .iter()
+            .filter(|property| !skip_keys.contains(&property.key.as_str()))
+            .collect();
+        let rhs: Vec<&AiMaterialProperty> = other
+            .properties
+            .iter()
+            .filter(|property| !skip_keys.contains(&property.key.as_str()))
+            .collect();
+        lhs.len() == rhs.len() && lhs.iter().all(|property| rhs.contains(property))
+    }
+}
+
 impl AiMaterial {
     pub fn get_property(
         &self,
@@ -49,7 +49,24 @@ impl ops::MulAssign for AiMatrix4x4 {
             d3: rhs.a3 * self.d1 + rhs.b3 * self.d2 + rhs.c3 * self.d3 + rhs.d3 * self.d4,
             d4: rhs.a4 * self.d1 + rhs.b4 * self.d2 + rhs.c4 * self.d3 + rhs.d4 * self.d4,
         };
-        self.a1 = new_self.a1;
+        *self = new_self;
+    }
+}
+
+impl ops::Mul for AiMatrix4x4 {
+    type Output = AiMatrix4x4;
+
+    fn mul(mut self, rhs: Self) -> Self::Output {
+        self *= rhs;
+        self
+    }
+}
+
+impl ops::Mul<&AiMatrix4x4> for &AiMatrix4x4 {
+    type Output = AiMatrix4x4;
+
+    fn mul(self, rhs: &AiMatrix4x4) -> Self::Output {
+        self.clone() * rhs.clone()
     }
 }
 
@@ -99,6 +116,166 @@ impl AiMatrix4x4 {
 }
 
 impl AiMatrix4x4 {
+    /// Creates a matrix translating by `translation`.
+    pub fn from_translation(translation: AiVector3D) -> Self {
+        Self {
+            a4: translation.x,
+            b4: translation.y,
+            c4: translation.z,
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a matrix scaling each axis by the matching component of `scaling`.
+    pub fn from_scaling(scaling: AiVector3D) -> Self {
+        Self {
+            a1: scaling.x,
+            b2: scaling.y,
+            c3: scaling.z,
+            ..Self::identity()
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self {
+            a1: self.a1,
+            a2: self.b1,
+            a3: self.c1,
+            a4: self.d1,
+            b1: self.a2,
+            b2: self.b2,
+            b3: self.c2,
+            b4: self.d2,
+            c1: self.a3,
+            c2: self.b3,
+            c3: self.c3,
+            c4: self.d3,
+            d1: self.a4,
+            d2: self.b4,
+            d3: self.c4,
+            d4: self.d4,
+        }
+    }
+
+    pub fn determinant(&self) -> AiReal {
+        self.a1 * self.b2 * self.c3 * self.d4 - self.a1 * self.b2 * self.c4 * self.d3
+            + self.a1 * self.b3 * self.c4 * self.d2
+            - self.a1 * self.b3 * self.c2 * self.d4
+            + self.a1 * self.b4 * self.c2 * self.d3
+            - self.a1 * self.b4 * self.c3 * self.d2
+            - self.a2 * self.b3 * self.c4 * self.d1
+            + self.a2 * self.b3 * self.c1 * self.d4
+            - self.a2 * self.b4 * self.c1 * self.d3
+            + self.a2 * self.b4 * self.c3 * self.d1
+            - self.a2 * self.b1 * self.c3 * self.d4
+            + self.a2 * self.b1 * self.c4 * self.d3
+            + self.a3 * self.b4 * self.c1 * self.d2
+            - self.a3 * self.b4 * self.c2 * self.d1
+            + self.a3 * self.b1 * self.c2 * self.d4
+            - self.a3 * self.b1 * self.c4 * self.d2
+            + self.a3 * self.b2 * self.c4 * self.d1
+            - self.a3 * self.b2 * self.c1 * self.d4
+            - self.a4 * self.b1 * self.c2 * self.d3
+            + self.a4 * self.b1 * self.c3 * self.d2
+            - self.a4 * self.b2 * self.c3 * self.d1
+            + self.a4 * self.b2 * self.c1 * self.d3
+            - self.a4 * self.b3 * self.c1 * self.d2
+            + self.a4 * self.b3 * self.c2 * self.d1
+    }
+
+    /// Computes the inverse of the matrix, returning `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        Some(Self {
+            a1: inv_det
+                * (self.b2 * (self.c3 * self.d4 - self.c4 * self.d3)
+                    + self.b3 * (self.c4 * self.d2 - self.c2 * self.d4)
+                    + self.b4 * (self.c2 * self.d3 - self.c3 * self.d2)),
+            a2: -inv_det
+                * (self.a2 * (self.c3 * self.d4 - self.c4 * self.d3)
+                    + self.a3 * (self.c4 * self.d2 - self.c2 * self.d4)
+                    + self.a4 * (self.c2 * self.d3 - self.c3 * self.d2)),
+            a3: inv_det
+                * (self.a2 * (self.b3 * self.d4 - self.b4 * self.d3)
+                    + self.a3 * (self.b4 * self.d2 - self.b2 * self.d4)
+                    + self.a4 * (self.b2 * self.d3 - self.b3 * self.d2)),
+            a4: -inv_det
+                * (self.a2 * (self.b3 * self.c4 - self.b4 * self.c3)
+                    + self.a3 * (self.b4 * self.c2 - self.b2 * self.c4)
+                    + self.a4 * (self.b2 * self.c3 - self.b3 * self.c2)),
+            b1: -inv_det
+                * (self.b1 * (self.c3 * self.d4 - self.c4 * self.d3)
+                    + self.b3 * (self.c4 * self.d1 - self.c1 * self.d4)
+                    + self.b4 * (self.c1 * self.d3 - self.c3 * self.d1)),
+            b2: inv_det
+                * (self.a1 * (self.c3 * self.d4 - self.c4 * self.d3)
+                    + self.a3 * (self.c4 * self.d1 - self.c1 * self.d4)
+                    + self.a4 * (self.c1 * self.d3 - self.c3 * self.d1)),
+            b3: -inv_det
+                * (self.a1 * (self.b3 * self.d4 - self.b4 * self.d3)
+                    + self.a3 * (self.b4 * self.d1 - self.b1 * self.d4)
+                    + self.a4 * (self.b1 * self.d3 - self.b3 * self.d1)),
+            b4: inv_det
+                * (self.a1 * (self.b3 * self.c4 - self.b4 * self.c3)
+                    + self.a3 * (self.b4 * self.c1 - self.b1 * self.c4)
+                    + self.a4 * (self.b1 * self.c3 - self.b3 * self.c1)),
+            c1: inv_det
+                * (self.b1 * (self.c2 * self.d4 - self.c4 * self.d2)
+                    + self.b2 * (self.c4 * self.d1 - self.c1 * self.d4)
+                    + self.b4 * (self.c1 * self.d2 - self.c2 * self.d1)),
+            c2: -inv_det
+                * (self.a1 * (self.c2 * self.d4 - self.c4 * self.d2)
+                    + self.a2 * (self.c4 * self.d1 - self.c1 * self.d4)
+                    + self.a4 * (self.c1 * self.d2 - self.c2 * self.d1)),
+            c3: inv_det
+                * (self.a1 * (self.b2 * self.d4 - self.b4 * self.d2)
+                    + self.a2 * (self.b4 * self.d1 - self.b1 * self.d4)
+                    + self.a4 * (self.b1 * self.d2 - self.b2 * self.d1)),
+            c4: -inv_det
+                * (self.a1 * (self.b2 * self.c4 - self.b4 * self.c2)
+                    + self.a2 * (self.b4 * self.c1 - self.b1 * self.c4)
+                    + self.a4 * (self.b1 * self.c2 - self.b2 * self.c1)),
+            d1: -inv_det
+                * (self.b1 * (self.c2 * self.d3 - self.c3 * self.d2)
+                    + self.b2 * (self.c3 * self.d1 - self.c1 * self.d3)
+                    + self.b3 * (self.c1 * self.d2 - self.c2 * self.d1)),
+            d2: inv_det
+                * (self.a1 * (self.c2 * self.d3 - self.c3 * self.d2)
+                    + self.a2 * (self.c3 * self.d1 - self.c1 * self.d3)
+                    + self.a3 * (self.c1 * self.d2 - self.c2 * self.d1)),
+            d3: -inv_det
+                * (self.a1 * (self.b2 * self.d3 - self.b3 * self.d2)
+                    + self.a2 * (self.b3 * self.d1 - self.b1 * self.d3)
+                    + self.a3 * (self.b1 * self.d2 - self.b2 * self.d1)),
+            d4: inv_det
+                * (self.a1 * (self.b2 * self.c3 - self.b3 * self.c2)
+                    + self.a2 * (self.b3 * self.c1 - self.b1 * self.c3)
+                    + self.a3 * (self.b1 * self.c2 - self.b2 * self.c1)),
+        })
+    }
+
+    /// Transforms a position, applying the translation part of the matrix.
+    pub fn transform_point(&self, point: &AiVector3D) -> AiVector3D {
+        AiVector3D::new(
+            self.a1 * point.x + self.a2 * point.y + self.a3 * point.z + self.a4,
+            self.b1 * point.x + self.b2 * point.y + self.b3 * point.z + self.b4,
+            self.c1 * point.x + self.c2 * point.y + self.c3 * point.z + self.c4,
+        )
+    }
+
+    /// Transforms a direction, ignoring the translation part of the matrix.
+    pub fn transform_vector(&self, vector: &AiVector3D) -> AiVector3D {
+        AiVector3D::new(
+            self.a1 * vector.x + self.a2 * vector.y + self.a3 * vector.z,
+            self.b1 * vector.x + self.b2 * vector.y + self.b3 * vector.z,
+            self.c1 * vector.x + self.c2 * vector.y + self.c3 * vector.z,
+        )
+    }
+
     pub fn is_identity(&self, epsilon: AiReal) -> bool {
         self.a2 <= epsilon
             && self.a2 >= -epsilon
@@ -277,3 +454,48 @@ impl From<AiMatrix4x4> for [AiReal; 16] {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_eq(lhs: &AiMatrix4x4, rhs: &AiMatrix4x4) {
+        let lhs: [AiReal; 16] = lhs.clone().into();
+        let rhs: [AiReal; 16] = rhs.clone().into();
+        for (x, y) in lhs.iter().zip(rhs.iter()) {
+            assert!((x - y).abs() < 1e-5, "{:?} != {:?}", lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn test_matrix_mul() {
+        let translation = AiMatrix4x4::from_translation(AiVector3D::new(1.0, 2.0, 3.0));
+        let scaling = AiMatrix4x4::from_scaling(AiVector3D::new(2.0, 2.0, 2.0));
+        let matrix = &translation * &scaling;
+        assert_eq!(
+            matrix.transform_point(&AiVector3D::new(1.0, 1.0, 1.0)),
+            AiVector3D::new(3.0, 4.0, 5.0)
+        );
+        assert_eq!(
+            matrix.transform_vector(&AiVector3D::new(1.0, 1.0, 1.0)),
+            AiVector3D::new(2.0, 2.0, 2.0)
+        );
+
+        let mut matrix = translation.clone();
+        matrix *= scaling;
+        assert_matrix_eq(
+            &matrix,
+            &(&translation * &AiMatrix4x4::from_scaling(AiVector3D::new(2.0, 2.0, 2.0))),
+        );
+    }
+
+    #[test]
+    fn test_matrix_inverse() {
+        let matrix = AiMatrix4x4::from_translation(AiVector3D::new(1.0, 2.0, 3.0))
+            * AiMatrix4x4::from_scaling(AiVector3D::new(2.0, 4.0, 8.0));
+        assert_eq!(matrix.determinant(), 64.0);
+        let inverse = matrix.inverse().unwrap();
+        assert_matrix_eq(&(&matrix * &inverse), &AiMatrix4x4::identity());
+        assert_eq!(AiMatrix4x4::new().inverse(), None);
+    }
+}
@@ -1,6 +1,7 @@
 use enumflags2::BitFlags;
 
 use super::aabb::AiAABB;
+use super::error::AiSkeletonError;
 use super::matrix::AiMatrix4x4;
 use super::{color::AiColor4D, type_def::base_types::AiReal, vector::AiVector3D};
 
@@ -103,9 +104,10 @@ unsafe impl ::enumflags2::_internal::RawBitFlags for AiPrimitiveType {
 impl ::enumflags2::BitFlag for AiPrimitiveType {}
 
 impl AiPrimitiveType {
-    const fn primitive_type_for_n_indices(n: u8) -> AiPrimitiveType {
+    /// Returns the primitive type of a face with `n` indices.
+    pub const fn primitive_type_for_n_indices(n: usize) -> AiPrimitiveType {
         match n {
-            4u8..=u8::MAX => AiPrimitiveType::Polygon,
+            4.. => AiPrimitiveType::Polygon,
             3 => AiPrimitiveType::Triangle,
             2 => AiPrimitiveType::Line,
             1 => AiPrimitiveType::Point,
@@ -193,21 +195,351 @@ impl AiMesh {
         }
         n
     }
+
+    /// Returns the tangents as glTF-style 4-component vectors `[x, y, z, w]`.
+    ///
+    /// `w` is the handedness of the tangent frame, `bitangent = w * (normal x tangent)`. It is
+    /// `-1.0` where the stored bitangent points against `normal x tangent` and `1.0` otherwise.
+    pub fn tangents_with_handedness(&self) -> Vec<[AiReal; 4]> {
+        self.tangents
+            .iter()
+            .enumerate()
+            .map(|(index, tangent)| {
+                let handedness = match (self.normals.get(index), self.bi_tangents.get(index)) {
+                    (Some(normal), Some(bi_tangent))
+                        if normal.cross(tangent) * bi_tangent < 0.0 =>
+                    {
+                        -1.0
+                    }
+                    _ => 1.0,
+                };
+                [tangent.x, tangent.y, tangent.z, handedness]
+            })
+            .collect()
+    }
 }
 
+/// A single bone of an [`AiSkeleton`].
+///
+/// The bone references the node it animates and, optionally, the mesh it deforms
+/// together with the vertex weights for that mesh.
 #[derive(Debug, PartialEq, Clone)]
 pub struct AiSkeletonBone {
-    parent: i32,
+    parent: Option<usize>,
     node_index: usize,
-    armature_index: usize,
-    mesh_index: usize,
+    armature_index: Option<usize>,
+    mesh_index: Option<usize>,
     weights: Vec<AiVertexWeight>,
     offset_matrix: AiMatrix4x4,
     local_matrix: AiMatrix4x4,
 }
 
+impl AiSkeletonBone {
+    /// Creates a new bone.
+    ///
+    /// # Arguments
+    /// * `parent` - Index of the parent bone within the skeleton, `None` for a root bone
+    /// * `node_index` - Index of the node in the scene's node arena this bone belongs to
+    /// * `offset_matrix` - Matrix transforming from mesh space to bone space (inverse bind matrix)
+    /// * `local_matrix` - Bind pose transformation of the bone relative to its parent
+    pub fn new(
+        parent: Option<usize>,
+        node_index: usize,
+        offset_matrix: AiMatrix4x4,
+        local_matrix: AiMatrix4x4,
+    ) -> Self {
+        Self {
+            parent,
+            node_index,
+            armature_index: None,
+            mesh_index: None,
+            weights: Vec::new(),
+            offset_matrix,
+            local_matrix,
+        }
+    }
+
+    /// Sets the node index of the armature the bone belongs to.
+    pub fn with_armature(mut self, armature_index: usize) -> Self {
+        self.armature_index = Some(armature_index);
+        self
+    }
+
+    /// Sets the mesh deformed by the bone and the vertex weights for that mesh.
+    pub fn with_mesh(mut self, mesh_index: usize, weights: Vec<AiVertexWeight>) -> Self {
+        self.mesh_index = Some(mesh_index);
+        self.weights = weights;
+        self
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn node_index(&self) -> usize {
+        self.node_index
+    }
+
+    pub fn set_node_index(&mut self, node_index: usize) {
+        self.node_index = node_index;
+    }
+
+    pub fn armature_index(&self) -> Option<usize> {
+        self.armature_index
+    }
+
+    pub fn set_armature_index(&mut self, armature_index: Option<usize>) {
+        self.armature_index = armature_index;
+    }
+
+    pub fn mesh_index(&self) -> Option<usize> {
+        self.mesh_index
+    }
+
+    pub fn set_mesh_index(&mut self, mesh_index: Option<usize>) {
+        self.mesh_index = mesh_index;
+    }
+
+    pub fn weights(&self) -> &[AiVertexWeight] {
+        &self.weights
+    }
+
+    pub fn weights_mut(&mut self) -> &mut Vec<AiVertexWeight> {
+        &mut self.weights
+    }
+
+    pub fn offset_matrix(&self) -> &AiMatrix4x4 {
+        &self.offset_matrix
+    }
+
+    pub fn set_offset_matrix(&mut self, offset_matrix: AiMatrix4x4) {
+        self.offset_matrix = offset_matrix;
+    }
+
+    pub fn local_matrix(&self) -> &AiMatrix4x4 {
+        &self.local_matrix
+    }
+
+    pub fn set_local_matrix(&mut self, local_matrix: AiMatrix4x4) {
+        self.local_matrix = local_matrix;
+    }
+}
+
+/// A named hierarchy of bones.
+///
+/// Bones reference their parent by index into the skeleton's bone list, so the
+/// hierarchy can be walked without going through the scene's node tree.
+/// A skeleton is only constructed if its bone hierarchy is valid, see [`AiSkeleton::new`].
 #[derive(Debug, PartialEq, Clone)]
 pub struct AiSkeleton {
     name: String,
     bones: Vec<AiSkeletonBone>,
 }
+
+impl AiSkeleton {
+    /// Creates a new skeleton, checking that every bone parent is in range and that
+    /// the parent links do not form a cycle.
+    pub fn new(name: String, bones: Vec<AiSkeletonBone>) -> Result<Self, AiSkeletonError> {
+        for (index, bone) in bones.iter().enumerate() {
+            if let Some(parent) = bone.parent
+                && parent >= bones.len()
+            {
+                return Err(AiSkeletonError::InvalidParent {
+                    bone: index,
+                    parent,
+                });
+            }
+
+            // Walk up the hierarchy, any path longer than the bone count has to contain a cycle
+            let mut current = bone.parent;
+            let mut depth = 0;
+            while let Some(parent) = current {
+                depth += 1;
+                if parent == index || depth > bones.len() {
+                    return Err(AiSkeletonError::Cycle { bone: index });
+                }
+                current = bones[parent].parent;
+            }
+        }
+        Ok(Self { name, bones })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn bones(&self) -> &[AiSkeletonBone] {
+        &self.bones
+    }
+
+    /// Mutable access to the bones.
+    ///
+    /// The hierarchy itself can not be changed this way, only the data attached to each bone.
+    pub fn bones_mut(&mut self) -> impl Iterator<Item = &mut AiSkeletonBone> {
+        self.bones.iter_mut()
+    }
+
+    /// Returns the first bone without a parent.
+    pub fn root_bone(&self) -> Option<&AiSkeletonBone> {
+        self.bones.iter().find(|bone| bone.parent.is_none())
+    }
+
+    /// Finds the bone attached to the given node.
+    pub fn find_bone_by_node(&self, node_index: usize) -> Option<usize> {
+        self.bones
+            .iter()
+            .position(|bone| bone.node_index == node_index)
+    }
+
+    /// Returns whether both skeletons have the same bone hierarchy, ignoring the mesh each bone
+    /// deforms and its weights.
+    ///
+    /// A skin deforming several meshes is represented by one skeleton per mesh, these compare
+    /// equal.
+    pub fn same_bones(&self, other: &AiSkeleton) -> bool {
+        self.bones.len() == other.bones.len()
+            && self.bones.iter().zip(&other.bones).all(|(lhs, rhs)| {
+                lhs.parent == rhs.parent
+                    && lhs.node_index == rhs.node_index
+                    && lhs.armature_index == rhs.armature_index
+                    && lhs.offset_matrix == rhs.offset_matrix
+                    && lhs.local_matrix == rhs.local_matrix
+            })
+    }
+
+    /// Validates the skeleton against the scene it belongs to.
+    ///
+    /// Checks that all node, armature and mesh indices are in range and that all vertex
+    /// weights reference existing vertices of their mesh.
+    pub fn validate(&self, node_count: usize, meshes: &[AiMesh]) -> Result<(), AiSkeletonError> {
+        for (index, bone) in self.bones.iter().enumerate() {
+            if bone.node_index >= node_count {
+                return Err(AiSkeletonError::InvalidNode {
+                    bone: index,
+                    node: bone.node_index,
+                });
+            }
+            if let Some(armature) = bone.armature_index
+                && armature >= node_count
+            {
+                return Err(AiSkeletonError::InvalidNode {
+                    bone: index,
+                    node: armature,
+                });
+            }
+            match bone.mesh_index {
+                Some(mesh_index) => {
+                    let Some(mesh) = meshes.get(mesh_index) else {
+                        return Err(AiSkeletonError::InvalidMesh {
+                            bone: index,
+                            mesh: mesh_index,
+                        });
+                    };
+                    if let Some(weight) = bone
+                        .weights
+                        .iter()
+                        .find(|weight| weight.vertex_id >= mesh.vertices.len())
+                    {
+                        return Err(AiSkeletonError::InvalidVertex {
+                            bone: index,
+                            vertex: weight.vertex_id,
+                        });
+                    }
+                }
+                None if !bone.weights.is_empty() => {
+                    return Err(AiSkeletonError::WeightsWithoutMesh { bone: index });
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bone(parent: Option<usize>, node_index: usize) -> AiSkeletonBone {
+        AiSkeletonBone::new(
+            parent,
+            node_index,
+            AiMatrix4x4::identity(),
+            AiMatrix4x4::identity(),
+        )
+    }
+
+    #[test]
+    fn test_skeleton_hierarchy() {
+        let skeleton = AiSkeleton::new(
+            "skeleton".to_string(),
+            vec![bone(None, 0), bone(Some(0), 1)],
+        )
+        .unwrap();
+        assert_eq!(skeleton.root_bone().map(|x| x.node_index()), Some(0));
+        assert_eq!(skeleton.find_bone_by_node(1), Some(1));
+
+        assert_eq!(
+            AiSkeleton::new("skeleton".to_string(), vec![bone(Some(2), 0)]),
+            Err(AiSkeletonError::InvalidParent { bone: 0, parent: 2 })
+        );
+        assert_eq!(
+            AiSkeleton::new(
+                "skeleton".to_string(),
+                vec![bone(Some(1), 0), bone(Some(0), 1)]
+            ),
+            Err(AiSkeletonError::Cycle { bone: 0 })
+        );
+    }
+
+    #[test]
+    fn test_skeleton_same_bones() {
+        let skeleton = |mesh_index: usize| {
+            AiSkeleton::new(
+                "skeleton".to_string(),
+                vec![
+                    bone(None, 0),
+                    bone(Some(0), 1).with_mesh(mesh_index, vec![AiVertexWeight::new(0, 1.0)]),
+                ],
+            )
+            .unwrap()
+        };
+        assert!(skeleton(0).same_bones(&skeleton(1)));
+
+        let other =
+            AiSkeleton::new("skeleton".to_string(), vec![bone(None, 0), bone(None, 1)]).unwrap();
+        assert!(!skeleton(0).same_bones(&other));
+    }
+
+    #[test]
+    fn test_skeleton_validate() {
+        let mesh = AiMesh {
+            vertices: vec![AiVector3D::zero(); 2],
+            ..Default::default()
+        };
+        let skeleton = AiSkeleton::new(
+            "skeleton".to_string(),
+            vec![bone(None, 0).with_mesh(0, vec![AiVertexWeight::new(1, 1.0)])],
+        )
+        .unwrap();
+        assert_eq!(skeleton.validate(1, std::slice::from_ref(&mesh)), Ok(()));
+        assert_eq!(
+            skeleton.validate(0, std::slice::from_ref(&mesh)),
+            Err(AiSkeletonError::InvalidNode { bone: 0, node: 0 })
+        );
+
+        let skeleton = AiSkeleton::new(
+            "skeleton".to_string(),
+            vec![bone(None, 0).with_mesh(0, vec![AiVertexWeight::new(2, 1.0)])],
+        )
+        .unwrap();
+        assert_eq!(
+            skeleton.validate(1, &[mesh]),
+            Err(AiSkeletonError::InvalidVertex { bone: 0, vertex: 2 })
+        );
+    }
+}
@@ -39,7 +39,7 @@ pub enum InsertError {
     RootAlreadyExists,
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiNodeTree {
     pub root: Option<usize>,
     pub arena: Vec<AiNode>,
@@ -62,6 +62,32 @@ impl AiNodeTree {
         }
     }
 
+    /// Finds the first node with the given name.
+    ///
+    /// Cameras, lights and bones reference their node by name, so this is how they are
+    /// placed in the hierarchy.
+    pub fn find_node_by_name(&self, name: &str) -> Option<usize> {
+        self.arena.iter().position(|node| node.name == name)
+    }
+
+    /// Computes the transformation of a node relative to the root of the tree.
+    ///
+    /// Returns `None` if the index is out of range or the parent links form a cycle.
+    pub fn global_transform(&self, index: usize) -> Option<AiMatrix4x4> {
+        let mut node = self.arena.get(index)?;
+        let mut transform = node.transformation.clone();
+        let mut depth = 0;
+        while let Some(parent_index) = node.parent {
+            depth += 1;
+            if depth > self.arena.len() {
+                return None;
+            }
+            node = self.arena.get(parent_index)?;
+            transform = &node.transformation * &transform;
+        }
+        Some(transform)
+    }
+
     pub fn merge(&mut self, other: AiNodeTree) {
         let offset = self.arena.len();
         let mut new_root_indices: Vec<usize> = Vec::with_capacity(offset);
@@ -74,7 +100,7 @@ impl AiNodeTree {
                     node.parent = Some(parent_idx + offset);
                 } else {
                     node.parent = self.root;
-                    new_root_indices.push(index);
+                    new_root_indices.push(index + offset);
                 }
                 for child in &mut node.children {
                     *child += offset;
@@ -158,7 +184,7 @@ pub enum AiSceneFlag {
 /// * `cameras` - Collection of all cameras in the scene
 /// * `skeletons` - Collection of all skeletal animations
 /// * `metadata` - Additional scene metadata and properties
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct AiScene {
     pub name: String,
     pub flags: BitFlags<AiSceneFlag>,
@@ -1,6 +1,9 @@
 use std::io::Cursor;
 
-use image::{ColorType, ImageError, ImageFormat, write_buffer_with_format};
+use image::{
+    ColorType, DynamicImage, ImageError, ImageFormat, load_from_memory_with_format,
+    write_buffer_with_format,
+};
 
 use super::color::AiColor4D;
 
@@ -105,6 +108,10 @@ pub struct AiTexture {
     pub height: u32,
     pub ach_format_hint: AiTextureFormat,
     pub texel: Vec<AiTexel>,
+    /// The file contents of a compressed texture, in the format of `ach_format_hint`.
+    ///
+    /// Empty for uncompressed textures, whose content is stored in `texel`.
+    pub compressed_data: Vec<u8>,
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -127,9 +134,32 @@ impl AiTexture {
             height,
             ach_format_hint,
             texel,
+            compressed_data: Vec::new(),
         }
     }
 
+    /// Creates a texture that keeps the compressed file contents instead of texels.
+    pub fn from_compressed(
+        filename: String,
+        width: u32,
+        height: u32,
+        ach_format_hint: AiTextureFormat,
+        compressed_data: Vec<u8>,
+    ) -> Self {
+        AiTexture {
+            filename,
+            width,
+            height,
+            ach_format_hint,
+            texel: Vec::new(),
+            compressed_data,
+        }
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        !self.compressed_data.is_empty()
+    }
+
     pub fn get_approved_format(&self, approved_formats: &[AiTextureFormat]) -> AiTextureFormat {
         if approved_formats.is_empty() {
             return self.ach_format_hint;
@@ -148,6 +178,23 @@ impl AiTexture {
     ) -> Result<TextureExport, ImageError> {
         let format = self.get_approved_format(approved_formats);
         let mut data: Vec<u8> = Vec::with_capacity((self.width * self.height * 4) as usize);
+        if self.is_compressed() {
+            // Compressed textures are only re-encoded if their format is not approved
+            if format == self.ach_format_hint {
+                return Ok(TextureExport {
+                    data: self.compressed_data.clone(),
+                    format,
+                });
+            }
+            let mut image =
+                load_from_memory_with_format(&self.compressed_data, self.ach_format_hint.into())?;
+            if format == AiTextureFormat::JPEG {
+                // JPEG has no alpha channel
+                image = DynamicImage::from(image.to_rgb8());
+            }
+            image.write_to(&mut Cursor::new(&mut data), format.into())?;
+            return Ok(TextureExport { data, format });
+        }
         let byte_slice: &[u8] = unsafe {
             std::slice::from_raw_parts(
                 self.texel.as_ptr() as *const u8,
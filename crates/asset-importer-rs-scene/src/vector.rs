@@ -197,6 +197,36 @@ impl AiVector3D {
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    /// Component-wise minimum of two vectors.
+    pub fn min(&self, other: &Self) -> Self {
+        AiVector3D {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+            z: self.z.min(other.z),
+        }
+    }
+
+    /// Component-wise maximum of two vectors.
+    pub fn max(&self, other: &Self) -> Self {
+        AiVector3D {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+            z: self.z.max(other.z),
+        }
+    }
+}
+
+impl ops::Add for AiVector3D {
+    type Output = AiVector3D;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        AiVector3D {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
 }
 
 impl ops::AddAssign<AiVector3D> for AiVector3D {
@@ -207,6 +237,18 @@ impl ops::AddAssign<AiVector3D> for AiVector3D {
     }
 }
 
+impl ops::Neg for AiVector3D {
+    type Output = AiVector3D;
+
+    fn neg(self) -> Self::Output {
+        AiVector3D {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 impl ops::BitXor<AiVector3D> for AiVector3D {
     type Output = AiVector3D;
 
@@ -251,6 +293,7 @@ impl ops::DivAssign<AiReal> for AiVector3D {
     fn div_assign(&mut self, rhs: AiReal) {
         self.x /= rhs;
         self.y /= rhs;
+        self.z /= rhs;
     }
 }
 
@@ -41,6 +41,8 @@ impl Error for AiImporterError {}
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum AiExporterError {
+    UnsupportedFileExtension(String),
+    UnsupportedFormat(String),
     #[cfg(feature = "gltf")]
     GltfExportError(asset_importer_rs_gltf_v1::GltfExportError),
     #[cfg(feature = "gltf2")]
@@ -52,6 +54,12 @@ pub enum AiExporterError {
 impl Display for AiExporterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            AiExporterError::UnsupportedFileExtension(extension) => {
+                write!(f, "Unsupported file extension: {}", extension)
+            }
+            AiExporterError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported export format: {}", format)
+            }
             #[cfg(feature = "gltf")]
             AiExporterError::GltfExportError(error) => write!(f, "GltfExportError: {}", error),
             #[cfg(feature = "gltf2")]
@@ -1,6 +1,11 @@
+use std::path::Path;
+
 use enumflags2::BitFlags;
 
-use asset_importer_rs_core::{AiExport, AiPostProcessSteps};
+use asset_importer_rs_core::{AiExport, AiExportExt, AiPostProcessSteps, ExportProperties};
+#[cfg(feature = "post-process")]
+use asset_importer_rs_post_process::AiPostProcesser;
+use asset_importer_rs_scene::AiScene;
 
 use crate::error::AiExporterError;
 
@@ -44,4 +49,56 @@ impl Exporter {
     pub fn new(exporters: Vec<ExportFormatEntry>) -> Self {
         Self { exporters }
     }
+
+    /// Exports a scene with the first format matching the extension of the file.
+    pub fn export_file(&self, scene: &AiScene, file_path: &str) -> Result<(), AiExporterError> {
+        let extension = Path::new(file_path).extension().unwrap_or_default();
+        let extension = extension.to_str().unwrap_or_default();
+
+        let entry = self
+            .exporters
+            .iter()
+            .find(|entry| entry.extension == extension)
+            .ok_or_else(|| AiExporterError::UnsupportedFileExtension(extension.to_string()))?;
+        Self::export_entry(entry, scene, file_path)
+    }
+
+    /// Exports a scene with the format of the given name.
+    pub fn export_file_as(
+        &self,
+        scene: &AiScene,
+        format: &str,
+        file_path: &str,
+    ) -> Result<(), AiExporterError> {
+        let entry = self
+            .exporters
+            .iter()
+            .find(|entry| entry.name == format)
+            .ok_or_else(|| AiExporterError::UnsupportedFormat(format.to_string()))?;
+        Self::export_entry(entry, scene, file_path)
+    }
+
+    /// Runs the post processing steps enforced by the format on a copy of the scene, then
+    /// exports it.
+    fn export_entry(
+        entry: &ExportFormatEntry,
+        scene: &AiScene,
+        file_path: &str,
+    ) -> Result<(), AiExporterError> {
+        let properties = ExportProperties::new();
+        #[cfg(feature = "post-process")]
+        if !entry.enforced_post_process_steps.is_empty() {
+            let mut scene = scene.clone();
+            let mut post_process = AiPostProcesser::post_process();
+            post_process
+                .process(&mut scene, entry.enforced_post_process_steps)
+                .map_err(AiExporterError::PostProcessError)?;
+            return entry
+                .exporter
+                .export_file_default(&scene, file_path, &properties);
+        }
+        entry
+            .exporter
+            .export_file_default(scene, file_path, &properties)
+    }
 }
@@ -4,8 +4,8 @@ use std::{
     path::Path,
 };
 
-use asset_importer_rs_core::{AiImporter, AiImporterExt, ReadSeek};
-use asset_importer_rs_scene::AiScene;
+use asset_importer_rs_core::{AI_METADATA_SOURCE_FILE_PATH, AiImporter, AiImporterExt, ReadSeek};
+use asset_importer_rs_scene::{AiMetadataEntry, AiScene};
 
 use crate::error::AiImporterError;
 
@@ -32,7 +32,12 @@ impl Importer {
             }
 
             if importer.can_read_default(path) {
-                return importer.read_file_default(path);
+                let mut scene = importer.read_file_default(path)?;
+                scene
+                    .metadata
+                    .entry(AI_METADATA_SOURCE_FILE_PATH.to_string())
+                    .or_insert_with(|| AiMetadataEntry::AiStr(file_path.to_string()));
+                return Ok(scene);
             }
         }
 
@@ -69,7 +69,7 @@ impl AssetImporter {
                 "gltf".to_string(),
                 "GL Transmission Format".to_string(),
                 "gltf".to_string(),
-                BitFlags::empty(),
+                BitFlags::from(AiPostProcessSteps::SplitLargeMeshes),
             ),
             #[cfg(feature = "gltf")]
             ExportFormatEntry::new(
@@ -77,7 +77,7 @@ impl AssetImporter {
                 "glb".to_string(),
                 "GL Transmission Format (binary)".to_string(),
                 "glb".to_string(),
-                BitFlags::empty(),
+                BitFlags::from(AiPostProcessSteps::SplitLargeMeshes),
             ),
         ])
     }
@@ -101,22 +101,22 @@ impl AssetImporter {
         Ok(scene)
     }
 
-    pub fn export_file(_scene: &AiScene, _file_path: &str) -> Result<(), AiExporterError> {
-        let _exporter = AssetImporter::exporter();
-        Ok(())
+    pub fn export_file(scene: &AiScene, file_path: &str) -> Result<(), AiExporterError> {
+        let exporter = AssetImporter::exporter();
+        exporter.export_file(scene, file_path)
     }
 
     #[cfg(feature = "post-process")]
     pub fn export_file_with_post_process(
         scene: &mut AiScene,
-        _file_path: &str,
+        file_path: &str,
         flags: BitFlags<AiPostProcessSteps>,
     ) -> Result<(), AiExporterError> {
         let mut post_process = AiPostProcesser::post_process();
         post_process
             .process(scene, flags)
             .map_err(AiExporterError::PostProcessError)?;
-        let _exporter = AssetImporter::exporter();
-        Ok(())
+        let exporter = AssetImporter::exporter();
+        exporter.export_file(scene, file_path)
     }
 }
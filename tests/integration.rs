@@ -6,3 +6,45 @@ fn test_importer_gltf2() {
     assert!(scene.is_ok());
     assert_eq!(scene.unwrap().name, "");
 }
+
+#[cfg(all(feature = "gltf", feature = "post-process"))]
+#[test]
+fn test_exporter_gltf_splits_large_meshes() {
+    use asset_importer_rs_scene::{AiMaterial, AiMesh, AiNode, AiPrimitiveType, AiVector3D};
+
+    // A triangle strip with more vertices than 16 bit indices can address
+    let vertex_count = usize::from(u16::MAX) + 10;
+    let mesh = AiMesh {
+        name: "strip".to_string(),
+        primitive_types: AiPrimitiveType::Triangle.into(),
+        vertices: (0..vertex_count)
+            .map(|index| AiVector3D::new((index / 2) as f32, (index % 2) as f32, 0.0))
+            .collect(),
+        faces: (0..vertex_count - 2)
+            .map(|index| vec![index, index + 1, index + 2])
+            .collect(),
+        ..Default::default()
+    };
+    let mut scene = asset_importer_rs_scene::AiScene {
+        meshes: vec![mesh],
+        materials: vec![AiMaterial::default()],
+        ..Default::default()
+    };
+    scene
+        .nodes
+        .insert(
+            AiNode {
+                name: "root".to_string(),
+                mesh_indexes: vec![0],
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+
+    let exporter = AssetImporter::exporter();
+    let result = exporter.export_file_as(&scene, "gltf", "tests/output/gltf/large_mesh.gltf");
+    assert!(result.is_ok(), "{:?}", result.err());
+    // The scene itself is left unsplit
+    assert_eq!(scene.meshes.len(), 1);
+}